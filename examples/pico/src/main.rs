@@ -116,7 +116,7 @@ fn main() -> ! {
             .unwrap();
         info!("power_saving_mode: {}", power_saving_mode);
         let battery_charge = modem
-            .send_and_wait_reply(&at_command::battery::BatteryCharge {})
+            .send_and_wait_response(&at_command::battery::QueryBatteryCharge)
             .unwrap();
         info!("battery_charge: {}", battery_charge);
         let gprs_status = modem
@@ -1,6 +1,6 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{verify_ok, AtRequest, BufferType};
 use crate::AtError;
 #[cfg(feature = "defmt")]
 use defmt::{error, info};
@@ -44,7 +44,13 @@ impl AtRequest for At {
         Ok(AtResponse::Ok)
     }
 
+    /// Accepts the plain OK of a modem with echo disabled as well as the reply sent while it
+    /// boots
     fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        if verify_ok(data).is_ok() {
+            return Ok(());
+        }
+
         Self::get_command_response(data)?;
         Ok(())
     }
@@ -1,6 +1,7 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{verify_ok, AtRequest, BufferType};
+use crate::rate_limit::CommandKind;
 use crate::AtError;
 use at_commands::parser::CommandParser;
 
@@ -59,3 +60,34 @@ impl AtRequest for GPRSServiceStatus {
         Ok(PacketDomainAttachmentState { state })
     }
 }
+
+/// Attaches to or detaches from the packet domain with `AT+CGATT`. The modem answers busy
+/// errors if it is issued too often, it is spaced out as [CommandKind::Attach] by the
+/// [RateLimiter](crate::rate_limit::RateLimiter).
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetGPRSServiceState {
+    pub state: GPRSServiceState,
+}
+
+impl AtRequest for SetGPRSServiceState {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let state = match self.state {
+            GPRSServiceState::Detached => 0,
+            GPRSServiceState::Attached => 1,
+        };
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGATT")
+            .with_int_parameter(state)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+
+    fn command_kind(&self) -> Option<CommandKind> {
+        Some(CommandKind::Attach)
+    }
+}
@@ -8,7 +8,7 @@ use crate::AtError;
 pub struct PINRequired;
 
 /// Indicates the response of [PinRequired]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PinStatus {
     Ready,
@@ -77,6 +77,18 @@ impl From<&str> for PinStatus {
     }
 }
 
+impl PinStatus {
+    /// Parses the `+CPIN: <code>` URC sent once the SIM is initialized after booting
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (code,) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CPIN: ")
+            .expect_raw_string()
+            .finish()?;
+
+        Ok(code.into())
+    }
+}
+
 impl PINRequired {
     fn get_pin_response(data: &[u8]) -> Result<PinStatus, AtError> {
         let response_code = at_commands::parser::CommandParser::parse(data)
@@ -153,7 +165,7 @@ mod test {
             _ => panic!("Unexpected response type"),
         }
 
-        return Ok(());
+        Ok(())
     }
 
     #[test]
@@ -171,7 +183,7 @@ mod test {
             _ => panic!("Unexpected response type"),
         }
 
-        return Ok(());
+        Ok(())
     }
 
     #[test]
@@ -189,7 +201,7 @@ mod test {
             _ => panic!("Unexpected response type"),
         }
 
-        return Ok(());
+        Ok(())
     }
 
     #[test]
@@ -207,7 +219,7 @@ mod test {
             _ => panic!("Unexpected response type"),
         }
 
-        return Ok(());
+        Ok(())
     }
 
     #[test]
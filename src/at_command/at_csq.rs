@@ -1,6 +1,7 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
 use crate::at_command::{AtRequest, BufferType};
+use crate::conversions::csq_rssi_to_dbm;
 use crate::AtError;
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -11,6 +12,15 @@ pub struct SignalQualityResponse {
     pub rx_quality: i32,
 }
 
+impl SignalQualityResponse {
+    /// Signal strength in dBm, `None` if the modem does not know it
+    pub fn rssi_dbm(&self) -> Option<i16> {
+        u8::try_from(self.rx_signal_strength)
+            .ok()
+            .and_then(csq_rssi_to_dbm)
+    }
+}
+
 impl SignalQualityReport {
     fn get_signal_response(data: &[u8]) -> Result<(i32, i32), AtError> {
         // \r\n+CSQ: 24,0\r\n\r\nOK\r\n
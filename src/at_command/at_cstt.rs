@@ -1,4 +1,4 @@
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{AtRequest, BufferType, FinishExact};
 use crate::AtError;
 
 const CSTT_SIZE_MAX: usize = 32; // AT Datasheet page 172
@@ -62,7 +62,7 @@ impl AtRequest for SetAPNUserPassword {
             .with_optional_string_parameter(self.apn)
             .with_optional_string_parameter(self.user)
             .with_optional_string_parameter(self.password)
-            .finish()
+            .finish_exact()
     }
 
     fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
@@ -1,6 +1,6 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{strip_ok, AtRequest, BufferType};
 use crate::AtError;
 use at_commands::parser::CommandParser;
 
@@ -11,9 +11,18 @@ pub struct BatteryChargeStatus {
     voltage_millivolt: i32,
 }
 
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[deprecated(note = "use QueryBatteryCharge")]
 pub struct BatteryCharge;
 
+#[cfg(feature = "defmt")]
+#[allow(deprecated)]
+impl defmt::Format for BatteryCharge {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "BatteryCharge")
+    }
+}
+
+#[allow(deprecated)]
 impl BatteryCharge {
     fn get_battery_charge_status(data: &[u8]) -> Result<BatteryChargeStatus, AtError> {
         let (capacity_percent, voltage_millivolt) = CommandParser::parse(data)
@@ -31,6 +40,7 @@ impl BatteryCharge {
     }
 }
 
+#[allow(deprecated)]
 impl AtRequest for BatteryCharge {
     type Response = BatteryChargeStatus;
 
@@ -50,3 +60,116 @@ impl AtRequest for BatteryCharge {
         Self::get_battery_charge_status(data)
     }
 }
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeStatus {
+    NotCharging,
+    Charging,
+    Finished,
+    Unknown,
+}
+
+impl From<i32> for ChargeStatus {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => ChargeStatus::NotCharging,
+            1 => ChargeStatus::Charging,
+            2 => ChargeStatus::Finished,
+            _ => ChargeStatus::Unknown,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryChargeReport {
+    /// Not reported by every firmware
+    pub charge_status: Option<ChargeStatus>,
+    /// Not reported by every firmware
+    pub capacity_percent: Option<u8>,
+    pub voltage_millivolt: u16,
+}
+
+/// Reads the battery charge and supply voltage. Depending on the firmware the modem replies
+/// `+CBC: <bcs>,<bcl>,<voltage>`, `+CBC: <bcl>,<voltage>` or only `+CBC: <voltage>`.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryBatteryCharge;
+
+impl QueryBatteryCharge {
+    fn get_report(data: &[u8]) -> Result<BatteryChargeReport, AtError> {
+        let (first, second, third) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CBC: ")
+            .expect_int_parameter()
+            .expect_optional_int_parameter()
+            .expect_optional_int_parameter()
+            .finish()?;
+
+        let (charge_status, capacity_percent, voltage_millivolt) = match (second, third) {
+            (Some(capacity), Some(voltage)) => (Some(first), Some(capacity), voltage),
+            (Some(voltage), None) => (None, Some(first), voltage),
+            (None, None) => (None, None, first),
+            (None, Some(_)) => return Err(AtError::AtParseError),
+        };
+
+        let capacity_percent = capacity_percent
+            .map(|capacity| match capacity {
+                0..=100 => Ok(capacity as u8),
+                _ => Err(AtError::AtParseError),
+            })
+            .transpose()?;
+
+        Ok(BatteryChargeReport {
+            charge_status: charge_status.map(ChargeStatus::from),
+            capacity_percent,
+            voltage_millivolt: u16::try_from(voltage_millivolt)
+                .map_err(|_| AtError::AtParseError)?,
+        })
+    }
+}
+
+impl AtRequest for QueryBatteryCharge {
+    type Response = BatteryChargeReport;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CBC")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_report(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query_battery_charge_request() {
+        let mut buffer = [0; 512];
+        let result = QueryBatteryCharge.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CBC\r\n");
+    }
+
+    #[test]
+    fn test_query_battery_charge_three_fields() {
+        let report = QueryBatteryCharge
+            .parse_response_struct(b"\r\n+CBC: 1,75,3900\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(report.charge_status, Some(ChargeStatus::Charging));
+        assert_eq!(report.capacity_percent, Some(75));
+        assert_eq!(report.voltage_millivolt, 3900);
+    }
+
+    #[test]
+    fn test_query_battery_charge_voltage_only() {
+        let report = QueryBatteryCharge
+            .parse_response_struct(b"\r\n+CBC: 3300\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(report.charge_status, None);
+        assert_eq!(report.capacity_percent, None);
+        assert_eq!(report.voltage_millivolt, 3300);
+    }
+}
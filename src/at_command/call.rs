@@ -0,0 +1,320 @@
+use crate::at_command::{strip_ok, trim_line_breaks, verify_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Longest number accepted by [Dial]
+pub const MAX_DIAL_NUMBER_LEN: usize = 20;
+
+/// Starts a voice call with `ATD<number>;`, on modules which support voice. A call which
+/// cannot be established ends the command with [AtError::CallFailed], the end of an
+/// established call is reported with [CallProgress].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Dial<'a> {
+    pub(crate) number: &'a str,
+}
+
+impl<'a> Dial<'a> {
+    /// The number is made of digits, `*` and `#`, optionally starting with `+`
+    pub fn new(number: &'a str) -> Result<Self, AtError> {
+        let digits = number.strip_prefix('+').unwrap_or(number);
+        if digits.is_empty()
+            || number.len() > MAX_DIAL_NUMBER_LEN
+            || !digits
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b == b'*' || b == b'#')
+        {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { number })
+    }
+}
+
+impl AtRequest for Dial<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let parts: [&[u8]; 3] = [b"ATD", self.number.as_bytes(), b";\r\n"];
+        let size = parts.iter().map(|part| part.len()).sum();
+        if size > buffer.len() {
+            return Err(size);
+        }
+
+        let mut offset = 0;
+        for part in parts {
+            buffer[offset..offset + part.len()].copy_from_slice(part);
+            offset += part.len();
+        }
+        Ok(&buffer[..size])
+    }
+
+    fn is_final_result(&self, line: &[u8]) -> bool {
+        CallFailure::parse(line).is_some()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let last_line = trim_line_breaks(data)
+            .rsplit(|b| *b == b'\n')
+            .next()
+            .unwrap_or_default();
+        if let Some(failure) = CallFailure::parse(trim_line_breaks(last_line)) {
+            return Err(AtError::CallFailed(failure));
+        }
+
+        verify_ok(data)
+    }
+}
+
+/// Final result of [Dial] when the call could not be established
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallFailure {
+    /// `NO CARRIER`
+    NoCarrier,
+    /// `BUSY`
+    Busy,
+    /// `NO ANSWER`
+    NoAnswer,
+}
+
+impl CallFailure {
+    fn parse(line: &[u8]) -> Option<Self> {
+        match line {
+            b"NO CARRIER" => Some(CallFailure::NoCarrier),
+            b"BUSY" => Some(CallFailure::Busy),
+            b"NO ANSWER" => Some(CallFailure::NoAnswer),
+            _ => None,
+        }
+    }
+}
+
+/// Answers an incoming call with `ATA`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Answer;
+
+impl AtRequest for Answer {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("A")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Ends the current call with `ATH`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Hangup;
+
+impl AtRequest for Hangup {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("H")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Makes the modem answer incoming calls by itself after a number of rings with `ATS0=<n>`,
+/// on modules which support incoming calls. `0` disables the automatic answer.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetAutoAnswer {
+    pub(crate) rings: u8,
+}
+
+impl SetAutoAnswer {
+    /// Answers after the given number of rings, `0` disables the automatic answer
+    pub fn new(rings: u8) -> Self {
+        Self { rings }
+    }
+
+    pub fn disable() -> Self {
+        Self { rings: 0 }
+    }
+}
+
+impl AtRequest for SetAutoAnswer {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("S0")
+            .with_int_parameter(self.rings as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads the number of rings after which an incoming call is answered with `ATS0?`, `0` means
+/// the automatic answer is disabled
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetAutoAnswer;
+
+impl AtRequest for GetAutoAnswer {
+    type Response = u8;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("S0")
+            .finish()
+    }
+
+    /// S-registers are answered with the bare value, which may be zero padded, e.g. `003`
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        core::str::from_utf8(strip_ok(data)?)
+            .map_err(|_| AtError::AtParseError)?
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| AtError::AtParseError)
+    }
+}
+
+/// Unsolicited message sent by the modem about an incoming call or the end of a call
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallProgress {
+    /// `RING`, an incoming call which can be accepted with [Answer]
+    Ring,
+    /// `NO CARRIER`, the call ended or could not be established
+    NoCarrier,
+    /// `BUSY`
+    Busy,
+    /// `NO ANSWER`
+    NoAnswer,
+}
+
+impl CallProgress {
+    /// Returns `None` if the line is not a call progress message
+    pub(crate) fn parse(data: &[u8]) -> Option<Self> {
+        match data {
+            b"RING" => Some(CallProgress::Ring),
+            b"NO CARRIER" => Some(CallProgress::NoCarrier),
+            b"BUSY" => Some(CallProgress::Busy),
+            b"NO ANSWER" => Some(CallProgress::NoAnswer),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockTimer, MockWriter};
+    use crate::urc::{Urc, UrcKind};
+    use crate::Modem;
+
+    #[test]
+    fn test_dial_command() {
+        let mut buffer = [0; 512];
+
+        let request = Dial::new("+34600111222").unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "ATD+34600111222;\r\n"
+        );
+
+        let result = Answer.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "ATA\r\n");
+        let result = Hangup.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "ATH\r\n");
+    }
+
+    #[test]
+    fn test_dial_busy() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nBUSY\r\n\r\nRING\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        assert!(matches!(
+            modem.send_and_wait_response(&Dial::new("600111222").unwrap()),
+            Err(AtError::CallFailed(CallFailure::Busy))
+        ));
+        // What follows the result is left for the next read
+        assert!(matches!(
+            modem.wait_for_urc(UrcKind::CallProgress, 1000, &MockTimer::new(10)),
+            Ok(Urc::CallProgress(CallProgress::Ring))
+        ));
+    }
+
+    #[test]
+    fn test_dial_failures() {
+        let request = Dial::new("600111222").unwrap();
+        for (response, failure) in [
+            (&b"\r\nNO CARRIER\r\n"[..], CallFailure::NoCarrier),
+            (b"\r\nNO ANSWER\r\n", CallFailure::NoAnswer),
+        ] {
+            assert!(matches!(
+                request.parse_response_struct(response),
+                Err(AtError::CallFailed(f)) if f == failure
+            ));
+        }
+        assert!(request.parse_response_struct(b"\r\nOK\r\n").is_ok());
+    }
+
+    #[test]
+    fn test_dial_invalid_number() {
+        for number in [
+            "",
+            "+",
+            "600 111 222",
+            "600111222;ATH",
+            "123456789012345678901",
+        ] {
+            assert!(matches!(Dial::new(number), Err(AtError::InvalidParameter)));
+        }
+        assert!(Dial::new("*100#").is_ok());
+    }
+
+    #[test]
+    fn test_set_auto_answer() {
+        let mut buffer = [0; 512];
+
+        let request = SetAutoAnswer::new(3);
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "ATS0=3\r\n");
+
+        let request = SetAutoAnswer::disable();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "ATS0=0\r\n");
+
+        let request = SetAutoAnswer::new(u8::MAX);
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "ATS0=255\r\n");
+    }
+
+    #[test]
+    fn test_get_auto_answer() {
+        let mut buffer = [0; 512];
+        let result = GetAutoAnswer.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "ATS0?\r\n");
+
+        assert_eq!(
+            GetAutoAnswer
+                .parse_response_struct(b"\r\n3\r\n\r\nOK\r\n")
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            GetAutoAnswer
+                .parse_response_struct(b"\r\n000\r\n\r\nOK\r\n")
+                .unwrap(),
+            0
+        );
+        assert!(GetAutoAnswer
+            .parse_response_struct(b"\r\n256\r\n\r\nOK\r\n")
+            .is_err());
+        assert!(GetAutoAnswer.parse_response_struct(b"\r\nOK\r\n").is_err());
+    }
+}
@@ -1,8 +1,9 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{strip_ok, verify_ok, AtRequest, BufferType};
 use crate::AtError;
 use chrono::NaiveDateTime;
+use core::time::Duration;
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Clock {}
@@ -41,3 +42,233 @@ impl AtRequest for Clock {
         Ok(timestamp)
     }
 }
+
+/// Enables the automatic time and time zone update from the network (NITZ). Changes are
+/// reported with [TimeZoneChanged].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetTimeZoneUpdate {
+    pub enable: bool,
+}
+
+impl AtRequest for SetTimeZoneUpdate {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CTZU")
+            .with_int_parameter(self.enable as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Unsolicited message sent by the modem when the network reports a time zone change
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZoneChanged {
+    /// Offset from UTC in quarters of an hour, daylight saving time included
+    pub quarter_hours: i8,
+    /// Hours added by the daylight saving time, 0 if not reported
+    pub daylight_saving_hours: u8,
+}
+
+impl TimeZoneChanged {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (time_zone, daylight_saving_hours) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CTZV: ")
+            .expect_raw_string_parameter()
+            .expect_optional_int_parameter()
+            .finish()?;
+
+        let quarter_hours = time_zone
+            .trim_matches('"')
+            .parse::<i8>()
+            .map_err(|_| AtError::AtParseError)?;
+        // At most UTC-12:00 and UTC+14:00
+        if !(-48..=56).contains(&quarter_hours) {
+            return Err(AtError::AtParseError);
+        }
+
+        let daylight_saving_hours = match daylight_saving_hours {
+            None => 0,
+            Some(hours @ 0..=2) => hours as u8,
+            Some(_) => return Err(AtError::AtParseError),
+        };
+
+        Ok(TimeZoneChanged {
+            quarter_hours,
+            daylight_saving_hours,
+        })
+    }
+
+    /// Local time offset from UTC in minutes, daylight saving time included
+    pub fn offset_minutes(&self) -> i16 {
+        self.quarter_hours as i16 * 15
+    }
+
+    /// Offset from UTC in minutes without the daylight saving time
+    pub fn standard_offset_minutes(&self) -> i16 {
+        self.offset_minutes() - self.daylight_saving_hours as i16 * 60
+    }
+}
+
+/// Sets whether the module applies the daylight saving time reported by the network to its
+/// clock with `AT+CTZDST`. When it does not, [Clock] returns the standard time and
+/// [TimeZoneChanged::daylight_saving_hours] has to be added by the application.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetDaylightSaving {
+    pub apply: bool,
+}
+
+impl AtRequest for SetDaylightSaving {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CTZDST")
+            .with_int_parameter(self.apply as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads whether the daylight saving time is applied to the clock, see [SetDaylightSaving]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetDaylightSaving;
+
+impl AtRequest for GetDaylightSaving {
+    /// Whether it is applied
+    type Response = bool;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CTZDST")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let (apply,) = at_commands::parser::CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CTZDST: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        match apply {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(AtError::AtParseError),
+        }
+    }
+}
+
+/// Reads the time since the module booted with `AT+CUPTIME`, e.g. to correlate logs or to
+/// detect unexpected resets
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryUptime;
+
+impl AtRequest for QueryUptime {
+    type Response = Duration;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CUPTIME")
+            .finish()
+    }
+
+    /// Parses `+CUPTIME: <seconds>`
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let (seconds,) = at_commands::parser::CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CUPTIME: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        let seconds = u64::try_from(seconds).map_err(|_| AtError::AtParseError)?;
+        Ok(Duration::from_secs(seconds))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+    use crate::Modem;
+
+    #[test]
+    fn test_set_time_zone_update_request() {
+        let mut buffer = [0; 512];
+        let request = SetTimeZoneUpdate { enable: true };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CTZU=1\r\n");
+
+        let request = SetTimeZoneUpdate { enable: false };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CTZU=0\r\n");
+    }
+
+    #[test]
+    fn test_parse_time_zone_changed() {
+        let report = TimeZoneChanged::parse(b"+CTZV: +8,1").unwrap();
+        assert_eq!(report.quarter_hours, 8);
+        assert_eq!(report.daylight_saving_hours, 1);
+        assert_eq!(report.offset_minutes(), 120);
+        assert_eq!(report.standard_offset_minutes(), 60);
+
+        let report = TimeZoneChanged::parse(b"+CTZV: \"-14\"").unwrap();
+        assert_eq!(report.offset_minutes(), -210);
+        assert_eq!(report.standard_offset_minutes(), -210);
+
+        assert!(TimeZoneChanged::parse(b"+CTZV: +99,0").is_err());
+        assert!(TimeZoneChanged::parse(b"+CTZV: +8,5").is_err());
+    }
+
+    #[test]
+    fn test_set_daylight_saving_request() {
+        let mut buffer = [0; 512];
+        let request = SetDaylightSaving { apply: false };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CTZDST=0\r\n");
+    }
+
+    #[test]
+    fn test_parse_daylight_saving() {
+        let mut buffer = [0; 512];
+        let result = GetDaylightSaving.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CTZDST?\r\n");
+
+        assert!(GetDaylightSaving
+            .parse_response_struct(b"\r\n+CTZDST: 1\r\n\r\nOK\r\n")
+            .unwrap());
+        assert!(!GetDaylightSaving
+            .parse_response_struct(b"\r\n+CTZDST: 0\r\n\r\nOK\r\n")
+            .unwrap());
+        assert!(GetDaylightSaving
+            .parse_response_struct(b"\r\n+CTZDST: 2\r\n\r\nOK\r\n")
+            .is_err());
+    }
+
+    #[test]
+    fn test_query_uptime() {
+        let mut buffer = [0; 512];
+        let result = QueryUptime.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CUPTIME\r\n");
+
+        let uptime = QueryUptime
+            .parse_response_struct(b"\r\n+CUPTIME: 86461\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(uptime, Duration::from_secs(86461));
+    }
+
+    #[test]
+    fn test_query_uptime_unsupported() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CME ERROR: 4\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        assert_eq!(modem.send_optional(&QueryUptime).unwrap(), None);
+    }
+}
@@ -106,3 +106,122 @@ impl AtRequest for SetReportMobileEquipmentError {
         Ok(())
     }
 }
+
+/// Header of the final result code sent instead of `ERROR` once enabled with
+/// [SetReportMobileEquipmentError]
+const CME_ERROR_PREFIX: &[u8] = b"+CME ERROR: ";
+
+/// Returns the code of a `+CME ERROR: <n>` line, `None` if it is not one. Verbose errors are
+/// reported as `Some(None)` as they do not carry the code.
+pub(crate) fn parse_cme_error(line: &[u8]) -> Option<Option<u16>> {
+    let code = crate::at_command::trim_line_breaks(line).strip_prefix(CME_ERROR_PREFIX)?;
+
+    Some(
+        core::str::from_utf8(code)
+            .ok()
+            .and_then(|code| code.parse().ok()),
+    )
+}
+
+/// Mobile equipment error codes reported in `+CME ERROR: <n>`, see [AtError::Cme]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmeError {
+    PhoneFailure,
+    OperationNotAllowed,
+    OperationNotSupported,
+    SimNotInserted,
+    SimPinRequired,
+    SimPukRequired,
+    SimFailure,
+    SimBusy,
+    SimWrong,
+    IncorrectPassword,
+    MemoryFull,
+    InvalidIndex,
+    NotFound,
+    NoNetworkService,
+    NetworkTimeout,
+    EmergencyCallsOnly,
+    IncorrectParameters,
+    Unknown(u16),
+}
+
+impl CmeError {
+    pub fn from_code(code: u16) -> Self {
+        match code {
+            0 => CmeError::PhoneFailure,
+            3 => CmeError::OperationNotAllowed,
+            4 => CmeError::OperationNotSupported,
+            10 => CmeError::SimNotInserted,
+            11 => CmeError::SimPinRequired,
+            12 => CmeError::SimPukRequired,
+            13 => CmeError::SimFailure,
+            14 => CmeError::SimBusy,
+            15 => CmeError::SimWrong,
+            16 => CmeError::IncorrectPassword,
+            20 => CmeError::MemoryFull,
+            21 => CmeError::InvalidIndex,
+            22 => CmeError::NotFound,
+            30 => CmeError::NoNetworkService,
+            31 => CmeError::NetworkTimeout,
+            32 => CmeError::EmergencyCallsOnly,
+            50 => CmeError::IncorrectParameters,
+            code => CmeError::Unknown(code),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            CmeError::PhoneFailure => "phone failure",
+            CmeError::OperationNotAllowed => "operation not allowed",
+            CmeError::OperationNotSupported => "operation not supported",
+            CmeError::SimNotInserted => "SIM not inserted",
+            CmeError::SimPinRequired => "SIM PIN required",
+            CmeError::SimPukRequired => "SIM PUK required",
+            CmeError::SimFailure => "SIM failure",
+            CmeError::SimBusy => "SIM busy",
+            CmeError::SimWrong => "SIM wrong",
+            CmeError::IncorrectPassword => "incorrect password",
+            CmeError::MemoryFull => "memory full",
+            CmeError::InvalidIndex => "invalid index",
+            CmeError::NotFound => "not found",
+            CmeError::NoNetworkService => "no network service",
+            CmeError::NetworkTimeout => "network timeout",
+            CmeError::EmergencyCallsOnly => "network not allowed, emergency calls only",
+            CmeError::IncorrectParameters => "incorrect parameters",
+            CmeError::Unknown(_) => "unknown error",
+        }
+    }
+}
+
+impl From<u16> for CmeError {
+    fn from(code: u16) -> Self {
+        CmeError::from_code(code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cme_error_from_code() {
+        assert_eq!(CmeError::from_code(10), CmeError::SimNotInserted);
+        assert_eq!(CmeError::from_code(10).description(), "SIM not inserted");
+        assert_eq!(CmeError::from_code(30), CmeError::NoNetworkService);
+        assert_eq!(CmeError::from_code(30).description(), "no network service");
+        assert_eq!(CmeError::from_code(999), CmeError::Unknown(999));
+        assert_eq!(CmeError::from_code(999).description(), "unknown error");
+    }
+
+    #[test]
+    fn test_parse_cme_error() {
+        assert_eq!(parse_cme_error(b"\r\n+CME ERROR: 10\r\n"), Some(Some(10)));
+        assert_eq!(
+            parse_cme_error(b"\r\n+CME ERROR: SIM not inserted\r\n"),
+            Some(None)
+        );
+        assert_eq!(parse_cme_error(b"\r\n+CSQ: 20,99\r\n"), None);
+    }
+}
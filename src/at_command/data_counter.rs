@@ -0,0 +1,134 @@
+use crate::at_command::{parse_csv_line, strip_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Bytes transferred by the module, as reported by [GetDataCounters]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DataCounters {
+    pub sent_bytes: u64,
+    pub received_bytes: u64,
+}
+
+/// Reads the bytes sent and received over all PDP contexts with `AT+CGDCNT?`. Depending on
+/// the firmware the counters are reset on every attach, use [DataUsage] to keep a total across
+/// attaches.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetDataCounters;
+
+impl AtRequest for GetDataCounters {
+    type Response = DataCounters;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CGDCNT")
+            .finish()
+    }
+
+    /// Parses a `+CGDCNT: <cid>,<sent>,<received>` line per context, an empty response means
+    /// nothing was transferred
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let mut counters = DataCounters::default();
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with(b"+CGDCNT: ") {
+                return Err(AtError::AtParseError);
+            }
+            let mut fields = parse_csv_line(line)?.skip(1);
+            let mut parse = || -> Result<u64, AtError> {
+                fields
+                    .next()
+                    .and_then(|field| core::str::from_utf8(field).ok())
+                    .and_then(|field| field.parse().ok())
+                    .ok_or(AtError::AtParseError)
+            };
+
+            counters.sent_bytes += parse()?;
+            counters.received_bytes += parse()?;
+        }
+
+        Ok(counters)
+    }
+}
+
+/// Keeps the total data usage from periodic [GetDataCounters] readings. A counter lower than
+/// the previous reading is taken as a reset, e.g. after an attach, and its value is added as is.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DataUsage {
+    total: DataCounters,
+    last: Option<DataCounters>,
+}
+
+impl DataUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accounts a new reading, returns the total so far
+    pub fn update(&mut self, counters: DataCounters) -> DataCounters {
+        let delta = |current: u64, last: Option<u64>| match last {
+            Some(last) if current >= last => current - last,
+            _ => current,
+        };
+
+        self.total.sent_bytes += delta(counters.sent_bytes, self.last.map(|last| last.sent_bytes));
+        self.total.received_bytes += delta(
+            counters.received_bytes,
+            self.last.map(|last| last.received_bytes),
+        );
+        self.last = Some(counters);
+
+        self.total
+    }
+
+    pub fn total(&self) -> DataCounters {
+        self.total
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_data_counters() {
+        let mut buffer = [0; 512];
+        let result = GetDataCounters.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGDCNT?\r\n");
+
+        let counters = GetDataCounters
+            .parse_response_struct(b"\r\n+CGDCNT: 1,1520,4096\r\n+CGDCNT: 2,100,0\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(counters.sent_bytes, 1620);
+        assert_eq!(counters.received_bytes, 4096);
+
+        let counters = GetDataCounters
+            .parse_response_struct(b"\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(counters, DataCounters::default());
+    }
+
+    #[test]
+    fn test_data_usage_across_reset() {
+        let mut usage = DataUsage::new();
+        usage.update(DataCounters {
+            sent_bytes: 100,
+            received_bytes: 200,
+        });
+        usage.update(DataCounters {
+            sent_bytes: 150,
+            received_bytes: 260,
+        });
+        // Reset by an attach
+        let total = usage.update(DataCounters {
+            sent_bytes: 10,
+            received_bytes: 20,
+        });
+
+        assert_eq!(total.sent_bytes, 160);
+        assert_eq!(total.received_bytes, 280);
+    }
+}
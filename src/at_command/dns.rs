@@ -0,0 +1,92 @@
+use crate::at_command::{verify_ok, AtRequest, BufferType, FinishExact};
+use crate::AtError;
+use core::net::IpAddr;
+
+/// Sets the DNS servers used by the modem instead of the ones provided by the network
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetDnsServer<'a> {
+    pub(crate) primary: &'a str,
+    pub(crate) secondary: Option<&'a str>,
+}
+
+impl<'a> SetDnsServer<'a> {
+    /// Both addresses have to be IPv4 or IPv6 literals
+    pub fn new(primary: &'a str, secondary: Option<&'a str>) -> Result<Self, AtError> {
+        validate_ip_address(primary)?;
+        if let Some(secondary) = secondary {
+            validate_ip_address(secondary)?;
+        }
+
+        Ok(Self { primary, secondary })
+    }
+}
+
+fn validate_ip_address(address: &str) -> Result<(), AtError> {
+    address
+        .parse::<IpAddr>()
+        .map_err(|_| AtError::InvalidAddress)?;
+    Ok(())
+}
+
+impl AtRequest for SetDnsServer<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let mut builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CDNSCFG")
+            .with_string_parameter(self.primary);
+
+        if let Some(secondary) = self.secondary {
+            builder = builder.with_string_parameter(secondary);
+        }
+
+        builder.finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_primary_dns_server() {
+        let mut buffer = [0; 512];
+
+        let request = SetDnsServer::new("8.8.8.8", None).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CDNSCFG=\"8.8.8.8\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_set_primary_and_secondary_dns_server() {
+        let mut buffer = [0; 512];
+
+        let request = SetDnsServer::new("8.8.8.8", Some("2001:4860:4860::8888")).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CDNSCFG=\"8.8.8.8\",\"2001:4860:4860::8888\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_set_malformed_dns_server() {
+        assert!(matches!(
+            SetDnsServer::new("8.8.8", None),
+            Err(AtError::InvalidAddress)
+        ));
+        assert!(matches!(
+            SetDnsServer::new("8.8.8.8", Some("dns.google")),
+            Err(AtError::InvalidAddress)
+        ));
+    }
+}
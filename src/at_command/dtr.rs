@@ -0,0 +1,112 @@
+use crate::at_command::{verify_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Action taken by the modem when the DTR line goes from ON to OFF, as set with `AT&D`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DtrMode {
+    /// DTR is ignored
+    Ignore = 0,
+    /// Switches from data mode back to command mode, the connection is kept
+    CommandMode = 1,
+    /// Drops the connection and switches to command mode
+    Disconnect = 2,
+}
+
+impl TryFrom<u8> for DtrMode {
+    type Error = AtError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DtrMode::Ignore),
+            1 => Ok(DtrMode::CommandMode),
+            2 => Ok(DtrMode::Disconnect),
+            _ => Err(AtError::InvalidParameter),
+        }
+    }
+}
+
+/// Sets how the modem reacts to the DTR line with `AT&D<mode>`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy)]
+pub struct SetDtrMode {
+    pub mode: DtrMode,
+}
+
+impl AtRequest for SetDtrMode {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, _buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let command = match self.mode {
+            DtrMode::Ignore => "AT&D0\r\n",
+            DtrMode::CommandMode => "AT&D1\r\n",
+            DtrMode::Disconnect => "AT&D2\r\n",
+        };
+        Ok(command.as_bytes())
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Lets the DTR line control the sleep of the module with `AT+CSCLK`: once enabled the module
+/// sleeps while DTR is high and wakes up when the host pulls it low, so the host does not
+/// have to send anything to wake it
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetDtrSleep {
+    pub enable: bool,
+}
+
+impl AtRequest for SetDtrSleep {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSCLK")
+            .with_int_parameter(self.enable as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_dtr_mode() {
+        let mut buffer = [0; 512];
+        for (mode, expected) in [
+            (DtrMode::Ignore, "AT&D0\r\n"),
+            (DtrMode::CommandMode, "AT&D1\r\n"),
+            (DtrMode::Disconnect, "AT&D2\r\n"),
+        ] {
+            let request = SetDtrMode { mode };
+            let result = request.get_command(&mut buffer).unwrap();
+            assert_eq!(core::str::from_utf8(result).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_dtr_mode_from_u8() {
+        assert_eq!(DtrMode::try_from(2).unwrap(), DtrMode::Disconnect);
+        assert!(matches!(
+            DtrMode::try_from(3),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_set_dtr_sleep() {
+        let mut buffer = [0; 512];
+        let result = SetDtrSleep { enable: true }
+            .get_command(&mut buffer)
+            .unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSCLK=1\r\n");
+    }
+}
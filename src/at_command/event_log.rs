@@ -0,0 +1,234 @@
+use crate::at_command::{parse_csv_line, strip_ok, verify_ok, AtRequest, BufferType};
+use crate::AtError;
+use at_commands::parser::CommandParser;
+use chrono::NaiveDateTime;
+
+/// Entries kept from the event log, a longer log fails to parse with
+/// [AtError::CapacityError]
+pub const MAX_EVENT_LOG_ENTRIES: usize = 8;
+
+const EVENT_DESCRIPTION_MAX_SIZE: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventLogEntry {
+    pub timestamp: NaiveDateTime,
+    /// Firmware specific code of the event
+    pub code: u16,
+    pub description: Option<heapless::String<EVENT_DESCRIPTION_MAX_SIZE>>,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for EventLogEntry {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "EventLogEntry {{ timestamp: {}, code: {}, description: {} }}",
+            self.timestamp.and_utc().timestamp(),
+            self.code,
+            self.description
+        )
+    }
+}
+
+impl EventLogEntry {
+    /// Parses `+CEVLOG: <code>,"<yy/MM/dd,hh:mm:ss>"[,"<description>"]`
+    fn parse(line: &[u8]) -> Result<Self, AtError> {
+        if !line.starts_with(b"+CEVLOG: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        let mut fields = parse_csv_line(line)?
+            .map(|field| core::str::from_utf8(field).map_err(|_| AtError::AtParseError));
+        let code = fields
+            .next()
+            .ok_or(AtError::AtParseError)??
+            .parse()
+            .map_err(|_| AtError::AtParseError)?;
+        let timestamp = NaiveDateTime::parse_from_str(
+            fields.next().ok_or(AtError::AtParseError)??,
+            "%y/%m/%d,%H:%M:%S",
+        )?;
+        let description = fields
+            .next()
+            .transpose()?
+            .map(|description| description.try_into())
+            .transpose()?;
+
+        Ok(EventLogEntry {
+            timestamp,
+            code,
+            description,
+        })
+    }
+}
+
+pub type EventLog = heapless::Vec<EventLogEntry, MAX_EVENT_LOG_ENTRIES>;
+
+/// Reads the internal event log kept by some firmwares with `AT+CEVLOG?`, oldest entry first
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryEventLog;
+
+impl QueryEventLog {
+    fn get_log(data: &[u8]) -> Result<EventLog, AtError> {
+        let mut log = EventLog::new();
+        for line in strip_ok(data)?
+            .split(|byte| *byte == b'\n')
+            .map(super::trim_line_breaks)
+            .filter(|line| !line.is_empty())
+        {
+            log.push(EventLogEntry::parse(line)?)
+                .map_err(|_| AtError::CapacityError)?;
+        }
+
+        Ok(log)
+    }
+}
+
+impl AtRequest for QueryEventLog {
+    type Response = EventLog;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CEVLOG")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_log(data)
+    }
+}
+
+/// Removes every entry of the event log with `AT+CEVLOG=0`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClearEventLog;
+
+impl AtRequest for ClearEventLog {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CEVLOG")
+            .with_int_parameter(0)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads how many internal asserts the firmware recorded with `AT+CASSERT?`, e.g. to monitor
+/// the reliability of devices in the field
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryCrashCounter;
+
+impl AtRequest for QueryCrashCounter {
+    type Response = u32;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CASSERT")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let (count,) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CASSERT: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        u32::try_from(count).map_err(|_| AtError::AtParseError)
+    }
+}
+
+/// Resets the counter read by [QueryCrashCounter] with `AT+CASSERT=0`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ResetCrashCounter;
+
+impl AtRequest for ResetCrashCounter {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CASSERT")
+            .with_int_parameter(0)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+    use crate::Modem;
+
+    #[test]
+    fn test_event_log_requests() {
+        let mut buffer = [0; 512];
+        let result = QueryEventLog.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CEVLOG?\r\n");
+        let result = ClearEventLog.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CEVLOG=0\r\n");
+    }
+
+    #[test]
+    fn test_query_event_log_two_entries() {
+        let log = QueryEventLog
+            .parse_response_struct(
+                b"\r\n+CEVLOG: 3,\"24/10/14,08:01:02\",\"watchdog reset\"\r\n\
+                +CEVLOG: 17,\"24/10/15,21:30:00\"\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].code, 3);
+        assert_eq!(
+            log[0].timestamp,
+            NaiveDateTime::parse_from_str("24/10/14,08:01:02", "%y/%m/%d,%H:%M:%S").unwrap()
+        );
+        assert_eq!(log[0].description.as_deref(), Some("watchdog reset"));
+        assert_eq!(log[1].code, 17);
+        assert_eq!(log[1].description, None);
+    }
+
+    #[test]
+    fn test_query_event_log_empty() {
+        let log = QueryEventLog.parse_response_struct(b"\r\nOK\r\n").unwrap();
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_query_crash_counter() {
+        let mut buffer = [0; 512];
+        let result = QueryCrashCounter.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CASSERT?\r\n");
+
+        let count = QueryCrashCounter
+            .parse_response_struct(b"\r\n+CASSERT: 3\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_reset_crash_counter() {
+        let mut buffer = [0; 512];
+        let result = ResetCrashCounter.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CASSERT=0\r\n");
+        assert!(ResetCrashCounter
+            .parse_response_struct(b"\r\nOK\r\n")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_query_crash_counter_unsupported() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CME ERROR: 4\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        assert_eq!(modem.send_optional(&QueryCrashCounter).unwrap(), None);
+    }
+}
@@ -0,0 +1,180 @@
+use crate::at_command::{validate_string_parameter, verify_ok, AtRequest, BufferType, FinishExact};
+use crate::AtError;
+use at_commands::parser::CommandParser;
+
+const REVISION_MAX_SIZE: usize = 32;
+
+pub type Revision = heapless::String<REVISION_MAX_SIZE>;
+
+/// Request the firmware revision
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FirmwareRevision;
+
+pub struct FirmwareRevisionResponse {
+    /// Application firmware revision
+    pub application: Revision,
+    /// Baseband revision, only reported by some firmwares
+    pub baseband: Option<Revision>,
+}
+
+impl FirmwareRevision {
+    fn parse_revision(data: &[u8]) -> Result<FirmwareRevisionResponse, AtError> {
+        // \r\nRevision:1752B12SIM7020E,MOLY.NB1.A2\r\n\r\nOK\r\n
+        let (revision,) = CommandParser::parse(data)
+            .expect_identifier(b"\r\n")
+            .expect_optional_identifier(b"Revision:")
+            .expect_raw_string()
+            .expect_identifier(b"\r\n\r\nOK")
+            .finish()?;
+
+        let (application, baseband) = match revision.split_once(',') {
+            Some((application, baseband)) => (application, Some(baseband.trim())),
+            None => (revision, None),
+        };
+
+        Ok(FirmwareRevisionResponse {
+            application: application.trim().try_into()?,
+            baseband: baseband.map(|x| x.try_into()).transpose()?,
+        })
+    }
+}
+
+impl AtRequest for FirmwareRevision {
+    type Response = FirmwareRevisionResponse;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CGMR")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::parse_revision(data)
+    }
+}
+
+/// Starts a firmware over the air update, the progress is reported through
+/// [FirmwareUpdateProgress] URCs. See [Modem::wait_for_firmware_update](crate::Modem::wait_for_firmware_update).
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct StartFirmwareUpdate<'a> {
+    /// URL of the firmware image
+    pub url: &'a str,
+}
+
+impl AtRequest for StartFirmwareUpdate<'_> {
+    type Response = ();
+
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.url)
+    }
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CFOTA")
+            .with_int_parameter(1)
+            .with_string_parameter(self.url)
+            .finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirmwareUpdateState {
+    Downloading,
+    Verifying,
+    /// The image is being written, the modem reboots once it is done
+    Applying,
+    Failed,
+    Unknown,
+}
+
+impl From<i32> for FirmwareUpdateState {
+    fn from(value: i32) -> Self {
+        match value {
+            1 => FirmwareUpdateState::Downloading,
+            2 => FirmwareUpdateState::Verifying,
+            3 => FirmwareUpdateState::Applying,
+            4 => FirmwareUpdateState::Failed,
+            _ => FirmwareUpdateState::Unknown,
+        }
+    }
+}
+
+/// Unsolicited message reporting the progress of a [StartFirmwareUpdate]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirmwareUpdateProgress {
+    pub state: FirmwareUpdateState,
+    /// Percentage of the current state, if reported
+    pub progress: Option<u8>,
+}
+
+impl FirmwareUpdateProgress {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (state, progress) = CommandParser::parse(data)
+            .expect_identifier(b"+CFOTA: ")
+            .expect_int_parameter()
+            .expect_optional_int_parameter()
+            .finish()?;
+
+        Ok(FirmwareUpdateProgress {
+            state: state.into(),
+            progress: progress.map(|x| x as u8),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_application_and_baseband_revision() {
+        let data = b"\r\nRevision:1752B12SIM7020E,MOLY.NB1.SIM7020.A2\r\n\r\nOK\r";
+
+        let revision = FirmwareRevision.parse_response_struct(data).unwrap();
+
+        assert_eq!(revision.application.as_str(), "1752B12SIM7020E");
+        assert_eq!(revision.baseband.unwrap().as_str(), "MOLY.NB1.SIM7020.A2");
+    }
+
+    #[test]
+    fn test_parse_single_revision() {
+        let data = b"\r\nRevision:1752B12SIM7020E\r\n\r\nOK\r";
+
+        let revision = FirmwareRevision.parse_response_struct(data).unwrap();
+
+        assert_eq!(revision.application.as_str(), "1752B12SIM7020E");
+        assert!(revision.baseband.is_none());
+    }
+
+    #[test]
+    fn test_start_firmware_update_command() {
+        let mut buffer = [0; 512];
+
+        let request = StartFirmwareUpdate {
+            url: "http://example.com/fw.bin",
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CFOTA=1,\"http://example.com/fw.bin\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_firmware_update_progress() {
+        let progress = FirmwareUpdateProgress::parse(b"+CFOTA: 1,42").unwrap();
+        assert_eq!(progress.state, FirmwareUpdateState::Downloading);
+        assert_eq!(progress.progress, Some(42));
+
+        let progress = FirmwareUpdateProgress::parse(b"+CFOTA: 4").unwrap();
+        assert_eq!(progress.state, FirmwareUpdateState::Failed);
+        assert_eq!(progress.progress, None);
+    }
+}
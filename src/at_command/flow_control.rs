@@ -72,6 +72,7 @@ impl AtRequest for SetFlowControl {
 pub struct GetFlowControl;
 
 #[allow(unused)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct GetFlowControlResponse {
     pub dce_by_dte: ControlFlowStatus,
     pub dte_by_dce: ControlFlowStatus,
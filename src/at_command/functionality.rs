@@ -0,0 +1,86 @@
+use crate::at_command::{verify_ok, AtRequest, BufferType};
+use crate::rate_limit::CommandKind;
+use crate::AtError;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Functionality {
+    Minimum = 0,
+    Full = 1,
+    /// Disables both transmitting and receiving RF circuits
+    DisableRf = 4,
+}
+
+impl Functionality {
+    /// Parses the `+CFUN: <fun>` URC sent while booting
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (functionality,) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CFUN: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        match functionality {
+            0 => Ok(Functionality::Minimum),
+            1 => Ok(Functionality::Full),
+            4 => Ok(Functionality::DisableRf),
+            _ => Err(AtError::AtParseError),
+        }
+    }
+}
+
+/// Sets the phone functionality with `AT+CFUN`. The modem answers busy errors if it is issued
+/// too often, it is spaced out as [CommandKind::Functionality] by the
+/// [RateLimiter](crate::rate_limit::RateLimiter).
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetFunctionality {
+    pub functionality: Functionality,
+    /// Resets the modem before setting the functionality
+    pub reset: bool,
+}
+
+impl AtRequest for SetFunctionality {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CFUN")
+            .with_int_parameter(self.functionality as i32);
+
+        if self.reset {
+            builder.with_int_parameter(1).finish()
+        } else {
+            builder.finish()
+        }
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+
+    fn command_kind(&self) -> Option<CommandKind> {
+        Some(CommandKind::Functionality)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_functionality_request() {
+        let mut buffer = [0; 512];
+        let command = SetFunctionality {
+            functionality: Functionality::Full,
+            reset: false,
+        };
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CFUN=1\r\n");
+
+        let command = SetFunctionality {
+            functionality: Functionality::Minimum,
+            reset: true,
+        };
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CFUN=0,1\r\n");
+    }
+}
@@ -0,0 +1,242 @@
+use crate::at_command::{strip_ok, verify_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Highest GPIO pin accessible over AT
+pub const MAX_GPIO_PIN: u8 = 4;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpioDirection {
+    Input,
+    Output,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpioLevel {
+    Low,
+    High,
+}
+
+impl TryFrom<i32> for GpioLevel {
+    type Error = AtError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(GpioLevel::Low),
+            1 => Ok(GpioLevel::High),
+            _ => Err(AtError::AtParseError),
+        }
+    }
+}
+
+fn validate_pin(pin: u8) -> Result<(), AtError> {
+    if pin > MAX_GPIO_PIN {
+        return Err(AtError::InvalidParameter);
+    }
+
+    Ok(())
+}
+
+/// Configures a modem GPIO pin as input or output with `AT+CGDRT`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetGpioDirection {
+    pub(crate) pin: u8,
+    pub(crate) direction: GpioDirection,
+}
+
+impl SetGpioDirection {
+    pub fn new(pin: u8, direction: GpioDirection) -> Result<Self, AtError> {
+        validate_pin(pin)?;
+        Ok(Self { pin, direction })
+    }
+}
+
+impl AtRequest for SetGpioDirection {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGDRT")
+            .with_int_parameter(self.pin as i32)
+            .with_int_parameter(self.direction as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Drives a pin configured as output with `AT+CGSETV`, e.g. to switch an LED
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetGpioLevel {
+    pub(crate) pin: u8,
+    pub(crate) level: GpioLevel,
+}
+
+impl SetGpioLevel {
+    pub fn new(pin: u8, level: GpioLevel) -> Result<Self, AtError> {
+        validate_pin(pin)?;
+        Ok(Self { pin, level })
+    }
+}
+
+impl AtRequest for SetGpioLevel {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGSETV")
+            .with_int_parameter(self.pin as i32)
+            .with_int_parameter(self.level as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads the level of a pin configured as input with `AT+CGGETV`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetGpioLevel {
+    pub(crate) pin: u8,
+}
+
+impl GetGpioLevel {
+    pub fn new(pin: u8) -> Result<Self, AtError> {
+        validate_pin(pin)?;
+        Ok(Self { pin })
+    }
+}
+
+impl AtRequest for GetGpioLevel {
+    type Response = GpioLevel;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGGETV")
+            .with_int_parameter(self.pin as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let (pin, level) = at_commands::parser::CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CGGETV: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        if pin != self.pin as i32 {
+            return Err(AtError::AtParseError);
+        }
+
+        level.try_into()
+    }
+}
+
+/// Lowest and highest PWM frequency in Hz
+pub const PWM_FREQUENCY_RANGE_HZ: core::ops::RangeInclusive<u32> = 1..=100_000;
+
+/// Configures the PWM output of the modem with `AT+CPWM`, on modules which expose it. A duty
+/// cycle of 0 disables the output.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetPwm {
+    pub(crate) frequency_hz: u32,
+    pub(crate) duty_percent: u8,
+}
+
+impl SetPwm {
+    pub fn new(frequency_hz: u32, duty_percent: u8) -> Result<Self, AtError> {
+        if !PWM_FREQUENCY_RANGE_HZ.contains(&frequency_hz) || duty_percent > 100 {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self {
+            frequency_hz,
+            duty_percent,
+        })
+    }
+}
+
+impl AtRequest for SetPwm {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CPWM")
+            .with_int_parameter(self.frequency_hz as i32)
+            .with_int_parameter(self.duty_percent as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_gpio_output_high() {
+        let mut buffer = [0; 512];
+
+        let request = SetGpioDirection::new(2, GpioDirection::Output).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGDRT=2,1\r\n");
+
+        let request = SetGpioLevel::new(2, GpioLevel::High).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGSETV=2,1\r\n");
+    }
+
+    #[test]
+    fn test_read_gpio_input() {
+        let mut buffer = [0; 512];
+
+        let request = GetGpioLevel::new(3).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGGETV=3\r\n");
+
+        let level = request
+            .parse_response_struct(b"\r\n+CGGETV: 3,1\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(level, GpioLevel::High);
+        assert!(request
+            .parse_response_struct(b"\r\n+CGGETV: 1,0\r\n\r\nOK\r\n")
+            .is_err());
+    }
+
+    #[test]
+    fn test_invalid_gpio_pin() {
+        assert!(matches!(
+            SetGpioLevel::new(MAX_GPIO_PIN + 1, GpioLevel::Low),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            GetGpioLevel::new(MAX_GPIO_PIN + 1),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_set_pwm() {
+        let mut buffer = [0; 512];
+
+        let request = SetPwm::new(1000, 25).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CPWM=1000,25\r\n");
+    }
+
+    #[test]
+    fn test_set_pwm_out_of_range() {
+        assert!(matches!(
+            SetPwm::new(1000, 101),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(SetPwm::new(0, 50), Err(AtError::InvalidParameter)));
+    }
+}
@@ -1,6 +1,6 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{validate_string_parameter, AtRequest, BufferType, FinishExact};
 use crate::AtError;
 use at_commands::builder::CommandBuilder;
 use at_commands::parser::CommandParser;
@@ -182,6 +182,10 @@ impl CreateHttpSession<'_> {
 impl AtRequest for CreateHttpSession<'_> {
     type Response = CreateHttpSessionResponse;
 
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.host)
+    }
+
     fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
         at_commands::builder::CommandBuilder::create_set(buffer, true)
             .named("+CHTTPCREATE")
@@ -189,7 +193,7 @@ impl AtRequest for CreateHttpSession<'_> {
             // todo: optional parameters need to be fixed
             // .with_optional_string_parameter(self.user)
             // .with_optional_string_parameter(self.password)
-            .finish()
+            .finish_exact()
     }
 
     #[allow(deprecated)]
@@ -289,6 +293,10 @@ pub struct HttpSend<'a> {
 impl AtRequest for HttpSend<'_> {
     type Response = ();
 
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.path)
+    }
+
     fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
         let method: u8 = match self.method {
             HttpMethod::GET => 0,
@@ -305,7 +313,7 @@ impl AtRequest for HttpSend<'_> {
             // .with_optional_string_parameter(self.customer_header)
             // .with_optional_string_parameter(self.content_type)
             // .with_optional_string_parameter(self.content_string)
-            .finish()
+            .finish_exact()
     }
 
     fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
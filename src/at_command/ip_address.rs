@@ -1,16 +1,20 @@
+use crate::at_command::cgcontrdp::PDPContextReadDynamicsParameters;
+use crate::at_command::socket::Domain;
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
-use crate::AtError;
+use crate::at_command::{strip_ok, AtRequest, BufferType};
+use crate::{AtError, Modem};
 use at_commands::parser::CommandParser;
 #[cfg(feature = "defmt")]
 use defmt::info;
+use embedded_io::{Read, Write};
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LocalIPAddress;
 
 const MAX_IP_SIZE: usize = 39;
 
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LocalIpAddressResponse {
     pub ip: heapless::String<MAX_IP_SIZE>,
 }
@@ -49,3 +53,215 @@ impl AtRequest for LocalIPAddress {
         Ok(LocalIpAddressResponse { ip })
     }
 }
+
+/// IPv6 addresses may be reported as 16 dot separated decimals
+const MAX_PDP_ADDRESS_SIZE: usize = 63;
+
+pub type PdpAddress = heapless::String<MAX_PDP_ADDRESS_SIZE>;
+
+/// Tells the family of an address as reported by `AT+CGPADDR` or `AT+CGCONTRDP`, which send
+/// IPv6 addresses either with colons or as 16 dot separated decimals. A subnet mask appended by
+/// `AT+CGCONTRDP` is ignored.
+pub fn address_family(address: &str) -> Option<Domain> {
+    if address.contains(':') {
+        return Some(Domain::IPv6);
+    }
+
+    match address.split('.').count() {
+        4 | 8 => Some(Domain::IPv4),
+        16 | 32 => Some(Domain::IPv6),
+        _ => None,
+    }
+}
+
+/// Addresses assigned to an activated PDP context
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PdpAddresses {
+    pub ipv4: Option<PdpAddress>,
+    pub ipv6: Option<PdpAddress>,
+}
+
+impl PdpAddresses {
+    pub fn is_dual_stack(&self) -> bool {
+        self.ipv4.is_some() && self.ipv6.is_some()
+    }
+
+    /// Domain to create sockets with, IPv4 is preferred on dual stack contexts. `None` if no
+    /// address was assigned.
+    pub fn domain(&self) -> Option<Domain> {
+        match (&self.ipv4, &self.ipv6) {
+            (Some(_), _) => Some(Domain::IPv4),
+            (None, Some(_)) => Some(Domain::IPv6),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Reads the addresses assigned to a PDP context with `AT+CGPADDR=<cid>`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetPdpAddresses {
+    pub cid: u8,
+}
+
+impl GetPdpAddresses {
+    fn get_addresses(data: &[u8]) -> Result<PdpAddresses, AtError> {
+        let payload = core::str::from_utf8(strip_ok(data)?).map_err(|_| AtError::AtParseError)?;
+        let payload = payload
+            .strip_prefix("+CGPADDR: ")
+            .ok_or(AtError::AtParseError)?;
+
+        // <cid>[,<PDP_addr_1>[,<PDP_addr_2>]]
+        let mut addresses = PdpAddresses::default();
+        for field in payload.split(',').skip(1) {
+            let address = field.trim_matches('"');
+            if address.is_empty() {
+                continue;
+            }
+
+            let slot = match address_family(address) {
+                Some(Domain::IPv4) => &mut addresses.ipv4,
+                Some(Domain::IPv6) => &mut addresses.ipv6,
+                None => return Err(AtError::AtParseError),
+            };
+            *slot = Some(address.try_into()?);
+        }
+
+        Ok(addresses)
+    }
+}
+
+impl AtRequest for GetPdpAddresses {
+    type Response = PdpAddresses;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGPADDR")
+            .with_int_parameter(self.cid as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_addresses(data)
+    }
+}
+
+/// Finds which address families were assigned to a context after its activation, as a dual
+/// stack request may be granted a single family. The addresses are read with `AT+CGPADDR`,
+/// falling back to the local address of `AT+CGCONTRDP` if it reported none.
+pub struct QueryAddressFamilies;
+
+impl QueryAddressFamilies {
+    pub fn query<T: Write, U: Read>(
+        modem: &mut Modem<'_, T, U>,
+        cid: u8,
+    ) -> Result<PdpAddresses, AtError> {
+        let mut addresses = modem.send_and_wait_response(&GetPdpAddresses { cid })?;
+        if addresses.domain().is_some() {
+            return Ok(addresses);
+        }
+
+        let parameters = modem.send_and_wait_response(&PDPContextReadDynamicsParameters)?;
+        let local_address = parameters
+            .filter(|parameters| parameters.cid == i32::from(cid))
+            .and_then(|parameters| parameters.local_address_and_subnet_mask);
+        if let Some(local_address) = local_address {
+            match address_family(&local_address) {
+                Some(Domain::IPv4) => addresses.ipv4 = Some(address_without_mask(&local_address)?),
+                Some(Domain::IPv6) => addresses.ipv6 = Some(address_without_mask(&local_address)?),
+                None => return Err(AtError::AtParseError),
+            }
+        }
+
+        Ok(addresses)
+    }
+}
+
+/// Removes the subnet mask `AT+CGCONTRDP` appends to dot separated addresses
+fn address_without_mask(address: &str) -> Result<PdpAddress, AtError> {
+    if address.contains(':') {
+        return Ok(address.try_into()?);
+    }
+
+    let parts = address.split('.').count();
+    let end = if parts == 8 || parts == 32 {
+        address
+            .match_indices('.')
+            .nth(parts / 2 - 1)
+            .map(|(index, _)| index)
+            .unwrap_or(address.len())
+    } else {
+        address.len()
+    };
+
+    Ok(address[..end].try_into()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+
+    #[test]
+    fn test_get_pdp_addresses_request() {
+        let mut buffer = [0; 512];
+        let result = GetPdpAddresses { cid: 1 }.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGPADDR=1\r\n");
+    }
+
+    #[test]
+    fn test_get_pdp_addresses_ipv4_only() {
+        let addresses = GetPdpAddresses { cid: 1 }
+            .parse_response_struct(b"\r\n+CGPADDR: 1,\"10.1.2.3\"\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(addresses.ipv4.as_deref(), Some("10.1.2.3"));
+        assert_eq!(addresses.ipv6, None);
+        assert_eq!(addresses.domain(), Some(Domain::IPv4));
+    }
+
+    #[test]
+    fn test_get_pdp_addresses_ipv6_only() {
+        let addresses = GetPdpAddresses { cid: 1 }
+            .parse_response_struct(
+                b"\r\n+CGPADDR: 1,\"32.1.13.184.0.0.0.0.0.0.0.0.0.0.0.1\"\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(addresses.ipv4, None);
+        assert_eq!(
+            addresses.ipv6.as_deref(),
+            Some("32.1.13.184.0.0.0.0.0.0.0.0.0.0.0.1")
+        );
+        assert_eq!(addresses.domain(), Some(Domain::IPv6));
+    }
+
+    #[test]
+    fn test_get_pdp_addresses_dual_stack() {
+        let addresses = GetPdpAddresses { cid: 1 }
+            .parse_response_struct(b"\r\n+CGPADDR: 1,\"10.1.2.3\",\"2001:DB8::1\"\r\n\r\nOK\r\n")
+            .unwrap();
+        assert!(addresses.is_dual_stack());
+        assert_eq!(addresses.ipv6.as_deref(), Some("2001:DB8::1"));
+        assert_eq!(addresses.domain(), Some(Domain::IPv4));
+    }
+
+    #[test]
+    fn test_address_family_with_subnet_mask() {
+        assert_eq!(address_family("10.1.2.3.255.255.255.0"), Some(Domain::IPv4));
+        assert_eq!(address_family("10.1.2"), None);
+    }
+
+    #[test]
+    fn test_query_address_families_falls_back_to_dynamic_parameters() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\
+            \r\n+CGPADDR: 1\r\n\r\nOK\r\n\
+            \r\n+CGCONTRDP: 1,5,\"iot\",\"10.1.2.3.255.255.255.0\"\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let addresses = QueryAddressFamilies::query(&mut modem, 1).unwrap();
+        assert_eq!(addresses.ipv4.as_deref(), Some("10.1.2.3"));
+        assert_eq!(addresses.ipv6, None);
+    }
+}
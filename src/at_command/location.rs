@@ -0,0 +1,100 @@
+use crate::at_command::{strip_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Position estimated by the network from the serving and neighbour cells, less accurate than
+/// a GNSS fix
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellPosition {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Radius of the area the device is in
+    pub accuracy_meters: u32,
+}
+
+/// Requests a network assisted position with `AT+CLBS=1,<cid>`, the PDP context has to be
+/// active. `None` if the location service could not find a position.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RequestCellPosition {
+    pub cid: u8,
+}
+
+impl RequestCellPosition {
+    fn get_position(data: &[u8]) -> Result<Option<CellPosition>, AtError> {
+        let payload = core::str::from_utf8(strip_ok(data)?).map_err(|_| AtError::AtParseError)?;
+
+        // +CLBS: <locationcode>[,<longitude>,<latitude>,<accuracy>]
+        let mut fields = payload
+            .strip_prefix("+CLBS: ")
+            .ok_or(AtError::AtParseError)?
+            .split(',')
+            .map(str::trim);
+        let code: u8 = fields
+            .next()
+            .and_then(|code| code.parse().ok())
+            .ok_or(AtError::AtParseError)?;
+        if code != 0 {
+            return Ok(None);
+        }
+
+        let mut next = || fields.next().ok_or(AtError::AtParseError);
+        let longitude: f64 = next()?.parse().map_err(|_| AtError::AtParseError)?;
+        let latitude: f64 = next()?.parse().map_err(|_| AtError::AtParseError)?;
+        let accuracy_meters = next()?.parse().map_err(|_| AtError::AtParseError)?;
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(AtError::AtParseError);
+        }
+
+        Ok(Some(CellPosition {
+            latitude,
+            longitude,
+            accuracy_meters,
+        }))
+    }
+}
+
+impl AtRequest for RequestCellPosition {
+    type Response = Option<CellPosition>;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CLBS")
+            .with_int_parameter(1)
+            .with_int_parameter(self.cid)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_position(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_request_cell_position() {
+        let mut buffer = [0; 512];
+        let request = RequestCellPosition { cid: 1 };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CLBS=1,1\r\n");
+
+        let position = request
+            .parse_response_struct(b"\r\n+CLBS: 0,-3.703790,40.416775,550\r\n\r\nOK\r\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(position.latitude, 40.416775);
+        assert_eq!(position.longitude, -3.70379);
+        assert_eq!(position.accuracy_meters, 550);
+    }
+
+    #[test]
+    fn test_request_cell_position_unavailable() {
+        let request = RequestCellPosition { cid: 1 };
+        assert!(request
+            .parse_response_struct(b"\r\n+CLBS: 1\r\n\r\nOK\r\n")
+            .unwrap()
+            .is_none());
+    }
+}
@@ -10,6 +10,7 @@ use crate::at_command::network_registration_status::{
 use crate::at_command::pdp_context::PDPState;
 use crate::at_command::power_saving_mode::PowerSavingModeState;
 use crate::at_command::sleep_indication::SleepIndication;
+use crate::rate_limit::CommandKind;
 use crate::{AtError, BUFFER_SIZE};
 #[cfg(feature = "defmt")]
 use defmt::debug;
@@ -23,22 +24,43 @@ pub mod at_cstt;
 pub mod ate;
 pub mod ati;
 pub mod battery;
+pub mod call;
 pub mod ceer;
 pub mod cgcontrdp;
 pub mod clock;
 pub mod cmee;
+pub mod data_counter;
+pub mod dns;
+pub mod dtr;
+pub mod event_log;
+pub mod firmware;
 pub(crate) mod flow_control;
+pub mod functionality;
+pub mod gpio;
 pub mod http;
 pub mod ip_address;
+pub mod location;
 pub mod model_identification;
 pub mod mqtt;
 pub mod network_information;
 pub mod network_registration_status;
+pub mod nidd;
 pub mod ntp;
 pub mod pdp_context;
 pub mod power_saving_mode;
+pub mod prefix;
+pub mod radio;
+pub mod raw;
+pub mod ring_indicator;
+pub mod self_test;
+pub mod sim;
 pub mod sleep_indication;
+pub mod sms;
 pub mod socket;
+pub mod temperature;
+pub mod uart;
+pub mod urc_port;
+pub mod ussd;
 pub mod wireless;
 
 type BufferType = [u8; BUFFER_SIZE];
@@ -91,6 +113,54 @@ pub trait AtRequest {
         self.get_command(buffer).expect("buffer too small")
     }
 
+    /// Length of the command, terminator included. It is computed with a dry-run build, so it
+    /// works for commands which do not fit into a [BufferType] too.
+    fn command_len(&self) -> usize {
+        let mut buffer = [0; BUFFER_SIZE];
+        match self.get_command(&mut buffer) {
+            Ok(command) => command.len(),
+            Err(size) => size,
+        }
+    }
+
+    /// Builds the command into an owned buffer, e.g. to queue or log it
+    fn to_vec<const N: usize>(&self) -> Result<heapless::Vec<u8, N>, AtError> {
+        self.validate()?;
+        let mut buffer = [0; BUFFER_SIZE];
+        let command = self
+            .get_command(&mut buffer)
+            .map_err(|_| AtError::CapacityError)?;
+        heapless::Vec::from_slice(command).map_err(|_| AtError::CapacityError)
+    }
+
+    /// Whether a line of the response which looks like a URC is part of it, e.g. an error report
+    /// sent in place of the data. Lines prefixed with the name of the command always are, any
+    /// other URC received before the final OK is kept pending by
+    /// [Modem::send_and_wait_response](crate::Modem::send_and_wait_response).
+    fn claims_line(&self, _line: &[u8]) -> bool {
+        false
+    }
+
+    /// Checks the parameters before the command is built, e.g. the string parameters with
+    /// [validate_string_parameter], as [AtRequest::get_command] can only report a buffer too
+    /// small
+    fn validate(&self) -> Result<(), AtError> {
+        Ok(())
+    }
+
+    /// Kind of the command if it has to be spaced out by the
+    /// [RateLimiter](crate::rate_limit::RateLimiter) set with
+    /// [Modem::with_rate_limiter](crate::Modem::with_rate_limiter)
+    fn command_kind(&self) -> Option<CommandKind> {
+        None
+    }
+
+    /// Whether a line other than `OK` or an error ends the response, e.g. `BUSY` for a call.
+    /// The line is included in the response given to [AtRequest::parse_response_struct].
+    fn is_final_result(&self, _line: &[u8]) -> bool {
+        false
+    }
+
     #[deprecated(since = "3.0.0", note = "Migrate to parse_response_struct")]
     #[allow(deprecated)]
     fn parse_response(&self, _data: &[u8]) -> Result<AtResponse, AtError> {
@@ -102,10 +172,416 @@ pub trait AtRequest {
     fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError>;
 }
 
+/// Longest prefix kept by [UnknownResponse]
+pub const UNKNOWN_PREFIX_MAX_SIZE: usize = 16;
+/// Longest response kept by [UnknownResponse]
+pub const UNKNOWN_RAW_MAX_SIZE: usize = 128;
+
+/// Response starting with the expected `prefix` whose fields could not be parsed, e.g. because
+/// a newer firmware added a field
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownResponse {
+    pub prefix: heapless::String<UNKNOWN_PREFIX_MAX_SIZE>,
+    /// Response without the final OK
+    pub raw: heapless::Vec<u8, UNKNOWN_RAW_MAX_SIZE>,
+}
+
+impl UnknownResponse {
+    /// Fallback for a parse `error`: the response is kept if it starts with `prefix`, e.g.
+    /// `+CSOC`, and the error is a parse error. Any other error, or a response with another
+    /// prefix, is returned as is.
+    fn from_parse_error(data: &[u8], prefix: &str, error: AtError) -> Result<Self, AtError> {
+        if !matches!(error, AtError::AtParseError) {
+            return Err(error);
+        }
+
+        let raw = match strip_ok(data) {
+            Ok(raw)
+                if raw
+                    .strip_prefix(prefix.as_bytes())
+                    .is_some_and(|header| header.starts_with(b":")) =>
+            {
+                raw
+            }
+            _ => return Err(error),
+        };
+
+        Ok(Self {
+            prefix: heapless::String::try_from(prefix)?,
+            raw: heapless::Vec::from_slice(raw).map_err(|_| AtError::CapacityError)?,
+        })
+    }
+}
+
+/// Requests whose response can be passed through as an [UnknownResponse] by [Lenient]
+pub trait LenientRequest: AtRequest {
+    /// Prefix of the response, e.g. `+CSOC`
+    const PREFIX: &'static str;
+
+    /// Parses the response, failing with [AtError::AtParseError] on any field it does not
+    /// expect instead of skipping it
+    fn parse_exact(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        self.parse_response_struct(data)
+    }
+}
+
+/// Response of a [Lenient] request
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LenientResponse<T> {
+    Parsed(T),
+    Unknown(UnknownResponse),
+}
+
+/// Sends the wrapped request and returns a response with the expected prefix which cannot be
+/// parsed as [LenientResponse::Unknown] instead of failing, so drivers can degrade gracefully on
+/// a newer firmware
+pub struct Lenient<'r, R>(pub &'r R);
+
+impl<R: LenientRequest> AtRequest for Lenient<'_, R> {
+    type Response = LenientResponse<R::Response>;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        self.0.get_command(buffer)
+    }
+
+    fn claims_line(&self, line: &[u8]) -> bool {
+        self.0.claims_line(line)
+    }
+
+    fn validate(&self) -> Result<(), AtError> {
+        self.0.validate()
+    }
+
+    fn command_kind(&self) -> Option<CommandKind> {
+        self.0.command_kind()
+    }
+
+    fn is_final_result(&self, line: &[u8]) -> bool {
+        self.0.is_final_result(line)
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        match self.0.parse_exact(data) {
+            Ok(response) => Ok(LenientResponse::Parsed(response)),
+            Err(error) => UnknownResponse::from_parse_error(data, R::PREFIX, error)
+                .map(LenientResponse::Unknown),
+        }
+    }
+}
+
+/// Commands sent in two stages: a header, after which the modem answers a `>` prompt, and
+/// the data, which is only sent once the prompt arrived. Used through
+/// [Modem::send_prompt_exchange](crate::Modem::send_prompt_exchange).
+pub trait PromptExchange {
+    type Response;
+
+    /// Command sent before the prompt, terminator included
+    fn get_header<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize>;
+
+    /// Data sent after the prompt, terminator included
+    fn get_data<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize>;
+
+    /// Parses the response received after the data was sent
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError>;
+}
+
+/// Requests whose data is written straight from the caller's buffer after a header built
+/// separately, instead of being copied into the command buffer. Used through
+/// [Modem::send_streamed](crate::Modem::send_streamed), the data is not limited by the size of
+/// the command buffer.
+pub trait StreamedRequest {
+    type Response;
+
+    /// Command up to the data
+    fn get_header<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize>;
+
+    /// Data written right after the header
+    fn data(&self) -> &[u8];
+
+    /// Written after the data
+    fn trailer(&self) -> &[u8] {
+        b"\r\n"
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError>;
+}
+
+/// Longest command line the modem accepts, terminator included, leaving room for the header of
+/// a send of 1024 hex digits. Longer lines are rejected with [AtError::BufferOverflow] before
+/// anything is sent.
+pub const MAX_COMMAND_LINE_LENGTH: usize = 1080;
+
+/// Checks a command line of `len` bytes, terminator included, fits into the line of the modem
+pub(crate) fn check_line_length(len: usize) -> Result<(), AtError> {
+    if len > MAX_COMMAND_LINE_LENGTH {
+        return Err(AtError::BufferOverflow);
+    }
+
+    Ok(())
+}
+
+/// Finishes a set command built into a [BufferType] like `finish` of the builder, which drops
+/// the comma after the last parameter but still counts it once the comma lies beyond the
+/// buffer. The size needed by a command which does not fit is returned without it.
+pub(crate) trait FinishExact<'a> {
+    fn finish_exact(self) -> Result<&'a [u8], usize>;
+}
+
+impl<'a> FinishExact<'a> for at_commands::builder::CommandBuilder<'a, at_commands::builder::Set> {
+    fn finish_exact(self) -> Result<&'a [u8], usize> {
+        const TERMINATOR_LEN: usize = 2;
+        self.finish().map_err(|size| {
+            if size - TERMINATOR_LEN > BUFFER_SIZE {
+                size - 1
+            } else {
+                size
+            }
+        })
+    }
+}
+
+/// Maps the size needed by a command which does not fit into the command buffer, as returned
+/// by [AtRequest::get_command], to [AtError::BufferOverflow] if the modem would not accept it
+/// either and to [AtError::CapacityError] otherwise
+pub(crate) fn command_too_long(size: usize) -> AtError {
+    match check_line_length(size) {
+        Ok(()) => AtError::CapacityError,
+        Err(e) => e,
+    }
+}
+
+/// Checks a value can be sent as a quoted string parameter. The modem has no escape sequence
+/// for a quote inside a quoted parameter and ends the line at a line break, so quotes and
+/// control characters are rejected with [AtError::InvalidParameter] instead of being escaped.
+pub fn validate_string_parameter(value: &str) -> Result<(), AtError> {
+    if value.bytes().any(|b| b == b'"' || b.is_ascii_control()) {
+        return Err(AtError::InvalidParameter);
+    }
+
+    Ok(())
+}
+
+/// Checks the response is a plain OK, ignoring any blank lines around it
 pub(crate) fn verify_ok(data: &[u8]) -> Result<(), AtError> {
-    at_commands::parser::CommandParser::parse(data)
-        .expect_identifier(b"\r\nOK\r")
-        .finish()?;
+    if !strip_ok(data)?.is_empty() {
+        return Err(AtError::AtParseError);
+    }
 
     Ok(())
 }
+
+/// Removes the final OK of a response and returns what came before it. Leading and trailing
+/// blank lines are ignored, as their amount differs between firmwares. A response ending with
+/// `ERROR` instead is reported as [AtError::ErrorReply].
+pub(crate) fn strip_ok(data: &[u8]) -> Result<&[u8], AtError> {
+    if strip_error(data).is_some() {
+        return Err(AtError::ErrorReply(0));
+    }
+
+    strip_final_line(data, b"OK").ok_or(AtError::AtParseError)
+}
+
+/// Removes the final `ERROR` of a response and returns what came before it, ignoring the blank
+/// lines as [strip_ok] does. `None` if the response does not end with `ERROR`.
+pub(crate) fn strip_error(data: &[u8]) -> Option<&[u8]> {
+    strip_final_line(data, b"ERROR")
+}
+
+fn strip_final_line<'a>(data: &'a [u8], result_code: &[u8]) -> Option<&'a [u8]> {
+    let payload = trim_line_breaks(data).strip_suffix(result_code)?;
+
+    // The result code has to be on its own line
+    if !payload.is_empty() && !payload.ends_with(b"\n") && !payload.ends_with(b"\r") {
+        return None;
+    }
+
+    Some(trim_line_breaks(payload))
+}
+
+pub(crate) fn trim_line_breaks(data: &[u8]) -> &[u8] {
+    let is_line_break = |b: &u8| *b == b'\r' || *b == b'\n';
+    let start = data
+        .iter()
+        .position(|b| !is_line_break(b))
+        .unwrap_or(data.len());
+    let end = data
+        .iter()
+        .rposition(|b| !is_line_break(b))
+        .map_or(start, |i| i + 1);
+    &data[start..end]
+}
+
+/// Splits a response line such as `+XXX: a,"b,c",,d` into its fields. The `+XXX: ` header is
+/// skipped, quoted fields are returned without the quotes and may contain commas, empty fields
+/// are returned as empty slices. A quote that is never closed, or text between a closing quote
+/// and the next comma, is rejected with [AtError::AtParseError].
+pub fn parse_csv_line(line: &[u8]) -> Result<CsvFields<'_>, AtError> {
+    let line = trim_line_breaks(line);
+    let fields = match line.windows(2).position(|x| x == b": ") {
+        Some(header_end) if line.starts_with(b"+") && !line[..header_end].contains(&b'"') => {
+            &line[header_end + 2..]
+        }
+        _ => line,
+    };
+
+    let rest = (!fields.is_empty()).then_some(fields);
+    let mut unchecked = rest;
+    while let Some(fields) = unchecked {
+        unchecked = split_field(fields)?.1;
+    }
+
+    Ok(CsvFields { rest })
+}
+
+/// Splits the first field off, returning the fields after its comma if there is one
+fn split_field(fields: &[u8]) -> Result<(&[u8], Option<&[u8]>), AtError> {
+    match fields.strip_prefix(b"\"") {
+        Some(quoted) => match quoted.iter().position(|b| *b == b'"') {
+            Some(end) => match &quoted[end + 1..] {
+                [] => Ok((&quoted[..end], None)),
+                [b',', after @ ..] => Ok((&quoted[..end], Some(after))),
+                _ => Err(AtError::AtParseError),
+            },
+            None => Err(AtError::AtParseError),
+        },
+        None => match fields.iter().position(|b| *b == b',') {
+            Some(comma) => Ok((&fields[..comma], Some(&fields[comma + 1..]))),
+            None => Ok((fields, None)),
+        },
+    }
+}
+
+/// Fields of a line, see [parse_csv_line]
+pub struct CsvFields<'a> {
+    rest: Option<&'a [u8]>,
+}
+
+impl<'a> Iterator for CsvFields<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The fields were checked by parse_csv_line
+        let (field, after) = split_field(self.rest?).ok()?;
+        self.rest = after;
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verify_ok_with_varying_line_breaks() {
+        assert!(verify_ok(b"OK").is_ok());
+        assert!(verify_ok(b"\r\nOK\r").is_ok());
+        assert!(verify_ok(b"\r\nOK\r\n").is_ok());
+        assert!(verify_ok(b"\r\n\r\n\r\nOK\r\n\r\n").is_ok());
+        assert!(verify_ok(b"\nOK\n").is_ok());
+        assert!(verify_ok(b"\r\nNOK\r\n").is_err());
+        assert!(verify_ok(b"\r\n+CSOC: 1\r\n\r\nOK\r\n").is_err());
+    }
+
+    #[test]
+    fn test_validate_string_parameter() {
+        assert!(validate_string_parameter("sensors/temp").is_ok());
+        assert!(validate_string_parameter("").is_ok());
+        assert!(matches!(
+            validate_string_parameter("sensors/\"temp\""),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            validate_string_parameter("sensors\r\ntemp"),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            validate_string_parameter("sensors\ttemp"),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_strip_error_with_varying_line_breaks() {
+        assert_eq!(strip_error(b"ERROR"), Some(&b""[..]));
+        assert_eq!(strip_error(b"\r\nERROR\r"), Some(&b""[..]));
+        assert_eq!(strip_error(b"\r\n\r\n\r\nERROR\r\n\r\n"), Some(&b""[..]));
+        assert_eq!(strip_error(b"\nERROR\n"), Some(&b""[..]));
+        assert_eq!(
+            strip_error(b"\r\n+CSOC: 1\r\n\r\n\r\nERROR\r\n"),
+            Some(&b"+CSOC: 1"[..])
+        );
+        assert_eq!(strip_error(b"\r\n+CME ERROR\r\n"), None);
+        assert_eq!(strip_error(b"\r\nOK\r\n"), None);
+
+        assert!(matches!(
+            strip_ok(b"\r\n\r\nERROR\r\n"),
+            Err(AtError::ErrorReply(_))
+        ));
+        assert!(matches!(
+            verify_ok(b"\nERROR\n"),
+            Err(AtError::ErrorReply(_))
+        ));
+    }
+
+    #[test]
+    fn test_strip_ok_with_varying_line_breaks() {
+        assert_eq!(strip_ok(b"\r\n+CSOC: 1\r\n\r\nOK\r").unwrap(), b"+CSOC: 1");
+        assert_eq!(strip_ok(b"+CSOC: 1\r\nOK").unwrap(), b"+CSOC: 1");
+        assert_eq!(
+            strip_ok(b"\r\n\r\n+CSOC: 1\r\n\r\n\r\n\r\nOK\r\n\r\n").unwrap(),
+            b"+CSOC: 1"
+        );
+        assert!(strip_ok(b"\r\n+CSOC: 1\r\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_line_quoted_commas() {
+        let fields: std::vec::Vec<&[u8]> =
+            parse_csv_line(b"+CEVLOG: 3,\"24/10/14,08:01:02\",\"reset\"\r\n")
+                .unwrap()
+                .collect();
+        assert_eq!(fields, [&b"3"[..], b"24/10/14,08:01:02", b"reset"].to_vec());
+    }
+
+    #[test]
+    fn test_parse_csv_line_empty_fields() {
+        let fields: std::vec::Vec<&[u8]> = parse_csv_line(b"+CEREG: 4,1,,,9,,,\"00100100\",")
+            .unwrap()
+            .collect();
+        assert_eq!(
+            fields,
+            [&b"4"[..], b"1", b"", b"", b"9", b"", b"", b"00100100", b""].to_vec()
+        );
+        assert_eq!(parse_csv_line(b"").unwrap().count(), 0);
+        assert_eq!(
+            parse_csv_line(b"\"a: b\",c")
+                .unwrap()
+                .collect::<std::vec::Vec<_>>(),
+            [&b"a: b"[..], b"c"].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_line_unterminated_quote() {
+        for line in [
+            &b"+CEVLOG: 3,\"reset"[..],
+            b"+CEVLOG: \"reset,4",
+            b"+CEVLOG: 3,\"",
+        ] {
+            assert!(matches!(parse_csv_line(line), Err(AtError::AtParseError)));
+        }
+    }
+
+    #[test]
+    fn test_parse_csv_line_text_after_closing_quote() {
+        for line in [
+            &b"+CEVLOG: 3,\"reset\"x,4"[..],
+            b"+CEVLOG: \"reset\" ,4",
+            b"+CEVLOG: 3,\"reset\"x",
+        ] {
+            assert!(matches!(parse_csv_line(line), Err(AtError::AtParseError)));
+        }
+    }
+}
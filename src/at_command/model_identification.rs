@@ -1,6 +1,6 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{parse_csv_line, strip_ok, trim_line_breaks, AtRequest, BufferType};
 use crate::AtError;
 #[cfg(feature = "defmt")]
 use defmt::error;
@@ -10,6 +10,7 @@ pub struct ModelIdentification {}
 
 pub const MODEL_IDENTIFIER_SIZE: usize = 8;
 
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ModelIdentificationResponse {
     pub model: [u8; MODEL_IDENTIFIER_SIZE],
 }
@@ -54,3 +55,145 @@ impl AtRequest for ModelIdentification {
         Ok(ModelIdentificationResponse { model: id })
     }
 }
+
+const MANUFACTURER_MAX_SIZE: usize = 32;
+
+pub type Manufacturer = heapless::String<MANUFACTURER_MAX_SIZE>;
+
+/// Queries the manufacturer identification
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryManufacturer;
+
+impl QueryManufacturer {
+    fn get_manufacturer(data: &[u8]) -> Result<Manufacturer, AtError> {
+        let mut payload = strip_ok(data)?;
+        // With echo enabled the command is repeated in the first line
+        if payload.starts_with(b"AT+CGMI") {
+            let line_end = payload
+                .iter()
+                .position(|b| *b == b'\n')
+                .ok_or(AtError::AtParseError)?;
+            payload = trim_line_breaks(&payload[line_end..]);
+        }
+        if payload.is_empty() || payload.contains(&b'\n') {
+            return Err(AtError::AtParseError);
+        }
+
+        let manufacturer = core::str::from_utf8(payload).map_err(|_| AtError::AtParseError)?;
+        Ok(manufacturer.trim().try_into()?)
+    }
+}
+
+impl AtRequest for QueryManufacturer {
+    type Response = Manufacturer;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CGMI")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_manufacturer(data)
+    }
+}
+
+/// Command groups reported by [QueryCapabilities], unknown ones are ignored
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `+CGSM`, the 3GPP commands
+    pub gsm: bool,
+    /// `+CLTE`, the LTE and NB-IoT commands
+    pub lte: bool,
+    /// `+FCLASS`, the fax commands
+    pub fax: bool,
+    /// `+DS`, data compression
+    pub data_compression: bool,
+    /// `+ES`, error control
+    pub error_control: bool,
+}
+
+/// Queries the command groups the firmware supports with `AT+GCAP`, so features can be
+/// detected instead of assumed
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryCapabilities;
+
+impl AtRequest for QueryCapabilities {
+    type Response = Capabilities;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+GCAP")
+            .finish()
+    }
+
+    /// Parses `+GCAP: <group>[,<group>...]`, e.g. `+GCAP: +CGSM,+CLTE`
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let line = strip_ok(data)?;
+        if !line.starts_with(b"+GCAP: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        let mut capabilities = Capabilities::default();
+        for group in parse_csv_line(line)? {
+            match group.trim_ascii() {
+                b"+CGSM" => capabilities.gsm = true,
+                b"+CLTE" => capabilities.lte = true,
+                b"+FCLASS" => capabilities.fax = true,
+                b"+DS" => capabilities.data_compression = true,
+                b"+ES" => capabilities.error_control = true,
+                _ => {}
+            }
+        }
+
+        Ok(capabilities)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query_manufacturer_request() {
+        let mut buffer = [0; 512];
+        let result = QueryManufacturer.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGMI\r\n");
+    }
+
+    #[test]
+    fn test_query_manufacturer_response() {
+        let manufacturer = QueryManufacturer
+            .parse_response_struct(b"\r\nSIMCOM_Ltd\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(manufacturer.as_str(), "SIMCOM_Ltd");
+    }
+
+    #[test]
+    fn test_query_manufacturer_response_with_echo() {
+        let manufacturer = QueryManufacturer
+            .parse_response_struct(b"AT+CGMI\r\r\nSIMCOM_Ltd\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(manufacturer.as_str(), "SIMCOM_Ltd");
+    }
+
+    #[test]
+    fn test_query_capabilities() {
+        let mut buffer = [0; 512];
+        let result = QueryCapabilities.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+GCAP\r\n");
+
+        let capabilities = QueryCapabilities
+            .parse_response_struct(b"\r\n+GCAP: +CGSM,+CLTE,+MS\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(
+            capabilities,
+            Capabilities {
+                gsm: true,
+                lte: true,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use crate::at_command::mqtt::MQTTSessionWrapper::Disconnected;
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
-use crate::{AtError, Modem};
+use crate::at_command::{
+    parse_csv_line, validate_string_parameter, AtRequest, BufferType, FinishExact,
+};
+use crate::urc::Urc;
+use crate::{AtError, Modem, Timer, BUFFER_SIZE};
 use at_commands::builder::CommandBuilder;
 #[cfg(feature = "defmt")]
 use defmt::{error, info};
@@ -270,6 +273,8 @@ pub enum MQTTConnection {
 }
 
 impl MQTTConnection {
+    /// Publishes the message, a topic which can't be sent as a string parameter is rejected
+    /// with [AtError::InvalidParameter], see [validate_string_parameter]
     pub fn publish<T: Write, U: Read>(
         &self,
         message: &MQTTMessage,
@@ -304,7 +309,48 @@ pub struct MQTTSessionSettings<'a> {
     pub context_id: Option<u16>, // PDP context, AT+CGAT response
 }
 
-impl MQTTSessionSettings<'_> {
+/// Amount of certificate slots of the modem
+pub const MAX_CERTIFICATE_SLOTS: u8 = 3;
+
+/// Certificates used by a session over TLS, referenced by the slot they were loaded into
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MqttTlsSettings {
+    pub(crate) ca_slot: u8,
+    /// Client certificate and its key, for brokers which authenticate the client
+    pub(crate) client_certificate_slot: Option<u8>,
+}
+
+impl MqttTlsSettings {
+    /// Verifies the broker with the CA certificate of the slot
+    pub fn new(ca_slot: u8) -> Result<Self, AtError> {
+        validate_certificate_slot(ca_slot)?;
+        Ok(Self {
+            ca_slot,
+            client_certificate_slot: None,
+        })
+    }
+
+    /// The client certificate has to be in a different slot than the CA certificate
+    pub fn with_client_certificate(mut self, slot: u8) -> Result<Self, AtError> {
+        validate_certificate_slot(slot)?;
+        if slot == self.ca_slot {
+            return Err(AtError::InvalidParameter);
+        }
+        self.client_certificate_slot = Some(slot);
+        Ok(self)
+    }
+}
+
+fn validate_certificate_slot(slot: u8) -> Result<(), AtError> {
+    if slot >= MAX_CERTIFICATE_SLOTS {
+        return Err(AtError::InvalidParameter);
+    }
+
+    Ok(())
+}
+
+impl<'a> MQTTSessionSettings<'a> {
     pub fn new(server: &str, port: u16) -> MQTTSessionSettings<'_> {
         let timeout_ms = 5000;
         let buffer_size = 600;
@@ -334,27 +380,37 @@ impl MQTTSessionSettings<'_> {
         self.context_id = context_id;
         self
     }
+
+    /// Creates the session over TLS with `AT+CMQTTSNEW` instead of `AT+CMQNEW`
+    pub fn with_tls(self, tls: MqttTlsSettings) -> MqttTlsSessionSettings<'a> {
+        MqttTlsSessionSettings {
+            settings: self,
+            tls,
+        }
+    }
 }
 
 pub struct MqttSessionId {
     pub mqtt_id: u8,
 }
 
-impl MQTTSessionSettings<'_> {
-    fn get_session_id(data: &[u8]) -> Result<u8, AtError> {
-        let (mqtt_id,) = at_commands::parser::CommandParser::parse(data)
-            .expect_identifier(b"\r\n+CMQNEW: ")
-            .expect_int_parameter()
-            .expect_identifier(b"\r\n\r\nOK")
-            .finish()?;
+fn get_session_id(identifier: &[u8], data: &[u8]) -> Result<u8, AtError> {
+    let (mqtt_id,) = at_commands::parser::CommandParser::parse(data)
+        .expect_identifier(identifier)
+        .expect_int_parameter()
+        .expect_identifier(b"\r\n\r\nOK")
+        .finish()?;
 
-        Ok(mqtt_id as u8)
-    }
+    Ok(mqtt_id as u8)
 }
 
 impl AtRequest for MQTTSessionSettings<'_> {
     type Response = MqttSessionId;
 
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.server)
+    }
+
     fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
         CommandBuilder::create_set(buffer, true)
             .named("+CMQNEW")
@@ -363,17 +419,53 @@ impl AtRequest for MQTTSessionSettings<'_> {
             .with_int_parameter(self.timeout_ms)
             .with_int_parameter(self.buffer_size)
             // .with_optional_int_parameter(self.context_id)
-            .finish()
+            .finish_exact()
     }
 
     #[allow(deprecated)]
     fn parse_response(&self, data: &[u8]) -> Result<AtResponse, AtError> {
-        let mqtt_id = Self::get_session_id(data)?;
+        let mqtt_id = get_session_id(b"\r\n+CMQNEW: ", data)?;
         Ok(AtResponse::MQTTSessionCreated(mqtt_id))
     }
 
     fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
-        let mqtt_id = Self::get_session_id(data)?;
+        let mqtt_id = get_session_id(b"\r\n+CMQNEW: ", data)?;
+        Ok(MqttSessionId { mqtt_id })
+    }
+}
+
+/// Creates the session over TLS with `AT+CMQTTSNEW`, built with
+/// [MQTTSessionSettings::with_tls]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MqttTlsSessionSettings<'a> {
+    settings: MQTTSessionSettings<'a>,
+    tls: MqttTlsSettings,
+}
+
+impl AtRequest for MqttTlsSessionSettings<'_> {
+    type Response = MqttSessionId;
+
+    fn validate(&self) -> Result<(), AtError> {
+        self.settings.validate()
+    }
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let builder = CommandBuilder::create_set(buffer, true)
+            .named("+CMQTTSNEW")
+            .with_string_parameter(self.settings.server)
+            .with_int_parameter(self.settings.port)
+            .with_int_parameter(self.settings.timeout_ms)
+            .with_int_parameter(self.settings.buffer_size)
+            .with_int_parameter(self.tls.ca_slot);
+
+        match self.tls.client_certificate_slot {
+            Some(slot) => builder.with_int_parameter(slot).finish_exact(),
+            None => builder.finish_exact(),
+        }
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let mqtt_id = get_session_id(b"\r\n+CMQTTSNEW: ", data)?;
         Ok(MqttSessionId { mqtt_id })
     }
 }
@@ -526,6 +618,12 @@ impl<'a> MQTTConnectionSettings<'a> {
 impl AtRequest for MQTTConnectionSettingsWithID<'_> {
     type Response = ();
 
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.client_id)?;
+        validate_string_parameter(self.username)?;
+        validate_string_parameter(self.password)
+    }
+
     fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
         let version: u8 = match self.version {
             MQTTVersion::MQTT31 => 3,
@@ -541,7 +639,7 @@ impl AtRequest for MQTTConnectionSettingsWithID<'_> {
             .with_int_parameter(self.will_flag as u8)
             .with_string_parameter(self.username)
             .with_string_parameter(self.password)
-            .finish()
+            .finish_exact()
     }
 
     fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
@@ -572,7 +670,7 @@ impl AtRequest for MQTTRawData {
         at_commands::builder::CommandBuilder::create_set(buffer, true)
             .named("+CREVHEX")
             .with_string_parameter(format)
-            .finish()
+            .finish_exact()
     }
 
     fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
@@ -608,6 +706,10 @@ pub struct MQTTPublish<'a> {
 impl AtRequest for MQTTPublish<'_> {
     type Response = ();
 
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.topic)
+    }
+
     fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
         CommandBuilder::create_set(buffer, true)
             .named("+CMQPUB")
@@ -618,7 +720,7 @@ impl AtRequest for MQTTPublish<'_> {
             .with_int_parameter(self.dup as u8)
             .with_int_parameter(self.message.len() as i32)
             .with_string_parameter(self.message)
-            .finish()
+            .finish_exact()
     }
 
     fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
@@ -636,16 +738,512 @@ pub struct MQTTSubscribe<'a> {
 impl AtRequest for MQTTSubscribe<'_> {
     type Response = ();
 
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.topic)
+    }
+
     fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
         CommandBuilder::create_set(buffer, true)
             .named("+CMQSUB")
             .with_int_parameter(self.mqtt_id)
             .with_string_parameter(self.topic)
             .with_int_parameter(self.qos)
-            .finish()
+            .finish_exact()
     }
 
     fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
         Ok(())
     }
 }
+
+/// Longest topic kept from a [MqttMessageReceived]
+pub const MQTT_TOPIC_MAX_SIZE: usize = 128;
+/// Maximum amount of bytes kept from the message of a `+CMQPUB` URC, as long as a whole line so
+/// every message that could be read is kept. Longer lines make
+/// [MqttClient::poll] fail with [AtError::CapacityError]
+pub const MQTT_MESSAGE_MAX_SIZE: usize = BUFFER_SIZE;
+
+/// Unsolicited message sent by the modem when a message arrives on a subscribed topic
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttMessageReceived {
+    pub mqtt_id: u8,
+    pub topic: heapless::String<MQTT_TOPIC_MAX_SIZE>,
+    pub qos: u8,
+    pub retained: bool,
+    pub dup: bool,
+    /// Message as sent by the modem, hex encoded unless configured otherwise with
+    /// [MQTTRawData]
+    pub message: heapless::Vec<u8, MQTT_MESSAGE_MAX_SIZE>,
+}
+
+impl MqttMessageReceived {
+    /// Parses `+CMQPUB: <mqtt_id>,<topic>,<qos>,<retained>,<dup>,<message_len>,<message>`
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        if !data.starts_with(b"+CMQPUB: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        let mut fields: heapless::Vec<&[u8], 7> = heapless::Vec::new();
+        for field in parse_csv_line(data)? {
+            fields.push(field).map_err(|_| AtError::AtParseError)?;
+        }
+        let [mqtt_id, topic, qos, retained, dup, _message_len, message] = fields.as_slice() else {
+            return Err(AtError::AtParseError);
+        };
+
+        let parse_int = |field: &[u8]| -> Result<u8, AtError> {
+            core::str::from_utf8(field)
+                .ok()
+                .and_then(|field| field.parse().ok())
+                .ok_or(AtError::AtParseError)
+        };
+        let topic = core::str::from_utf8(topic).map_err(|_| AtError::AtParseError)?;
+
+        Ok(MqttMessageReceived {
+            mqtt_id: parse_int(mqtt_id)?,
+            topic: heapless::String::try_from(topic)?,
+            qos: parse_int(qos)?,
+            retained: parse_int(retained)? != 0,
+            dup: parse_int(dup)? != 0,
+            message: heapless::Vec::from_slice(message)?,
+        })
+    }
+}
+
+/// Unsolicited message sent by the modem when the connection of a session is lost, e.g. closed
+/// by the broker
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MqttDisconnected {
+    pub mqtt_id: u8,
+    /// Reason code, only reported by some firmwares
+    pub reason: Option<u8>,
+}
+
+impl MqttDisconnected {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (mqtt_id, reason) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CMQDISCON: ")
+            .expect_int_parameter()
+            .expect_optional_int_parameter()
+            .finish()?;
+
+        Ok(MqttDisconnected {
+            mqtt_id: mqtt_id as u8,
+            reason: reason.map(|reason| reason as u8),
+        })
+    }
+}
+
+/// Handle to a connected MQTT session, tracking the ID given by the modem
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MqttClient {
+    mqtt_id: u8,
+}
+
+impl MqttClient {
+    /// Creates a session, plain with [MQTTSessionSettings] or over TLS with
+    /// [MqttTlsSessionSettings], and connects it to the broker
+    pub fn connect<T: Write, U: Read, S: AtRequest<Response = MqttSessionId>>(
+        modem: &mut Modem<'_, T, U>,
+        session_settings: &S,
+        connection_settings: MQTTConnectionSettings,
+    ) -> Result<Self, AtError> {
+        let mqtt_id = modem.send_and_wait_response(session_settings)?.mqtt_id;
+
+        let connected = modem.send_and_wait_response(&connection_settings.with_mqtt_id(mqtt_id));
+        if let Err(e) = connected {
+            // Do not leak the session, the connection error is the relevant one
+            let _ = modem.send_and_wait_response(&CloseMQTTConnection { mqtt_id });
+            return Err(e);
+        }
+
+        Ok(Self { mqtt_id })
+    }
+
+    pub fn mqtt_id(&self) -> u8 {
+        self.mqtt_id
+    }
+
+    pub fn publish<T: Write, U: Read>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        message: &MQTTMessage,
+    ) -> Result<(), AtError> {
+        modem.send_and_wait_response(&MQTTPublish {
+            mqtt_id: self.mqtt_id,
+            topic: message.topic,
+            qos: message.qos,
+            retained: message.retained,
+            dup: message.dup,
+            message: message.message,
+        })
+    }
+
+    /// Subscribes to the topic, its messages are returned by [MqttClient::poll]
+    pub fn subscribe<T: Write, U: Read>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        topic: &str,
+        qos: u8,
+    ) -> Result<(), AtError> {
+        modem.send_and_wait_response(&MQTTSubscribe {
+            mqtt_id: self.mqtt_id,
+            topic,
+            qos,
+        })
+    }
+
+    /// Waits up to `timeout_ms` for a message of this session. `Ok(None)` means none arrived
+    /// in time. Messages of other sessions are kept pending, like the other URCs received in
+    /// the meantime, see [Modem::wait_for_urc].
+    pub fn poll<T: Write, U: Read, C: Timer>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<Option<MqttMessageReceived>, AtError> {
+        let mqtt_id = self.mqtt_id;
+        match modem.wait_for_urc_matching(
+            |urc| matches!(urc, Urc::MqttMessage(message) if message.mqtt_id == mqtt_id),
+            timeout_ms,
+            timer,
+        ) {
+            Ok(Urc::MqttMessage(message)) => Ok(Some(message)),
+            Ok(_) => unreachable!(),
+            Err(AtError::Timeout) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the loss of this session's connection if it was reported in the URCs kept
+    /// pending, e.g. while waiting in [MqttClient::poll]. The session has to be connected again
+    /// with [MqttClient::connect] afterwards.
+    pub fn take_disconnect<T: Write, U: Read>(
+        &self,
+        modem: &mut Modem<'_, T, U>,
+    ) -> Option<MqttDisconnected> {
+        match modem.take_pending_urc(|urc| {
+            matches!(urc, Urc::MqttDisconnected(disconnected) if disconnected.mqtt_id == self.mqtt_id)
+        })? {
+            Urc::MqttDisconnected(disconnected) => Some(disconnected),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn disconnect<T: Write, U: Read>(self, modem: &mut Modem<'_, T, U>) -> Result<(), AtError> {
+        modem.send_and_wait_response(&CloseMQTTConnection {
+            mqtt_id: self.mqtt_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockTimer, MockWriter};
+    use crate::UrcQueue;
+
+    fn connection_settings() -> MQTTConnectionSettings<'static> {
+        MQTTConnectionSettings {
+            version: MQTTVersion::MQTT311,
+            client_id: "sim7020",
+            keepalive_interval: 60,
+            clean_session: true,
+            will_flag: false,
+            username: "",
+            password: "",
+        }
+    }
+
+    #[test]
+    fn test_parse_mqtt_message_received() {
+        let message =
+            MqttMessageReceived::parse(b"+CMQPUB: 0,\"sensors/temp\",1,0,0,4,\"3231\"").unwrap();
+        assert_eq!(message.mqtt_id, 0);
+        assert_eq!(message.topic.as_str(), "sensors/temp");
+        assert_eq!(message.qos, 1);
+        assert!(!message.retained);
+        assert_eq!(message.message.as_slice(), b"3231");
+
+        assert!(MqttMessageReceived::parse(b"+CMQPUB: 0,\"sensors/temp\",1").is_err());
+    }
+
+    #[test]
+    fn test_mqtt_client_publish() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CMQNEW: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let session_settings = MQTTSessionSettings::new("broker.example", 1883);
+
+        let mut client =
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()).unwrap();
+        assert_eq!(client.mqtt_id(), 0);
+
+        client
+            .publish(
+                &mut modem,
+                &MQTTMessage {
+                    topic: "sensors/temp",
+                    qos: 0,
+                    retained: false,
+                    dup: false,
+                    message: b"21",
+                },
+            )
+            .unwrap();
+        assert!(modem
+            .writer
+            .written
+            .ends_with(b"AT+CMQPUB=0,\"sensors/temp\",0,0,0,2,\"21\"\r\n"));
+
+        client.disconnect(&mut modem).unwrap();
+        assert!(modem.writer.written.ends_with(b"AT+CMQDISCON=0\r\n"));
+    }
+
+    #[test]
+    fn test_mqtt_client_rejects_quoted_topic() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CMQNEW: 0\r\n\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let session_settings = MQTTSessionSettings::new("broker.example", 1883);
+
+        let mut client =
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()).unwrap();
+        let written = modem.writer.written.len();
+
+        assert!(matches!(
+            client.publish(
+                &mut modem,
+                &MQTTMessage {
+                    topic: "sensors/\"temp\"",
+                    qos: 0,
+                    retained: false,
+                    dup: false,
+                    message: b"21",
+                },
+            ),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            client.subscribe(&mut modem, "sensors/\"temp\"", 0),
+            Err(AtError::InvalidParameter)
+        ));
+        assert_eq!(modem.writer.written.len(), written);
+    }
+
+    #[test]
+    fn test_mqtt_connect_rejects_quoted_parameters() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CMQNEW: 0\r\n\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let session_settings = MQTTSessionSettings::new("broker\".example", 1883);
+        assert!(matches!(
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()),
+            Err(AtError::InvalidParameter)
+        ));
+        assert_eq!(modem.writer.written, b"ATE0\r\n");
+
+        let session_settings = MQTTSessionSettings::new("broker.example", 1883);
+        let connection_settings = MQTTConnectionSettings {
+            password: "pass\r\nAT+CFUN=0",
+            ..connection_settings()
+        };
+        assert!(matches!(
+            MqttClient::connect(&mut modem, &session_settings, connection_settings),
+            Err(AtError::InvalidParameter)
+        ));
+        // The session created is closed, the connection is never sent
+        assert!(modem.writer.written.ends_with(b"AT+CMQDISCON=0\r\n"));
+        assert!(!modem
+            .writer
+            .written
+            .windows(b"+CMQCON".len())
+            .any(|window| window == b"+CMQCON"));
+    }
+
+    #[test]
+    fn test_mqtt_client_poll() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CMQNEW: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSONMI: 1,2,AB\r\n\r\n+CMQPUB: 1,\"cmd\",0,0,0,2,\"4F\"\r\n\r\n+CMQPUB: 0,\"cmd\",0,0,0,2,\"4E\"\r\n",
+        );
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(1);
+        let session_settings = MQTTSessionSettings::new("broker.example", 1883);
+
+        let mut client =
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()).unwrap();
+        client.subscribe(&mut modem, "cmd", 0).unwrap();
+        assert!(modem.writer.written.ends_with(b"AT+CMQSUB=0,\"cmd\",0\r\n"));
+
+        let message = client.poll(&mut modem, 1000, &timer).unwrap().unwrap();
+        assert_eq!(message.topic.as_str(), "cmd");
+        assert_eq!(message.message.as_slice(), b"4E");
+
+        // The socket data and the message of the other session are not lost
+        assert_eq!(
+            modem.pop_pending_urc().map(|urc| urc.kind()),
+            Some(crate::urc::UrcKind::SocketData)
+        );
+        match modem.pop_pending_urc() {
+            Some(Urc::MqttMessage(message)) => assert_eq!(message.mqtt_id, 1),
+            _ => panic!("Expected pending Urc::MqttMessage"),
+        }
+    }
+
+    #[test]
+    fn test_mqtt_client_poll_long_message() {
+        let mut response = std::vec::Vec::from(
+            &b"\r\nOK\r\n\r\n+CMQNEW: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CMQPUB: 0,\"cmd\",0,0,0,400,\""
+                [..],
+        );
+        response.extend_from_slice(&[b'A'; 400]);
+        response.extend_from_slice(b"\"\r\n");
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(&response);
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+        let session_settings = MQTTSessionSettings::new("broker.example", 1883);
+
+        let mut client =
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()).unwrap();
+        let message = client.poll(&mut modem, 1000, &timer).unwrap().unwrap();
+        assert_eq!(message.message.as_slice(), &[b'A'; 400]);
+    }
+
+    #[test]
+    fn test_mqtt_client_poll_message_longer_than_a_line() {
+        let mut response = std::vec::Vec::from(
+            &b"\r\nOK\r\n\r\n+CMQNEW: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CMQPUB: 0,\"cmd\",0,0,0,600,\""
+                [..],
+        );
+        response.extend_from_slice(&[b'A'; 600]);
+        response.extend_from_slice(b"\"\r\n");
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(&response);
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+        let session_settings = MQTTSessionSettings::new("broker.example", 1883);
+
+        let mut client =
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()).unwrap();
+        assert!(matches!(
+            client.poll(&mut modem, 1000, &timer),
+            Err(AtError::CapacityError)
+        ));
+    }
+
+    #[test]
+    fn test_parse_broker_disconnect() {
+        assert_eq!(
+            MqttDisconnected::parse(b"+CMQDISCON: 0").unwrap(),
+            MqttDisconnected {
+                mqtt_id: 0,
+                reason: None
+            }
+        );
+        assert_eq!(
+            MqttDisconnected::parse(b"+CMQDISCON: 1,3").unwrap(),
+            MqttDisconnected {
+                mqtt_id: 1,
+                reason: Some(3)
+            }
+        );
+    }
+
+    #[test]
+    fn test_mqtt_client_detects_disconnect() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CMQNEW: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CMQDISCON: 0,3\r\n\r\nOK\r\n\r\nOK\r\n",
+        );
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(10);
+        let session_settings = MQTTSessionSettings::new("broker.example", 1883);
+
+        let mut client =
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()).unwrap();
+        assert!(client.take_disconnect(&mut modem).is_none());
+        assert!(client.poll(&mut modem, 30, &timer).unwrap().is_none());
+
+        let disconnected = client.take_disconnect(&mut modem).unwrap();
+        assert_eq!(disconnected.reason, Some(3));
+        assert!(modem.pop_pending_urc().is_none());
+    }
+
+    #[test]
+    fn test_create_session_with_tls() {
+        let mut buffer = [0; 512];
+        let tls = MqttTlsSettings::new(0)
+            .unwrap()
+            .with_client_certificate(1)
+            .unwrap();
+        let settings = MQTTSessionSettings::new("broker.example", 8883).with_tls(tls);
+
+        let result = settings.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CMQTTSNEW=\"broker.example\",8883,5000,600,0,1\r\n"
+        );
+
+        let settings = MQTTSessionSettings::new("broker.example", 8883)
+            .with_tls(MqttTlsSettings::new(2).unwrap());
+        let result = settings.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CMQTTSNEW=\"broker.example\",8883,5000,600,2\r\n"
+        );
+
+        let session = settings
+            .parse_response_struct(b"\r\n+CMQTTSNEW: 0\r\n\r\nOK")
+            .unwrap();
+        assert_eq!(session.mqtt_id, 0);
+    }
+
+    #[test]
+    fn test_mqtt_client_connect_with_tls() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CMQTTSNEW: 1\r\n\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let session_settings = MQTTSessionSettings::new("broker.example", 8883)
+            .with_tls(MqttTlsSettings::new(0).unwrap());
+
+        let client =
+            MqttClient::connect(&mut modem, &session_settings, connection_settings()).unwrap();
+        assert_eq!(client.mqtt_id(), 1);
+        assert!(modem
+            .writer
+            .written
+            .starts_with(b"ATE0\r\nAT+CMQTTSNEW=\"broker.example\",8883,5000,600,0\r\n"));
+    }
+
+    #[test]
+    fn test_tls_unknown_certificate_slot() {
+        assert!(matches!(
+            MqttTlsSettings::new(MAX_CERTIFICATE_SLOTS),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            MqttTlsSettings::new(0)
+                .unwrap()
+                .with_client_certificate(MAX_CERTIFICATE_SLOTS),
+            Err(AtError::InvalidParameter)
+        ));
+        // A slot holds a single certificate
+        assert!(matches!(
+            MqttTlsSettings::new(1).unwrap().with_client_certificate(1),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+}
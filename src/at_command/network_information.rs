@@ -1,10 +1,15 @@
+use crate::at_command::network_registration_status::{
+    EpsNetworkRegistration, NetworkRegistrationStatus,
+};
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
-use crate::AtError;
+use crate::at_command::{parse_csv_line, strip_ok, verify_ok, AtRequest, BufferType, FinishExact};
+use crate::{AtError, Modem};
 use at_commands::parser::CommandParser;
+use embedded_io::{Read, Write};
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NetworkFormat {
     LongAlphanumeric,
     ShortAlphanumeric,
@@ -40,25 +45,47 @@ impl From<i32> for NetworkMode {
     }
 }
 
+/// Radio access technology the modem is camped on
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessTechnology {
+    CatM,
+    NBIoT,
+    Unknown,
+}
+
+impl From<i32> for AccessTechnology {
+    fn from(value: i32) -> Self {
+        match value {
+            7 => Self::CatM,
+            9 => Self::NBIoT,
+            _ => Self::Unknown,
+        }
+    }
+}
+
 /// TA returns a list of quadruplets, each representing an operator present in
 /// the network. Any of the formats may be unavailable and should then be an
 /// empty field. The list of operators shall be in order: home network,
 /// networks referenced in SIM, and other networks.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct NetworkInformation;
 
 const OPERATOR_MAX_SIZE: usize = 16;
 
 pub type NetworkOperator = heapless::String<OPERATOR_MAX_SIZE>;
 
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct NetworkInformationState {
     pub mode: NetworkMode,
     pub format: NetworkFormat,
     pub operator: Option<NetworkOperator>,
+    pub access_technology: Option<AccessTechnology>,
 }
 
 impl NetworkInformation {
     fn get_network_info(data: &[u8]) -> Result<NetworkInformationState, AtError> {
-        let (mode, format, operator, _access_technology) = CommandParser::parse(data)
+        let (mode, format, operator, access_technology) = CommandParser::parse(data)
             .expect_identifier(b"\r\n+COPS: ")
             .expect_int_parameter()
             .expect_optional_int_parameter()
@@ -80,6 +107,7 @@ impl NetworkInformation {
             format,
             mode,
             operator,
+            access_technology: access_technology.map(AccessTechnology::from),
         })
     }
 }
@@ -108,3 +136,385 @@ impl AtRequest for NetworkInformation {
         Ok(network)
     }
 }
+
+/// Sets the format of the operator reported by [NetworkInformation]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetOperatorFormat {
+    pub format: NetworkFormat,
+}
+
+impl AtRequest for SetOperatorFormat {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let format = match self.format {
+            NetworkFormat::LongAlphanumeric => 0,
+            NetworkFormat::ShortAlphanumeric => 1,
+            NetworkFormat::Numeric | NetworkFormat::Unknown => 2,
+        };
+        // Mode 3 only changes the format
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+COPS")
+            .with_int_parameter(3)
+            .with_int_parameter(format)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+const PLMN_MAX_SIZE: usize = 6;
+
+/// A PLMN is the 3 digits MCC followed by the 2 or 3 digits MNC
+fn is_valid_plmn(plmn: &str) -> bool {
+    (5..=PLMN_MAX_SIZE).contains(&plmn.len()) && plmn.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredPlmn {
+    /// MCC followed by the 2 or 3 digits MNC, checked with [is_valid_plmn]
+    plmn: heapless::String<PLMN_MAX_SIZE>,
+    pub roaming: bool,
+}
+
+impl RegisteredPlmn {
+    /// MCC followed by the 2 or 3 digits MNC
+    pub fn plmn(&self) -> &str {
+        &self.plmn
+    }
+
+    pub fn mcc(&self) -> &str {
+        &self.plmn[..3]
+    }
+
+    pub fn mnc(&self) -> &str {
+        &self.plmn[3..]
+    }
+}
+
+/// Reports the PLMN the modem is registered on and whether it is roaming. The operator format
+/// is switched to numeric with [SetOperatorFormat] to get it and set back to the previous one
+/// afterwards.
+pub struct QueryRegisteredPlmn;
+
+impl QueryRegisteredPlmn {
+    /// Returns `None` if the modem is not registered
+    pub fn query<T: Write, U: Read>(
+        modem: &mut Modem<'_, T, U>,
+    ) -> Result<Option<RegisteredPlmn>, AtError> {
+        let registration = modem.send_and_wait_response(&EpsNetworkRegistration)?;
+        let roaming = match registration.status {
+            NetworkRegistrationStatus::RegisteredHomeNetwork
+            | NetworkRegistrationStatus::SMSOnlyHome => false,
+            NetworkRegistrationStatus::RegisteredRoaming
+            | NetworkRegistrationStatus::SMSOnlyRoaming => true,
+            _ => return Ok(None),
+        };
+
+        let mut network = modem.send_and_wait_response(&NetworkInformation)?;
+        let previous_format = network.format;
+        if !matches!(previous_format, NetworkFormat::Numeric) {
+            modem.send_and_wait_response(&SetOperatorFormat {
+                format: NetworkFormat::Numeric,
+            })?;
+            let numeric = modem.send_and_wait_response(&NetworkInformation);
+            // Without a format reported there is none to restore
+            if !matches!(previous_format, NetworkFormat::Unknown) {
+                modem.send_and_wait_response(&SetOperatorFormat {
+                    format: previous_format,
+                })?;
+            }
+            network = numeric?;
+        }
+
+        let Some(plmn) = network.operator else {
+            return Ok(None);
+        };
+        if !is_valid_plmn(&plmn) {
+            return Err(AtError::AtParseError);
+        }
+
+        Ok(Some(RegisteredPlmn {
+            plmn: plmn.as_str().try_into()?,
+            roaming,
+        }))
+    }
+}
+
+/// Most entries kept by [QueryPreferredOperators] and written by [PreferredOperatorList]
+pub const MAX_PREFERRED_OPERATORS: usize = 8;
+
+/// Writes an entry of the preferred operator list with `AT+CPOL=<index>,2,"<plmn>"`. Entries
+/// with a lower index are tried first, e.g. to prefer some networks while roaming.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetPreferredOperator<'a> {
+    pub(crate) index: u16,
+    pub(crate) plmn: &'a str,
+}
+
+impl<'a> SetPreferredOperator<'a> {
+    /// The index starts at 1, the PLMN is given in numeric format, e.g. `21401`
+    pub fn new(index: u16, plmn: &'a str) -> Result<Self, AtError> {
+        if index < 1 || !is_valid_plmn(plmn) {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { index, plmn })
+    }
+}
+
+impl AtRequest for SetPreferredOperator<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CPOL")
+            .with_int_parameter(self.index as i32)
+            .with_int_parameter(2)
+            .with_string_parameter(self.plmn)
+            .finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Removes the entry at the given index of the preferred operator list with `AT+CPOL=<index>`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RemovePreferredOperator {
+    pub index: u16,
+}
+
+impl AtRequest for RemovePreferredOperator {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CPOL")
+            .with_int_parameter(self.index as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreferredOperator {
+    pub index: u16,
+    /// MCC followed by the 2 or 3 digits MNC
+    pub plmn: heapless::String<PLMN_MAX_SIZE>,
+}
+
+pub type PreferredOperators = heapless::Vec<PreferredOperator, MAX_PREFERRED_OPERATORS>;
+
+/// Reads the preferred operator list with `AT+CPOL?`. The modem reports the operators in the
+/// format used to write them, the list is expected to be written in numeric format as done by
+/// [SetPreferredOperator].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryPreferredOperators;
+
+impl AtRequest for QueryPreferredOperators {
+    type Response = PreferredOperators;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CPOL")
+            .finish()
+    }
+
+    /// Parses a `+CPOL: <index>,2,"<plmn>"` line per entry, an empty list is a plain OK
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let mut operators = PreferredOperators::new();
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with(b"+CPOL: ") {
+                return Err(AtError::AtParseError);
+            }
+
+            let mut fields = parse_csv_line(line)?
+                .map(|field| core::str::from_utf8(field).map_err(|_| AtError::AtParseError));
+            let index = fields
+                .next()
+                .transpose()?
+                .and_then(|index| index.parse().ok())
+                .ok_or(AtError::AtParseError)?;
+            if fields.next().transpose()? != Some("2") {
+                return Err(AtError::AtParseError);
+            }
+            let plmn = fields
+                .next()
+                .transpose()?
+                .filter(|plmn| is_valid_plmn(plmn))
+                .ok_or(AtError::AtParseError)?;
+
+            operators
+                .push(PreferredOperator {
+                    index,
+                    plmn: heapless::String::try_from(plmn)?,
+                })
+                .map_err(|_| AtError::CapacityError)?;
+        }
+
+        Ok(operators)
+    }
+}
+
+pub struct PreferredOperatorList;
+
+impl PreferredOperatorList {
+    /// Writes the PLMNs from the highest priority to the lowest, starting at index 1. Nothing is
+    /// written if any of them is invalid. Entries past the written ones are kept, remove them
+    /// with [RemovePreferredOperator].
+    pub fn write<T: Write, U: Read>(
+        modem: &mut Modem<'_, T, U>,
+        plmns: &[&str],
+    ) -> Result<(), AtError> {
+        if plmns.len() > MAX_PREFERRED_OPERATORS || !plmns.iter().all(|plmn| is_valid_plmn(plmn)) {
+            return Err(AtError::InvalidParameter);
+        }
+
+        for (index, plmn) in (1..).zip(plmns) {
+            modem.send_and_wait_response(&SetPreferredOperator::new(index, plmn)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+
+    #[test]
+    fn test_set_operator_format_request() {
+        let mut buffer = [0; 512];
+        let request = SetOperatorFormat {
+            format: NetworkFormat::Numeric,
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+COPS=3,2\r\n");
+    }
+
+    #[test]
+    fn test_query_registered_plmn_home() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\
+            \r\n+CEREG: 0,1\r\n\r\nOK\r\n\
+            \r\n+COPS: 0,0,\"Movistar\",9\r\n\r\nOK\r\n\
+            \r\nOK\r\n\
+            \r\n+COPS: 0,2,\"21401\",9\r\n\r\nOK\r\n\
+            \r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let plmn = QueryRegisteredPlmn::query(&mut modem).unwrap().unwrap();
+        assert_eq!(plmn.plmn(), "21401");
+        assert_eq!(plmn.mcc(), "214");
+        assert_eq!(plmn.mnc(), "01");
+        assert!(!plmn.roaming);
+        drop(modem);
+
+        // The long alphanumeric format is set back
+        assert!(writer
+            .written
+            .ends_with(b"AT+COPS?\r\nAT+COPS=3,2\r\nAT+COPS?\r\nAT+COPS=3,0\r\n"));
+    }
+
+    #[test]
+    fn test_query_registered_plmn_roaming() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\
+            \r\n+CEREG: 0,5\r\n\r\nOK\r\n\
+            \r\n+COPS: 0,2,\"310260\",9\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let plmn = QueryRegisteredPlmn::query(&mut modem).unwrap().unwrap();
+        assert_eq!(plmn.mcc(), "310");
+        assert_eq!(plmn.mnc(), "260");
+        assert!(plmn.roaming);
+        drop(modem);
+
+        // Already numeric, the format is not changed
+        assert!(!writer.written.windows(8).any(|w| w == b"+COPS=3,"));
+    }
+
+    #[test]
+    fn test_query_registered_plmn_not_registered() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CEREG: 0,2\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        assert!(QueryRegisteredPlmn::query(&mut modem).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_preferred_operator_list() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        PreferredOperatorList::write(&mut modem, &["21401", "310260"]).unwrap();
+        assert_eq!(
+            modem.writer.written,
+            b"ATE0\r\nAT+CPOL=1,2,\"21401\"\r\nAT+CPOL=2,2,\"310260\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_write_invalid_preferred_operator_list() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        for plmns in [
+            ["21401", "2140"],
+            ["21401", "21401A"],
+            ["21401", "\"2140\""],
+        ] {
+            assert!(matches!(
+                PreferredOperatorList::write(&mut modem, &plmns),
+                Err(AtError::InvalidParameter)
+            ));
+        }
+        assert_eq!(modem.writer.written, b"ATE0\r\n");
+        assert!(matches!(
+            SetPreferredOperator::new(0, "21401"),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_query_preferred_operators() {
+        let operators = QueryPreferredOperators
+            .parse_response_struct(
+                b"\r\n+CPOL: 1,2,\"21401\"\r\n+CPOL: 2,2,\"310260\"\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(operators.len(), 2);
+        assert_eq!(operators[0].index, 1);
+        assert_eq!(operators[0].plmn.as_str(), "21401");
+        assert_eq!(operators[1].plmn.as_str(), "310260");
+
+        assert!(QueryPreferredOperators
+            .parse_response_struct(b"\r\nOK\r\n")
+            .unwrap()
+            .is_empty());
+        assert!(QueryPreferredOperators
+            .parse_response_struct(b"\r\n+CPOL: 1,0,\"Movistar\"\r\n\r\nOK\r\n")
+            .is_err());
+    }
+}
@@ -1,6 +1,6 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{strip_ok, AtRequest, BufferType};
 use crate::AtError;
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -24,6 +24,7 @@ impl From<i32> for UnsolicitedResultCodes {
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NetworkRegistrationStatus {
     NotRegistered,
     RegisteredHomeNetwork,
@@ -101,3 +102,115 @@ impl AtRequest for NetworkRegistration {
         })
     }
 }
+
+const TRACKING_AREA_CODE_MAX_SIZE: usize = 4;
+const CELL_ID_MAX_SIZE: usize = 8;
+
+/// Unsolicited EPS registration message, enabled with `AT+CEREG=<n>` where n > 0
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpsRegistrationReport {
+    pub status: NetworkRegistrationStatus,
+    /// Tracking area code in hex format
+    pub tracking_area_code: Option<heapless::String<TRACKING_AREA_CODE_MAX_SIZE>>,
+    /// Cell ID in hex format
+    pub cell_id: Option<heapless::String<CELL_ID_MAX_SIZE>>,
+}
+
+impl EpsRegistrationReport {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (stat, tracking_area_code, cell_id) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CEREG: ")
+            .expect_int_parameter()
+            .expect_optional_string_parameter()
+            .expect_optional_string_parameter()
+            .finish()?;
+
+        if !(0..=7).contains(&stat) {
+            return Err(AtError::AtParseError);
+        }
+
+        Ok(EpsRegistrationReport {
+            status: NetworkRegistrationStatus::from(stat),
+            tracking_area_code: tracking_area_code.map(|x| x.try_into()).transpose()?,
+            cell_id: cell_id.map(|x| x.try_into()).transpose()?,
+        })
+    }
+}
+
+/// Queries the EPS registration, the location is only reported if it was enabled with
+/// `AT+CEREG=2`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EpsNetworkRegistration;
+
+impl EpsNetworkRegistration {
+    fn get_report(data: &[u8]) -> Result<EpsRegistrationReport, AtError> {
+        let (_n, stat, tracking_area_code, cell_id, _access_technology) =
+            at_commands::parser::CommandParser::parse(strip_ok(data)?)
+                .expect_identifier(b"+CEREG: ")
+                .expect_int_parameter()
+                .expect_int_parameter()
+                .expect_optional_string_parameter()
+                .expect_optional_string_parameter()
+                .expect_optional_int_parameter()
+                .finish()?;
+
+        if !(0..=7).contains(&stat) {
+            return Err(AtError::AtParseError);
+        }
+
+        Ok(EpsRegistrationReport {
+            status: NetworkRegistrationStatus::from(stat),
+            tracking_area_code: tracking_area_code.map(|x| x.try_into()).transpose()?,
+            cell_id: cell_id.map(|x| x.try_into()).transpose()?,
+        })
+    }
+}
+
+impl AtRequest for EpsNetworkRegistration {
+    type Response = EpsRegistrationReport;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CEREG")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_report(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_eps_network_registration_request() {
+        let mut buffer = [0; 512];
+        let result = EpsNetworkRegistration.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CEREG?\r\n");
+    }
+
+    #[test]
+    fn test_eps_network_registration_response() {
+        let report = EpsNetworkRegistration
+            .parse_response_struct(b"\r\n+CEREG: 2,1,\"1A2B\",\"01A2B3C4\",9\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(
+            report.status,
+            NetworkRegistrationStatus::RegisteredHomeNetwork
+        );
+        assert_eq!(report.tracking_area_code.unwrap().as_str(), "1A2B");
+        assert_eq!(report.cell_id.unwrap().as_str(), "01A2B3C4");
+
+        let report = EpsNetworkRegistration
+            .parse_response_struct(b"\r\n+CEREG: 0,2\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(
+            report.status,
+            NetworkRegistrationStatus::NotRegisteredSearching
+        );
+        assert!(report.cell_id.is_none());
+    }
+}
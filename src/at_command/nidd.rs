@@ -0,0 +1,124 @@
+use crate::at_command::{verify_ok, AtRequest, BufferType, FinishExact};
+use crate::AtError;
+
+/// Maximum amount of bytes kept from the data of a `+CRTDCP` URC
+pub const NIDD_DATA_MAX_SIZE: usize = 256;
+
+/// Sends data over a Non-IP context, see
+/// [DefinePDPContext](crate::at_command::pdp_context::DefinePDPContext), with `AT+CSODCP`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SendNonIpData<'a> {
+    pub(crate) cid: i32,
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> SendNonIpData<'a> {
+    /// The data has to be hex encoded, e.g. `b"48656C6C6F"`
+    pub fn new(cid: i32, data: &'a [u8]) -> Result<Self, AtError> {
+        if data.is_empty()
+            || !data.len().is_multiple_of(2)
+            || !data.iter().all(u8::is_ascii_hexdigit)
+        {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { cid, data })
+    }
+}
+
+impl AtRequest for SendNonIpData<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSODCP")
+            .with_int_parameter(self.cid)
+            .with_int_parameter((self.data.len() / 2) as i32)
+            .with_raw_parameter(self.data)
+            .finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Enables the report of the data received over Non-IP contexts with [NonIpDataReceived],
+/// with `AT+CRTDCP`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetNonIpDataReporting {
+    pub enable: bool,
+}
+
+impl AtRequest for SetNonIpDataReporting {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CRTDCP")
+            .with_int_parameter(self.enable as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Unsolicited message sent by the modem when data arrives on a Non-IP context
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonIpDataReceived {
+    pub cid: i32,
+    /// Length of the data in bytes
+    pub data_len: u16,
+    /// Hex encoded data
+    pub data: heapless::Vec<u8, NIDD_DATA_MAX_SIZE>,
+}
+
+impl NonIpDataReceived {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (cid, data_len, payload) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CRTDCP: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .expect_raw_string()
+            .finish()?;
+
+        Ok(NonIpDataReceived {
+            cid,
+            data_len: u16::try_from(data_len).map_err(|_| AtError::AtParseError)?,
+            data: heapless::Vec::from_slice(payload.trim_matches('"').as_bytes())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_send_non_ip_data() {
+        let mut buffer = [0; 512];
+
+        let request = SendNonIpData::new(1, b"48656C6C6F").unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSODCP=1,5,48656C6C6F\r\n"
+        );
+
+        assert!(matches!(
+            SendNonIpData::new(1, b"Hello"),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_parse_non_ip_data_received() {
+        let received = NonIpDataReceived::parse(b"+CRTDCP: 1,2,\"AB01\"").unwrap();
+        assert_eq!(received.cid, 1);
+        assert_eq!(received.data_len, 2);
+        assert_eq!(received.data.as_slice(), b"AB01");
+    }
+}
@@ -1,5 +1,6 @@
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{validate_string_parameter, AtRequest, BufferType, FinishExact};
 use crate::AtError;
+use chrono::NaiveDateTime;
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct StartQueryNTP<'a> {
@@ -10,17 +11,22 @@ pub struct StartQueryNTP<'a> {
 impl AtRequest for StartQueryNTP<'_> {
     type Response = ();
 
+    fn validate(&self) -> Result<(), AtError> {
+        validate_string_parameter(self.url)?;
+        self.tzinfo.map_or(Ok(()), validate_string_parameter)
+    }
+
     fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
         match &self.tzinfo {
             None => at_commands::builder::CommandBuilder::create_set(buffer, true)
                 .named("+CSNTPSTART")
                 .with_string_parameter(self.url)
-                .finish(),
+                .finish_exact(),
             Some(tzinfo) => at_commands::builder::CommandBuilder::create_set(buffer, true)
                 .named("+CSNTPSTART")
                 .with_string_parameter(self.url)
                 .with_string_parameter(tzinfo)
-                .finish(),
+                .finish_exact(),
         }
     }
 
@@ -45,3 +51,38 @@ impl AtRequest for StopQueryNTP {
         Ok(())
     }
 }
+
+/// Unsolicited message sent by the modem once a [StartQueryNTP] finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct NtpSynchronized {
+    pub time: NaiveDateTime,
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for NtpSynchronized {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "NtpSynchronized {{ time: {} }}",
+            self.time.and_utc().timestamp()
+        )
+    }
+}
+
+impl NtpSynchronized {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (parsed,) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CSNTP: ")
+            .expect_raw_string()
+            .finish()?;
+        // 24/10/14,08:01:02.000 the time zone and fractions are not taken into account, same as
+        // with the clock
+        let parsed = parsed.trim_matches('"');
+        let time = NaiveDateTime::parse_from_str(
+            parsed.get(..17).ok_or(AtError::ParseClockError)?,
+            "%y/%m/%d,%H:%M:%S",
+        )?;
+
+        Ok(NtpSynchronized { time })
+    }
+}
@@ -1,6 +1,9 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
+use crate::at_command::{
+    parse_csv_line, strip_ok, validate_string_parameter, verify_ok, AtRequest, BufferType,
+    FinishExact,
+};
 use crate::AtError;
 use at_commands::parser::CommandParser;
 #[cfg(feature = "defmt")]
@@ -71,3 +74,444 @@ impl AtRequest for PDPContext {
         Ok(PDPContextResponse { state, context })
     }
 }
+
+/// Maximum length of the user and password, AT Datasheet page 172
+const AUTH_CREDENTIAL_MAX_SIZE: usize = 32;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AuthenticationProtocol {
+    None = 0,
+    PAP = 1,
+    CHAP = 2,
+}
+
+impl From<i32> for AuthenticationProtocol {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => AuthenticationProtocol::None,
+            1 => AuthenticationProtocol::PAP,
+            2 => AuthenticationProtocol::CHAP,
+            _ => {
+                unreachable!()
+            }
+        }
+    }
+}
+
+/// Sets the authentication used when activating a PDP context
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetPDPAuthentication<'a> {
+    pub(crate) cid: i32,
+    pub(crate) protocol: AuthenticationProtocol,
+    pub(crate) credentials: Option<(&'a str, &'a str)>,
+}
+
+impl<'a> SetPDPAuthentication<'a> {
+    /// Disables the authentication for the context
+    pub fn none(cid: i32) -> Self {
+        Self {
+            cid,
+            protocol: AuthenticationProtocol::None,
+            credentials: None,
+        }
+    }
+
+    pub fn new(
+        cid: i32,
+        protocol: AuthenticationProtocol,
+        user: &'a str,
+        password: &'a str,
+    ) -> Result<Self, AtError> {
+        if user.len() > AUTH_CREDENTIAL_MAX_SIZE || password.len() > AUTH_CREDENTIAL_MAX_SIZE {
+            return Err(AtError::InvalidParameter);
+        }
+        validate_string_parameter(user)?;
+        validate_string_parameter(password)?;
+        if protocol == AuthenticationProtocol::None {
+            return Ok(Self::none(cid));
+        }
+
+        Ok(Self {
+            cid,
+            protocol,
+            credentials: Some((user, password)),
+        })
+    }
+}
+
+impl AtRequest for SetPDPAuthentication<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let mut builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGAUTH")
+            .with_int_parameter(self.cid)
+            .with_int_parameter(self.protocol as u8);
+
+        if let Some((user, password)) = self.credentials {
+            builder = builder
+                .with_string_parameter(user)
+                .with_string_parameter(password);
+        }
+
+        builder.finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads the authentication of the first PDP context
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetPDPAuthentication;
+
+pub struct PDPAuthentication {
+    pub cid: i32,
+    pub protocol: AuthenticationProtocol,
+    pub user: Option<heapless::String<AUTH_CREDENTIAL_MAX_SIZE>>,
+}
+
+impl GetPDPAuthentication {
+    fn get_authentication(data: &[u8]) -> Result<PDPAuthentication, AtError> {
+        let (cid, protocol, user) = CommandParser::parse(data)
+            .expect_identifier(b"\r\n+CGAUTH: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .expect_optional_string_parameter()
+            .finish()?;
+
+        if !(0..=2).contains(&protocol) {
+            return Err(AtError::AtParseError);
+        }
+
+        Ok(PDPAuthentication {
+            cid,
+            protocol: protocol.into(),
+            user: user.map(|x| x.try_into()).transpose()?,
+        })
+    }
+}
+
+impl AtRequest for GetPDPAuthentication {
+    type Response = PDPAuthentication;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CGAUTH")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_authentication(data)
+    }
+}
+
+/// Smallest MTU every IPv4 host has to accept
+pub const MIN_MTU: u16 = 576;
+/// Largest MTU accepted by the modem
+pub const MAX_MTU: u16 = 1500;
+
+/// Sets the IP MTU of a PDP context with `AT+CGMTU`, e.g. to avoid fragmentation on links
+/// with a smaller MTU than the default one. Takes effect on the next activation.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetPDPMtu {
+    pub(crate) cid: i32,
+    pub(crate) mtu: u16,
+}
+
+impl SetPDPMtu {
+    pub fn new(cid: i32, mtu: u16) -> Result<Self, AtError> {
+        if !(MIN_MTU..=MAX_MTU).contains(&mtu) {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { cid, mtu })
+    }
+}
+
+impl AtRequest for SetPDPMtu {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGMTU")
+            .with_int_parameter(self.cid)
+            .with_int_parameter(self.mtu as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Type of a PDP context
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PDPType {
+    IP,
+    IPV6,
+    IPV4V6,
+    /// Non-IP Data Delivery (NIDD), data is exchanged with
+    /// [SendNonIpData](crate::at_command::nidd::SendNonIpData) instead of sockets
+    NonIP,
+}
+
+impl PDPType {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            PDPType::IP => "IP",
+            PDPType::IPV6 => "IPV6",
+            PDPType::IPV4V6 => "IPV4V6",
+            PDPType::NonIP => "Non-IP",
+        }
+    }
+
+    /// Returns `None` on types not supported by this crate
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "IP" => Some(PDPType::IP),
+            "IPV6" => Some(PDPType::IPV6),
+            "IPV4V6" => Some(PDPType::IPV4V6),
+            "Non-IP" => Some(PDPType::NonIP),
+            _ => None,
+        }
+    }
+}
+
+/// Defines a PDP context with `AT+CGDCONT`, e.g. a Non-IP context for NIDD. Takes effect on
+/// the next activation.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DefinePDPContext<'a> {
+    pub(crate) cid: i32,
+    pub(crate) pdp_type: PDPType,
+    pub(crate) apn: Option<&'a str>,
+}
+
+impl<'a> DefinePDPContext<'a> {
+    /// Without an APN the network selects the default one
+    pub fn new(cid: i32, pdp_type: PDPType, apn: Option<&'a str>) -> Result<Self, AtError> {
+        if cid < 1 {
+            return Err(AtError::InvalidParameter);
+        }
+        if let Some(apn) = apn {
+            validate_string_parameter(apn)?;
+        }
+
+        Ok(Self { cid, pdp_type, apn })
+    }
+}
+
+impl AtRequest for DefinePDPContext<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CGDCONT")
+            .with_int_parameter(self.cid)
+            .with_string_parameter(self.pdp_type.as_str());
+
+        match self.apn {
+            Some(apn) => builder.with_string_parameter(apn).finish_exact(),
+            None => builder.finish_exact(),
+        }
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Longest APN reported by [QueryPdpContexts], 3GPP TS 23.003
+const APN_MAX_SIZE: usize = 100;
+
+/// Most contexts kept by [QueryPdpContexts]
+pub const MAX_PDP_CONTEXTS: usize = 8;
+
+/// A context as defined with [DefinePDPContext]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinedPdpContext {
+    pub cid: i32,
+    /// `None` if the modem reports a type not supported by this crate
+    pub pdp_type: Option<PDPType>,
+    /// Empty if the network selects the APN
+    pub apn: heapless::String<APN_MAX_SIZE>,
+}
+
+pub type PdpContexts = heapless::Vec<DefinedPdpContext, MAX_PDP_CONTEXTS>;
+
+/// Lists the contexts defined in the modem with `AT+CGDCONT?`, activated or not
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryPdpContexts;
+
+impl AtRequest for QueryPdpContexts {
+    type Response = PdpContexts;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CGDCONT")
+            .finish()
+    }
+
+    /// Parses a `+CGDCONT: <cid>,<type>,<apn>,...` line per context, the rest of the fields
+    /// are ignored. Without contexts the reply is a plain OK.
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let mut contexts = PdpContexts::new();
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with(b"+CGDCONT: ") {
+                return Err(AtError::AtParseError);
+            }
+
+            let mut fields = parse_csv_line(line)?
+                .map(|field| core::str::from_utf8(field).map_err(|_| AtError::AtParseError));
+            let cid = fields
+                .next()
+                .transpose()?
+                .and_then(|cid| cid.parse().ok())
+                .ok_or(AtError::AtParseError)?;
+            let pdp_type = fields.next().transpose()?.ok_or(AtError::AtParseError)?;
+            let apn = fields.next().transpose()?.unwrap_or("");
+
+            contexts
+                .push(DefinedPdpContext {
+                    cid,
+                    pdp_type: PDPType::from_str(pdp_type),
+                    apn: heapless::String::try_from(apn)?,
+                })
+                .map_err(|_| AtError::CapacityError)?;
+        }
+
+        Ok(contexts)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_pap_authentication() {
+        let mut buffer = [0; 512];
+
+        let request =
+            SetPDPAuthentication::new(1, AuthenticationProtocol::PAP, "user", "secret").unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CGAUTH=1,1,\"user\",\"secret\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_set_no_authentication() {
+        let mut buffer = [0; 512];
+
+        let request = SetPDPAuthentication::none(1);
+        let result = request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGAUTH=1,0\r\n");
+    }
+
+    #[test]
+    fn test_set_too_long_credentials() {
+        let user = "a-very-long-user-name-over-the-limit";
+
+        assert!(matches!(
+            SetPDPAuthentication::new(1, AuthenticationProtocol::CHAP, user, "secret"),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_parse_authentication() {
+        let data = b"\r\n+CGAUTH: 1,2,\"user\"\r\n\r\nOK\r";
+
+        let authentication = GetPDPAuthentication.parse_response_struct(data).unwrap();
+
+        assert_eq!(authentication.cid, 1);
+        assert_eq!(authentication.protocol, AuthenticationProtocol::CHAP);
+        assert_eq!(authentication.user.unwrap().as_str(), "user");
+    }
+
+    #[test]
+    fn test_set_mtu() {
+        let mut buffer = [0; 512];
+
+        let request = SetPDPMtu::new(1, 1280).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGMTU=1,1280\r\n");
+    }
+
+    #[test]
+    fn test_set_mtu_out_of_range() {
+        assert!(matches!(
+            SetPDPMtu::new(1, MIN_MTU - 1),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            SetPDPMtu::new(1, MAX_MTU + 1),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_define_non_ip_context() {
+        let mut buffer = [0; 512];
+
+        let request = DefinePDPContext::new(1, PDPType::NonIP, Some("nidd.iot")).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CGDCONT=1,\"Non-IP\",\"nidd.iot\"\r\n"
+        );
+
+        let request = DefinePDPContext::new(2, PDPType::IP, None).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CGDCONT=2,\"IP\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_query_pdp_contexts() {
+        let mut buffer = [0; 512];
+        let result = QueryPdpContexts.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CGDCONT?\r\n");
+
+        let contexts = QueryPdpContexts
+            .parse_response_struct(
+                b"\r\n+CGDCONT: 1,\"IP\",\"iot.1nce.net\",\"10.0.0.12\",0,0,0,0\r\n\
+                +CGDCONT: 2,\"Non-IP\",\"\",\"\",0,0,0,0\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(contexts.len(), 2);
+        assert_eq!(contexts[0].cid, 1);
+        assert_eq!(contexts[0].pdp_type, Some(PDPType::IP));
+        assert_eq!(contexts[0].apn.as_str(), "iot.1nce.net");
+        assert_eq!(contexts[1].cid, 2);
+        assert_eq!(contexts[1].pdp_type, Some(PDPType::NonIP));
+        assert!(contexts[1].apn.is_empty());
+    }
+
+    #[test]
+    fn test_query_no_pdp_contexts() {
+        assert!(QueryPdpContexts
+            .parse_response_struct(b"\r\nOK\r\n")
+            .unwrap()
+            .is_empty());
+        assert!(QueryPdpContexts
+            .parse_response_struct(b"\r\n+CGACT: 1,1\r\n\r\nOK\r\n")
+            .is_err());
+    }
+}
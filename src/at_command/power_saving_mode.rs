@@ -1,8 +1,10 @@
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
-use crate::at_command::{AtRequest, BufferType};
-use crate::AtError;
+use crate::at_command::{parse_csv_line, strip_ok, AtRequest, BufferType};
+use crate::{AtError, Modem};
 use at_commands::parser::CommandParser;
+use core::time::Duration;
+use embedded_io::{Read, Write};
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum PowerSavingModeState {
@@ -98,3 +100,327 @@ impl AtRequest for SetPowerSavingMode {
         Ok(state)
     }
 }
+
+/// 3 bit codes of the GPRS Timer 3 units and their length in seconds, finest first
+const TAU_UNITS_SECS: [(u8, u64); 7] = [
+    (0b011, 2),
+    (0b100, 30),
+    (0b101, 60),
+    (0b000, 600),
+    (0b001, 3600),
+    (0b010, 36000),
+    (0b110, 1_152_000),
+];
+
+/// 3 bit codes of the GPRS Timer 2 units and their length in seconds, finest first
+const ACTIVE_TIME_UNITS_SECS: [(u8, u64); 3] = [(0b000, 2), (0b001, 60), (0b010, 360)];
+
+/// Picks the finest unit which can represent the duration in the 5 bit value, rounding up.
+/// Durations longer than the largest value saturate to it.
+fn encode_timer(duration: Duration, units: &[(u8, u64)]) -> u8 {
+    let secs = duration
+        .as_secs()
+        .saturating_add(u64::from(duration.subsec_nanos() > 0));
+    for (code, unit_secs) in units {
+        let value = secs.div_ceil(*unit_secs);
+        if value <= 0x1F {
+            return (code << 5) | value as u8;
+        }
+    }
+
+    // The unit lists are not empty
+    let (code, _) = units[units.len() - 1];
+    (code << 5) | 0x1F
+}
+
+/// Encodes the periodic TAU as a GPRS Timer 3 value (3GPP TS 24.008 10.5.7.4a), e.g. to
+/// request it with `AT+CPSMS`. It is rounded up to the next value which can be represented.
+pub fn encode_tau(duration: Duration) -> u8 {
+    encode_timer(duration, &TAU_UNITS_SECS)
+}
+
+/// Encodes the active time as a GPRS Timer 2 value (3GPP TS 24.008 10.5.7.3), e.g. to request
+/// it with `AT+CPSMS`. It is rounded up to the next value which can be represented.
+pub fn encode_active_time(duration: Duration) -> u8 {
+    encode_timer(duration, &ACTIVE_TIME_UNITS_SECS)
+}
+
+/// Decodes a GPRS Timer 3 value (3GPP TS 24.008 10.5.7.4a) as used by the periodic TAU,
+/// `None` if the timer is deactivated
+pub fn decode_tau(bits: u8) -> Option<Duration> {
+    let value = u64::from(bits & 0x1F);
+    let (_, unit_secs) = TAU_UNITS_SECS.iter().find(|(code, _)| *code == bits >> 5)?;
+
+    Some(Duration::from_secs(value * unit_secs))
+}
+
+/// Decodes a GPRS Timer 2 value (3GPP TS 24.008 10.5.7.3) as used by the active time, `None`
+/// if the timer is deactivated
+pub fn decode_active_time(bits: u8) -> Option<Duration> {
+    let value = u64::from(bits & 0x1F);
+    let unit_secs = match bits >> 5 {
+        0b000 => 2,
+        0b010 => 360,
+        0b111 => return None,
+        // Other values are interpreted as minutes
+        _ => 60,
+    };
+
+    Some(Duration::from_secs(value * unit_secs))
+}
+
+/// eDRX cycle length in milliseconds indexed by its 4 bit value (3GPP TS 24.008 10.5.5.32)
+const EDRX_CYCLE_MS: [u64; 16] = [
+    5_120, 10_240, 20_480, 40_960, 61_440, 81_920, 102_400, 122_880, 143_360, 163_840, 327_680,
+    655_360, 1_310_720, 2_621_440, 5_242_880, 10_485_760,
+];
+
+/// Parses a binary string as sent by the modem, e.g. `"01000111"`
+fn parse_bits(data: &[u8]) -> Result<u8, AtError> {
+    if data.is_empty() || data.len() > 8 {
+        return Err(AtError::AtParseError);
+    }
+
+    data.iter().try_fold(0_u8, |value, bit| match bit {
+        b'0' => Ok(value << 1),
+        b'1' => Ok((value << 1) | 1),
+        _ => Err(AtError::AtParseError),
+    })
+}
+
+/// PSM timers granted by the network
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PsmTimers {
+    /// T3324, `None` if it was not granted
+    pub active_time: Option<Duration>,
+    /// T3412 extended, `None` if it was not granted
+    pub periodic_tau: Option<Duration>,
+}
+
+/// Reads the PSM timers granted by the network from `AT+CEREG?`. They are only reported once
+/// the registration reports are set to `AT+CEREG=4`, otherwise both are `None`.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetNegotiatedPsmTimers;
+
+impl GetNegotiatedPsmTimers {
+    fn get_timers(data: &[u8]) -> Result<PsmTimers, AtError> {
+        let payload = strip_ok(data)?;
+        if !payload.starts_with(b"+CEREG: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        // <n>,<stat>[,<tac>,<ci>,<AcT>[,<cause_type>,<reject_cause>[,<active>,<periodic>]]]
+        let mut fields = parse_csv_line(payload)?;
+        let active_time = fields.nth(7).filter(|field| !field.is_empty());
+        let periodic_tau = fields.next().filter(|field| !field.is_empty());
+
+        Ok(PsmTimers {
+            active_time: active_time
+                .map(parse_bits)
+                .transpose()?
+                .and_then(decode_active_time),
+            periodic_tau: periodic_tau
+                .map(parse_bits)
+                .transpose()?
+                .and_then(decode_tau),
+        })
+    }
+}
+
+impl AtRequest for GetNegotiatedPsmTimers {
+    type Response = PsmTimers;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CEREG")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_timers(data)
+    }
+}
+
+/// eDRX parameters granted by the network
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdrxParameters {
+    /// `None` if eDRX is not in use
+    pub cycle: Option<Duration>,
+    pub paging_time_window: Option<Duration>,
+}
+
+/// Reads the eDRX parameters granted by the network with `AT+CEDRXRDP`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetNegotiatedEdrx;
+
+impl GetNegotiatedEdrx {
+    fn get_parameters(data: &[u8]) -> Result<EdrxParameters, AtError> {
+        let (access_technology, _requested, granted, paging_time_window) =
+            CommandParser::parse(strip_ok(data)?)
+                .expect_identifier(b"+CEDRXRDP: ")
+                .expect_int_parameter()
+                .expect_optional_string_parameter()
+                .expect_optional_string_parameter()
+                .expect_optional_string_parameter()
+                .finish()?;
+
+        let (Some(granted), Some(paging_time_window)) = (granted, paging_time_window) else {
+            return Ok(EdrxParameters {
+                cycle: None,
+                paging_time_window: None,
+            });
+        };
+
+        let cycle = usize::from(parse_bits(granted.as_bytes())? & 0x0F);
+        let window = u64::from(parse_bits(paging_time_window.as_bytes())? & 0x0F) + 1;
+        // The paging time window unit is 2.56 s in NB-IoT and 1.28 s in Cat-M
+        let window_unit_ms = if access_technology == 5 { 2_560 } else { 1_280 };
+
+        Ok(EdrxParameters {
+            cycle: Some(Duration::from_millis(EDRX_CYCLE_MS[cycle])),
+            paging_time_window: Some(Duration::from_millis(window * window_unit_ms)),
+        })
+    }
+}
+
+impl AtRequest for GetNegotiatedEdrx {
+    type Response = EdrxParameters;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CEDRXRDP")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_parameters(data)
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegotiatedTimers {
+    pub psm: PsmTimers,
+    pub edrx: EdrxParameters,
+}
+
+/// Gathers the PSM and eDRX timers granted by the network with `AT+CEREG?` and `AT+CEDRXRDP`
+pub struct QueryNegotiatedTimers;
+
+impl QueryNegotiatedTimers {
+    pub fn query<T: Write, U: Read>(
+        modem: &mut Modem<'_, T, U>,
+    ) -> Result<NegotiatedTimers, AtError> {
+        let psm = modem.send_and_wait_response(&GetNegotiatedPsmTimers)?;
+        let edrx = modem.send_and_wait_response(&GetNegotiatedEdrx)?;
+
+        Ok(NegotiatedTimers { psm, edrx })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+
+    #[test]
+    fn test_decode_timers() {
+        assert_eq!(
+            decode_tau(0b0100_0111),
+            Some(Duration::from_secs(7 * 36000))
+        );
+        assert_eq!(decode_tau(0b1110_0001), None);
+        assert_eq!(
+            decode_active_time(0b0000_0101),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(decode_active_time(0b1110_0000), None);
+    }
+
+    #[test]
+    fn test_encode_timers_round_trip() {
+        for secs in [0, 2, 30, 62, 600, 3600, 4 * 3600, 36000, 10 * 24 * 3600] {
+            let duration = Duration::from_secs(secs);
+            assert_eq!(decode_tau(encode_tau(duration)), Some(duration));
+        }
+        for secs in [0, 10, 62, 120, 30 * 60, 3 * 3600] {
+            let duration = Duration::from_secs(secs);
+            assert_eq!(
+                decode_active_time(encode_active_time(duration)),
+                Some(duration)
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_timers_unit() {
+        // 2 s steps as long as they fit
+        assert_eq!(encode_tau(Duration::from_secs(60)), 0b0111_1110);
+        assert_eq!(encode_tau(Duration::from_secs(90)), 0b1000_0011);
+        assert_eq!(encode_tau(Duration::from_secs(54 * 3600)), 0b0100_0110);
+        assert_eq!(encode_active_time(Duration::from_secs(10)), 0b0000_0101);
+        assert_eq!(encode_active_time(Duration::from_secs(4 * 60)), 0b0010_0100);
+        assert_eq!(
+            encode_active_time(Duration::from_secs(40 * 60)),
+            0b0100_0111
+        );
+
+        // Rounded up and saturated
+        assert_eq!(encode_active_time(Duration::from_millis(2500)), 0b0000_0010);
+        assert_eq!(
+            encode_active_time(Duration::from_secs(24 * 3600)),
+            0b0101_1111
+        );
+        assert_eq!(
+            decode_tau(encode_tau(Duration::from_secs(u64::MAX))),
+            Some(Duration::from_secs(31 * 1_152_000))
+        );
+    }
+
+    #[test]
+    fn test_get_negotiated_psm_timers_response() {
+        let timers = GetNegotiatedPsmTimers
+            .parse_response_struct(
+                b"\r\n+CEREG: 4,1,\"1A2B\",\"01A2B3C4\",9,,,\"00100100\",\"01000111\"\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(timers.active_time, Some(Duration::from_secs(4 * 60)));
+        assert_eq!(timers.periodic_tau, Some(Duration::from_secs(7 * 36000)));
+
+        let timers = GetNegotiatedPsmTimers
+            .parse_response_struct(b"\r\n+CEREG: 0,1\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(timers.active_time, None);
+        assert_eq!(timers.periodic_tau, None);
+    }
+
+    #[test]
+    fn test_query_negotiated_timers() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\
+            \r\n+CEREG: 4,1,\"1A2B\",\"01A2B3C4\",9,,,\"00000101\",\"00100001\"\r\n\r\nOK\r\n\
+            \r\n+CEDRXRDP: 5,\"0101\",\"0010\",\"0011\"\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let timers = QueryNegotiatedTimers::query(&mut modem).unwrap();
+        assert_eq!(timers.psm.active_time, Some(Duration::from_secs(10)));
+        assert_eq!(timers.psm.periodic_tau, Some(Duration::from_secs(3600)));
+        assert_eq!(timers.edrx.cycle, Some(Duration::from_millis(20_480)));
+        assert_eq!(
+            timers.edrx.paging_time_window,
+            Some(Duration::from_millis(4 * 2_560))
+        );
+    }
+
+    #[test]
+    fn test_get_negotiated_edrx_not_in_use() {
+        let parameters = GetNegotiatedEdrx
+            .parse_response_struct(b"\r\n+CEDRXRDP: 0\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(parameters.cycle, None);
+    }
+}
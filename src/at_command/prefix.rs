@@ -0,0 +1,78 @@
+use crate::at_command::{AtRequest, BufferType};
+use crate::AtError;
+
+/// Sends a request with a lead-in other than `AT`, for modules or test harnesses using a
+/// different prefix. An empty prefix omits it. Commands not starting with `AT` get the prefix
+/// prepended.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WithPrefix<'a, R: AtRequest> {
+    pub prefix: &'a [u8],
+    pub request: &'a R,
+}
+
+impl<'a, R: AtRequest> WithPrefix<'a, R> {
+    pub fn new(prefix: &'a [u8], request: &'a R) -> Self {
+        Self { prefix, request }
+    }
+}
+
+impl<R: AtRequest> AtRequest for WithPrefix<'_, R> {
+    type Response = R::Response;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let (len, replaced) = match self.request.get_command(buffer) {
+            Ok(command) => (
+                command.len(),
+                if command.starts_with(b"AT") { 2 } else { 0 },
+            ),
+            Err(size) => return Err(size + self.prefix.len()),
+        };
+
+        let size = len - replaced + self.prefix.len();
+        if size > buffer.len() {
+            return Err(size);
+        }
+
+        buffer.copy_within(replaced..len, self.prefix.len());
+        buffer[..self.prefix.len()].copy_from_slice(self.prefix);
+        Ok(&buffer[..size])
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        self.request.parse_response_struct(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::at_command::urc_port::{SetUrcPort, UrcPort};
+
+    #[test]
+    fn test_custom_prefix() {
+        let mut buffer = [0; 512];
+        let request = SetUrcPort {
+            port: UrcPort::MainUart,
+        };
+
+        let prefixed = WithPrefix::new(b"XT", &request);
+        let result = prefixed.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "XT+CATR=1\r\n");
+
+        let prefixed = WithPrefix::new(b"", &request);
+        let result = prefixed.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "+CATR=1\r\n");
+
+        let prefixed = WithPrefix::new(b"AT#TEST", &request);
+        let result = prefixed.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT#TEST+CATR=1\r\n");
+        assert_eq!(prefixed.command_len(), "AT#TEST+CATR=1\r\n".len());
+    }
+
+    #[test]
+    fn test_custom_prefix_response() {
+        let request = SetUrcPort { port: UrcPort::All };
+        let prefixed = WithPrefix::new(b"XT", &request);
+        prefixed.parse_response_struct(b"\r\nOK\r\n").unwrap();
+    }
+}
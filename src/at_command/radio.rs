@@ -0,0 +1,665 @@
+use crate::at_command::network_information::{AccessTechnology, NetworkInformation};
+use crate::at_command::network_registration_status::EpsNetworkRegistration;
+use crate::at_command::{parse_csv_line, strip_ok, verify_ok, AtRequest, BufferType};
+use crate::{AtError, Modem};
+use at_commands::parser::CommandParser;
+use embedded_io::{Read, Write};
+
+/// Gets the band the modem is working on
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetBand;
+
+impl GetBand {
+    fn get_band(data: &[u8]) -> Result<u8, AtError> {
+        let (band,) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CBAND: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        u8::try_from(band).map_err(|_| AtError::AtParseError)
+    }
+}
+
+impl AtRequest for GetBand {
+    type Response = u8;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CBAND")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_band(data)
+    }
+}
+
+/// Bands kept from the supported ones, a longer list fails to parse with
+/// [AtError::CapacityError]
+pub const MAX_SUPPORTED_BANDS: usize = 32;
+
+pub type SupportedBands = heapless::Vec<u8, MAX_SUPPORTED_BANDS>;
+
+/// Lists the bands supported by the firmware with `AT+CBAND=?`, to check a band before
+/// locking the modem to it
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QuerySupportedBands;
+
+impl QuerySupportedBands {
+    fn get_bands(data: &[u8]) -> Result<SupportedBands, AtError> {
+        let payload = core::str::from_utf8(strip_ok(data)?).map_err(|_| AtError::AtParseError)?;
+        let list = payload
+            .strip_prefix("+CBAND: ")
+            .and_then(|list| list.trim().strip_prefix('('))
+            .and_then(|list| list.strip_suffix(')'))
+            .ok_or(AtError::AtParseError)?;
+
+        let mut bands = SupportedBands::new();
+        for band in list
+            .split(',')
+            .map(str::trim)
+            .filter(|band| !band.is_empty())
+        {
+            let band = band.parse().map_err(|_| AtError::AtParseError)?;
+            bands.push(band).map_err(|_| AtError::CapacityError)?;
+        }
+
+        Ok(bands)
+    }
+}
+
+impl AtRequest for QuerySupportedBands {
+    type Response = SupportedBands;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_test(buffer, true)
+            .named("+CBAND")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_bands(data)
+    }
+}
+
+/// Largest timing advance of an LTE cell, about 100 km
+const MAX_TIMING_ADVANCE: u16 = 1282;
+
+/// Timing advance of the serving cell, in steps of 16 Ts
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimingAdvance(pub u16);
+
+impl TimingAdvance {
+    /// Estimated distance to the cell, each step is about 78 m of round trip delay
+    pub fn distance_meters(&self) -> u32 {
+        u32::from(self.0) * 7812 / 100
+    }
+}
+
+/// Queries the timing advance of the serving cell with `AT+CTA?`. `None` when it is unknown,
+/// e.g. while the modem is idle and the network did not send it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryTimingAdvance;
+
+impl QueryTimingAdvance {
+    fn get_timing_advance(data: &[u8]) -> Result<Option<TimingAdvance>, AtError> {
+        let payload = strip_ok(data)?;
+        if payload.trim_ascii_end() == b"+CTA:" {
+            return Ok(None);
+        }
+
+        let (timing_advance,) = CommandParser::parse(payload)
+            .expect_identifier(b"+CTA: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        Ok(u16::try_from(timing_advance)
+            .ok()
+            .filter(|timing_advance| *timing_advance <= MAX_TIMING_ADVANCE)
+            .map(TimingAdvance))
+    }
+}
+
+impl AtRequest for QueryTimingAdvance {
+    type Response = Option<TimingAdvance>;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CTA")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_timing_advance(data)
+    }
+}
+
+/// Bands kept from a scan, a longer result fails to parse with [AtError::CapacityError]
+pub const MAX_SCANNED_BANDS: usize = 16;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BandRssi {
+    pub band: u8,
+    /// Strongest signal found on the band in dBm, `None` if nothing was received
+    pub rssi_dbm: Option<i16>,
+}
+
+pub type BandScan = heapless::Vec<BandRssi, MAX_SCANNED_BANDS>;
+
+/// Scans the bands set with `AT+CBAND` and reports the signal found on each of them with
+/// `AT+CBANDSCAN`, e.g. to choose which band to lock during a site survey. The scan takes
+/// several seconds per band, during which the modem is not registered.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScanBands;
+
+impl ScanBands {
+    /// Parses a `+CBANDSCAN: <band>,<rssi>` line per band, where an empty RSSI means nothing
+    /// was received
+    fn get_scan(data: &[u8]) -> Result<BandScan, AtError> {
+        let mut scan = BandScan::new();
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with(b"+CBANDSCAN: ") {
+                return Err(AtError::AtParseError);
+            }
+
+            let mut fields = parse_csv_line(line)?
+                .map(|field| core::str::from_utf8(field).map_err(|_| AtError::AtParseError));
+            let band = fields
+                .next()
+                .transpose()?
+                .and_then(|band| band.parse().ok())
+                .ok_or(AtError::AtParseError)?;
+            let rssi_dbm = match fields.next().transpose()? {
+                None | Some("") => None,
+                Some(rssi) => Some(rssi.parse().map_err(|_| AtError::AtParseError)?),
+            };
+
+            scan.push(BandRssi { band, rssi_dbm })
+                .map_err(|_| AtError::CapacityError)?;
+        }
+
+        Ok(scan)
+    }
+}
+
+impl AtRequest for ScanBands {
+    type Response = BandScan;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CBANDSCAN")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_scan(data)
+    }
+}
+
+/// Accepted dwell time on each frequency in milliseconds
+pub const SEARCH_DWELL_RANGE_MS: core::ops::RangeInclusive<u16> = 10..=1000;
+/// Accepted time between two network searches in seconds
+pub const SEARCH_INTERVAL_RANGE_SECS: core::ops::RangeInclusive<u16> = 1..=3600;
+
+/// Timers of the network search done while not registered
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchTimers {
+    /// Time spent on each frequency
+    pub dwell_ms: u16,
+    /// Time between two searches when no cell was found
+    pub interval_secs: u16,
+}
+
+/// Tunes the network search with `AT+CNSTMR`, e.g. a longer dwell time helps to find weak
+/// cells at the cost of a slower scan. Advanced setting, the defaults fit most deployments.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetSearchTimers {
+    pub(crate) timers: SearchTimers,
+}
+
+impl SetSearchTimers {
+    pub fn new(timers: SearchTimers) -> Result<Self, AtError> {
+        if !SEARCH_DWELL_RANGE_MS.contains(&timers.dwell_ms)
+            || !SEARCH_INTERVAL_RANGE_SECS.contains(&timers.interval_secs)
+        {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { timers })
+    }
+}
+
+impl AtRequest for SetSearchTimers {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CNSTMR")
+            .with_int_parameter(self.timers.dwell_ms as i32)
+            .with_int_parameter(self.timers.interval_secs as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads the network search timers, see [SetSearchTimers]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetSearchTimers;
+
+impl AtRequest for GetSearchTimers {
+    type Response = SearchTimers;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CNSTMR")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let (dwell_ms, interval_secs) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CNSTMR: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        Ok(SearchTimers {
+            dwell_ms: u16::try_from(dwell_ms).map_err(|_| AtError::AtParseError)?,
+            interval_secs: u16::try_from(interval_secs).map_err(|_| AtError::AtParseError)?,
+        })
+    }
+}
+
+/// Frequency and physical id of the serving cell
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServingCell {
+    pub earfcn: u32,
+    pub pci: u16,
+}
+
+/// Reads the EARFCN and PCI of the serving cell from the engineering report of `AT+CENG?`.
+/// `None` if the modem is not camped on any cell.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryServingCell;
+
+impl AtRequest for QueryServingCell {
+    type Response = Option<ServingCell>;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CENG")
+            .finish()
+    }
+
+    /// The serving cell is the first `+CENG:` line, laid out as an [EngineeringReport], the
+    /// modem only sends the OK when it is not camped
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let Some(line) = strip_ok(data)?
+            .split(|b| *b == b'\n')
+            .find(|line| line.starts_with(b"+CENG: "))
+        else {
+            return Ok(None);
+        };
+
+        let report = EngineeringReport::parse(line.trim_ascii_end())?;
+        Ok(Some(ServingCell {
+            earfcn: report.earfcn,
+            pci: report.pci,
+        }))
+    }
+}
+
+/// Accepted interval between two engineering reports in seconds
+pub const ENGINEERING_REPORT_INTERVAL_RANGE_SECS: core::ops::RangeInclusive<u16> = 1..=3600;
+
+/// Enables or disables the periodic engineering reports with `AT+CENG=<mode>[,<interval>]`,
+/// received as [Urc::EngineeringReport](crate::urc::Urc::EngineeringReport), so the RF
+/// metrics can be logged without polling
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetEngineeringReporting {
+    pub(crate) interval_secs: Option<u16>,
+}
+
+impl SetEngineeringReporting {
+    /// Sends a report every `interval_secs`
+    pub fn enable(interval_secs: u16) -> Result<Self, AtError> {
+        if !ENGINEERING_REPORT_INTERVAL_RANGE_SECS.contains(&interval_secs) {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self {
+            interval_secs: Some(interval_secs),
+        })
+    }
+
+    pub fn disable() -> Self {
+        Self {
+            interval_secs: None,
+        }
+    }
+}
+
+impl AtRequest for SetEngineeringReporting {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let builder = at_commands::builder::CommandBuilder::create_set(buffer, true).named("+CENG");
+
+        match self.interval_secs {
+            Some(interval_secs) => builder
+                .with_int_parameter(1)
+                .with_int_parameter(interval_secs as i32)
+                .finish(),
+            None => builder.with_int_parameter(0).finish(),
+        }
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Serving cell measurements sent periodically once enabled with [SetEngineeringReporting]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineeringReport {
+    pub earfcn: u32,
+    pub pci: u16,
+    /// Serving cell ID in hex format
+    pub cell_id: heapless::String<CELL_ID_MAX_SIZE>,
+    pub rsrp_dbm: i16,
+    pub rsrq_db: i16,
+    pub rssi_dbm: i16,
+    pub snr_db: i16,
+}
+
+impl EngineeringReport {
+    /// Parses `+CENG: <earfcn>,<earfcn_offset>,<pci>,<cellid>,<rsrp>,<rsrq>,<rssi>,<snr>,...`,
+    /// the fields after the SNR are ignored
+    pub(crate) fn parse(line: &[u8]) -> Result<Self, AtError> {
+        if !line.starts_with(b"+CENG: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        fn number<N: core::str::FromStr>(field: Option<&[u8]>) -> Result<N, AtError> {
+            field
+                .and_then(|field| core::str::from_utf8(field).ok())
+                .and_then(|field| field.trim().parse().ok())
+                .ok_or(AtError::AtParseError)
+        }
+
+        let mut fields = parse_csv_line(line)?;
+        let earfcn = number(fields.next())?;
+        let pci = number(fields.nth(1))?;
+        let cell_id = fields.next().ok_or(AtError::AtParseError)?;
+        let cell_id = core::str::from_utf8(cell_id).map_err(|_| AtError::AtParseError)?;
+
+        Ok(Self {
+            earfcn,
+            pci,
+            cell_id: heapless::String::try_from(cell_id)?,
+            rsrp_dbm: number(fields.next())?,
+            rsrq_db: number(fields.next())?,
+            rssi_dbm: number(fields.next())?,
+            snr_db: number(fields.next())?,
+        })
+    }
+}
+
+const CELL_ID_MAX_SIZE: usize = 8;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadioInfo {
+    /// `None` if the modem is not camped on any operator
+    pub access_technology: Option<AccessTechnology>,
+    pub band: u8,
+    /// Serving cell ID in hex format, only reported if enabled with `AT+CEREG=2`
+    pub cell_id: Option<heapless::String<CELL_ID_MAX_SIZE>>,
+}
+
+/// Gathers the access technology, band and serving cell from `AT+COPS?`, `AT+CBAND?` and
+/// `AT+CEREG?`
+pub struct QueryRadioInfo;
+
+impl QueryRadioInfo {
+    pub fn query<T: Write, U: Read>(modem: &mut Modem<'_, T, U>) -> Result<RadioInfo, AtError> {
+        let network = modem.send_and_wait_response(&NetworkInformation)?;
+        let band = modem.send_and_wait_response(&GetBand)?;
+        let registration = modem.send_and_wait_response(&EpsNetworkRegistration)?;
+
+        Ok(RadioInfo {
+            access_technology: network.access_technology,
+            band,
+            cell_id: registration.cell_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+
+    #[test]
+    fn test_get_band_request() {
+        let mut buffer = [0; 512];
+        let result = GetBand.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CBAND?\r\n");
+    }
+
+    #[test]
+    fn test_get_band_response() {
+        let band = GetBand
+            .parse_response_struct(b"\r\n+CBAND: 20\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(band, 20);
+    }
+
+    #[test]
+    fn test_query_supported_bands() {
+        let mut buffer = [0; 512];
+        let result = QuerySupportedBands.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CBAND=?\r\n");
+
+        let bands = QuerySupportedBands
+            .parse_response_struct(b"\r\n+CBAND: (1,2,3,5,8,20,28)\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(bands.as_slice(), &[1, 2, 3, 5, 8, 20, 28]);
+
+        assert!(matches!(
+            QuerySupportedBands.parse_response_struct(b"\r\n+CBAND: 1,2\r\n\r\nOK\r\n"),
+            Err(AtError::AtParseError)
+        ));
+    }
+
+    #[test]
+    fn test_query_timing_advance() {
+        let mut buffer = [0; 512];
+        let result = QueryTimingAdvance.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CTA?\r\n");
+
+        let timing_advance = QueryTimingAdvance
+            .parse_response_struct(b"\r\n+CTA: 12\r\n\r\nOK\r\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(timing_advance, TimingAdvance(12));
+        assert_eq!(timing_advance.distance_meters(), 937);
+    }
+
+    #[test]
+    fn test_query_timing_advance_unknown() {
+        for response in [
+            &b"\r\n+CTA: -1\r\n\r\nOK\r\n"[..],
+            b"\r\n+CTA: 65535\r\n\r\nOK\r\n",
+            b"\r\n+CTA:\r\n\r\nOK\r\n",
+        ] {
+            assert!(QueryTimingAdvance
+                .parse_response_struct(response)
+                .unwrap()
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn test_scan_bands() {
+        let mut buffer = [0; 512];
+        let result = ScanBands.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CBANDSCAN\r\n");
+
+        let scan = ScanBands
+            .parse_response_struct(
+                b"\r\n+CBANDSCAN: 3,-97\r\n+CBANDSCAN: 8,-105\r\n+CBANDSCAN: 20,\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(
+            scan.as_slice(),
+            &[
+                BandRssi {
+                    band: 3,
+                    rssi_dbm: Some(-97)
+                },
+                BandRssi {
+                    band: 8,
+                    rssi_dbm: Some(-105)
+                },
+                BandRssi {
+                    band: 20,
+                    rssi_dbm: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_search_timers() {
+        let mut buffer = [0; 512];
+        let request = SetSearchTimers::new(SearchTimers {
+            dwell_ms: 200,
+            interval_secs: 60,
+        })
+        .unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CNSTMR=200,60\r\n"
+        );
+
+        assert!(matches!(
+            SetSearchTimers::new(SearchTimers {
+                dwell_ms: 5,
+                interval_secs: 60,
+            }),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_get_search_timers() {
+        let timers = GetSearchTimers
+            .parse_response_struct(b"\r\n+CNSTMR: 100,30\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(
+            timers,
+            SearchTimers {
+                dwell_ms: 100,
+                interval_secs: 30,
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_serving_cell() {
+        let mut buffer = [0; 512];
+        let result = QueryServingCell.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CENG?\r\n");
+
+        let cell = QueryServingCell
+            .parse_response_struct(
+                b"\r\n+CENG: 2525,0,117,\"0A2B3C1\",-85,-9,-76,12,8,\"1F5A\",0,,-80\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(
+            cell,
+            Some(ServingCell {
+                earfcn: 2525,
+                pci: 117,
+            })
+        );
+    }
+
+    #[test]
+    fn test_query_serving_cell_not_camped() {
+        let cell = QueryServingCell
+            .parse_response_struct(b"\r\nOK\r\n")
+            .unwrap();
+        assert!(cell.is_none());
+    }
+
+    #[test]
+    fn test_set_engineering_reporting() {
+        let mut buffer = [0; 512];
+        let request = SetEngineeringReporting::enable(10).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CENG=1,10\r\n");
+
+        let request = SetEngineeringReporting::disable();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CENG=0\r\n");
+
+        assert!(matches!(
+            SetEngineeringReporting::enable(0),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_parse_engineering_report() {
+        let report = EngineeringReport::parse(
+            b"+CENG: 2525,0,117,\"0A2B3C1\",-85,-9,-76,12,8,\"1F5A\",0,,-80",
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            EngineeringReport {
+                earfcn: 2525,
+                pci: 117,
+                cell_id: heapless::String::try_from("0A2B3C1").unwrap(),
+                rsrp_dbm: -85,
+                rsrq_db: -9,
+                rssi_dbm: -76,
+                snr_db: 12,
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_radio_info() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\
+            \r\n+COPS: 0,2,\"21401\",9\r\n\r\nOK\r\n\
+            \r\n+CBAND: 8\r\n\r\nOK\r\n\
+            \r\n+CEREG: 2,1,\"1A2B\",\"01A2B3C4\",9\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let info = QueryRadioInfo::query(&mut modem).unwrap();
+        assert_eq!(info.access_technology, Some(AccessTechnology::NBIoT));
+        assert_eq!(info.band, 8);
+        assert_eq!(info.cell_id.unwrap().as_str(), "01A2B3C4");
+        assert!(writer
+            .written
+            .ends_with(b"AT+COPS?\r\nAT+CBAND?\r\nAT+CEREG?\r\n"));
+    }
+}
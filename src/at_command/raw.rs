@@ -0,0 +1,104 @@
+use crate::at_command::{strip_ok, AtRequest, BufferType};
+use crate::{AtError, BUFFER_SIZE};
+
+pub type RawResponse = heapless::Vec<u8, BUFFER_SIZE>;
+
+/// Sends a command not modeled by this crate. The command is sent as is, followed by `\r\n`.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RawCommand<'a> {
+    pub command: &'a [u8],
+    /// Identifier the response has to start with, e.g. `+CSQ: `. If it is not set any response
+    /// ending with OK is accepted.
+    pub expect: Option<&'a [u8]>,
+}
+
+impl<'a> RawCommand<'a> {
+    pub fn new(command: &'a [u8]) -> Self {
+        Self {
+            command,
+            expect: None,
+        }
+    }
+
+    pub fn with_expect(mut self, expect: &'a [u8]) -> Self {
+        self.expect = Some(expect);
+        self
+    }
+}
+
+impl AtRequest for RawCommand<'_> {
+    /// The response without the final OK and the surrounding blank lines
+    type Response = RawResponse;
+
+    /// Without an expected identifier the whole response is returned, URCs included
+    fn claims_line(&self, line: &[u8]) -> bool {
+        self.expect.is_none_or(|expect| line.starts_with(expect))
+    }
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let size = self.command.len() + 2;
+        if size > buffer.len() {
+            return Err(size);
+        }
+
+        buffer[..self.command.len()].copy_from_slice(self.command);
+        buffer[self.command.len()..size].copy_from_slice(b"\r\n");
+        Ok(&buffer[..size])
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let payload = strip_ok(data)?;
+
+        if let Some(expect) = self.expect {
+            if !payload.starts_with(expect) {
+                return Err(AtError::AtParseError);
+            }
+        }
+
+        RawResponse::from_slice(payload).map_err(|_| AtError::CapacityError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_raw_command_request() {
+        let mut buffer = [0; 512];
+        let command = RawCommand::new(b"AT+CSQ");
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSQ\r\n");
+    }
+
+    #[test]
+    fn test_raw_command_without_expect() {
+        let command = RawCommand::new(b"AT+CSQ");
+        let response = command
+            .parse_response_struct(b"\r\n+CSQ: 20,99\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(response.as_slice(), b"+CSQ: 20,99");
+    }
+
+    #[test]
+    fn test_raw_command_expect_matches() {
+        let command = RawCommand::new(b"AT+CSQ").with_expect(b"+CSQ: ");
+        let response = command
+            .parse_response_struct(b"\r\n+CSQ: 20,99\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(response.as_slice(), b"+CSQ: 20,99");
+    }
+
+    #[test]
+    fn test_raw_command_expect_does_not_match() {
+        let command = RawCommand::new(b"AT+CSQ").with_expect(b"+CSQ: ");
+        assert!(matches!(
+            command.parse_response_struct(b"\r\nOK\r"),
+            Err(AtError::AtParseError)
+        ));
+        assert!(matches!(
+            command.parse_response_struct(b"\r\n+CEREG: 0,1\r\n\r\nOK\r"),
+            Err(AtError::AtParseError)
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::at_command::{verify_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Events pulsing the RI line, combined as a bitmask
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingIndicatorEvents(u8);
+
+impl RingIndicatorEvents {
+    pub const NONE: Self = Self(0);
+    /// `+CSONMI`
+    pub const SOCKET_DATA: Self = Self(1 << 0);
+    /// `+CMTI` and `+CMT`
+    pub const SMS: Self = Self(1 << 1);
+    /// Registration changes, e.g. `+CEREG`
+    pub const NETWORK: Self = Self(1 << 2);
+    /// Any other URC
+    pub const OTHER: Self = Self(1 << 3);
+    pub const ALL: Self = Self(0x0F);
+
+    /// Fails if bits outside of the documented events are set
+    pub fn from_bits(bits: u8) -> Result<Self, AtError> {
+        if bits & !Self::ALL.0 != 0 {
+            return Err(AtError::InvalidParameter);
+        }
+        Ok(Self(bits))
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for RingIndicatorEvents {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Selects which URCs pulse the RI line, so the host can sleep until one of them arrives.
+/// RI is disabled if no event is selected.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetRingIndicator {
+    pub events: RingIndicatorEvents,
+}
+
+impl AtRequest for SetRingIndicator {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let enabled = self.events != RingIndicatorEvents::NONE;
+        let builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CFGRI")
+            .with_int_parameter(enabled as i32);
+        if enabled {
+            builder.with_int_parameter(self.events.bits()).finish()
+        } else {
+            builder.finish()
+        }
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_ring_indicator_socket_data() {
+        let mut buffer = [0; 512];
+        let request = SetRingIndicator {
+            events: RingIndicatorEvents::SOCKET_DATA,
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CFGRI=1,1\r\n");
+
+        let request = SetRingIndicator {
+            events: RingIndicatorEvents::SOCKET_DATA | RingIndicatorEvents::SMS,
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CFGRI=1,3\r\n");
+    }
+
+    #[test]
+    fn test_set_ring_indicator_disabled() {
+        let mut buffer = [0; 512];
+        let request = SetRingIndicator {
+            events: RingIndicatorEvents::NONE,
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CFGRI=0\r\n");
+    }
+
+    #[test]
+    fn test_ring_indicator_events_mask() {
+        let events = RingIndicatorEvents::from_bits(0x05).unwrap();
+        assert!(events.contains(RingIndicatorEvents::SOCKET_DATA));
+        assert!(events.contains(RingIndicatorEvents::NETWORK));
+        assert!(!events.contains(RingIndicatorEvents::SMS));
+
+        assert!(matches!(
+            RingIndicatorEvents::from_bits(0x10),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::at_command::{strip_ok, AtRequest, BufferType};
+use crate::AtError;
+use at_commands::parser::CommandParser;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubsystemResult {
+    Pass,
+    Fail,
+    Unknown,
+}
+
+impl From<i32> for SubsystemResult {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => SubsystemResult::Pass,
+            1 => SubsystemResult::Fail,
+            _ => SubsystemResult::Unknown,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestResult {
+    pub rf: SubsystemResult,
+    pub sim: SubsystemResult,
+    pub flash: SubsystemResult,
+}
+
+impl SelfTestResult {
+    pub fn passed(&self) -> bool {
+        self.rf == SubsystemResult::Pass
+            && self.sim == SubsystemResult::Pass
+            && self.flash == SubsystemResult::Pass
+    }
+}
+
+/// Runs the built-in self test of the module with `AT+CSELFTEST`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RunSelfTest;
+
+impl RunSelfTest {
+    fn get_self_test_result(data: &[u8]) -> Result<SelfTestResult, AtError> {
+        let (rf, sim, flash) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CSELFTEST: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        Ok(SelfTestResult {
+            rf: rf.into(),
+            sim: sim.into(),
+            flash: flash.into(),
+        })
+    }
+}
+
+impl AtRequest for RunSelfTest {
+    type Response = SelfTestResult;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CSELFTEST")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_self_test_result(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+    use crate::Modem;
+
+    #[test]
+    fn test_run_self_test_request() {
+        let mut buffer = [0; 512];
+        let result = RunSelfTest.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSELFTEST\r\n");
+    }
+
+    #[test]
+    fn test_run_self_test_response() {
+        let result = RunSelfTest
+            .parse_response_struct(b"\r\n+CSELFTEST: 0,1,0\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(result.rf, SubsystemResult::Pass);
+        assert_eq!(result.sim, SubsystemResult::Fail);
+        assert_eq!(result.flash, SubsystemResult::Pass);
+        assert!(!result.passed());
+    }
+
+    #[test]
+    fn test_self_test_unsupported() {
+        let mut writer = MockWriter::default();
+        let mut reader =
+            MockReader::new(b"\r\nOK\r\n\r\n+CME ERROR: 4\r\n\r\n+CME ERROR: 3\r\n\r\nERROR\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        assert_eq!(modem.send_optional(&RunSelfTest).unwrap(), None);
+        // Any other error is a failure of the test
+        assert!(matches!(
+            modem.send_optional(&RunSelfTest),
+            Err(AtError::Cme(3))
+        ));
+        assert!(matches!(
+            modem.send_optional(&RunSelfTest),
+            Err(AtError::ErrorReply(_))
+        ));
+    }
+}
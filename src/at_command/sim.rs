@@ -0,0 +1,213 @@
+use crate::at_command::{parse_csv_line, strip_ok, AtRequest, BufferType};
+use crate::AtError;
+
+const IMSI_SIZE: usize = 15;
+
+pub type Imsi = heapless::String<IMSI_SIZE>;
+
+/// Queries the International Mobile Subscriber Identity of the SIM
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryImsi;
+
+impl QueryImsi {
+    fn get_imsi(data: &[u8]) -> Result<Imsi, AtError> {
+        let imsi = strip_ok(data)?;
+
+        if imsi.len() != IMSI_SIZE || !imsi.iter().all(u8::is_ascii_digit) {
+            return Err(AtError::AtParseError);
+        }
+
+        let imsi = core::str::from_utf8(imsi).map_err(|_| AtError::AtParseError)?;
+        Ok(imsi.try_into()?)
+    }
+}
+
+impl AtRequest for QueryImsi {
+    type Response = Imsi;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CIMI")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_imsi(data)
+    }
+}
+
+/// Longest phone number kept from a phonebook entry
+pub const PHONEBOOK_NUMBER_MAX_SIZE: usize = 32;
+/// Longest name kept from a phonebook entry
+pub const PHONEBOOK_NAME_MAX_SIZE: usize = 32;
+/// Entries kept from a read, more entries fail to parse with [AtError::CapacityError]
+pub const MAX_PHONEBOOK_ENTRIES: usize = 10;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhonebookEntry {
+    pub index: u16,
+    pub number: heapless::String<PHONEBOOK_NUMBER_MAX_SIZE>,
+    /// Type of address, 145 for international numbers and 129 otherwise
+    pub number_type: u8,
+    pub name: heapless::String<PHONEBOOK_NAME_MAX_SIZE>,
+}
+
+pub type Phonebook = heapless::Vec<PhonebookEntry, MAX_PHONEBOOK_ENTRIES>;
+
+/// Reads the entries of the SIM phonebook between two indexes with `AT+CPBR`. Empty slots are
+/// left out, so reading a single empty slot returns no entry.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReadPhonebook {
+    pub(crate) first: u16,
+    pub(crate) last: Option<u16>,
+}
+
+impl ReadPhonebook {
+    /// Indexes start at 1
+    pub fn entry(index: u16) -> Result<Self, AtError> {
+        if index == 0 {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self {
+            first: index,
+            last: None,
+        })
+    }
+
+    /// Reads the entries from `first` to `last`, both included
+    pub fn range(first: u16, last: u16) -> Result<Self, AtError> {
+        if first == 0 || last < first {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self {
+            first,
+            last: Some(last),
+        })
+    }
+
+    /// Parses a `+CPBR: <index>,<number>,<type>,<name>` line per entry
+    fn get_entries(data: &[u8]) -> Result<Phonebook, AtError> {
+        let mut entries = Phonebook::new();
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            if !line.starts_with(b"+CPBR: ") {
+                return Err(AtError::AtParseError);
+            }
+
+            let mut fields = parse_csv_line(line)?
+                .map(|field| core::str::from_utf8(field).map_err(|_| AtError::AtParseError));
+            let mut next = || fields.next().ok_or(AtError::AtParseError)?;
+            let index = next()?.parse().map_err(|_| AtError::AtParseError)?;
+            let number = next()?;
+            let number_type = next()?.parse().map_err(|_| AtError::AtParseError)?;
+            let name = next()?;
+
+            // Some firmwares list empty slots as well
+            if number.is_empty() {
+                continue;
+            }
+
+            entries
+                .push(PhonebookEntry {
+                    index,
+                    number: number.try_into()?,
+                    number_type,
+                    name: name.try_into()?,
+                })
+                .map_err(|_| AtError::CapacityError)?;
+        }
+
+        Ok(entries)
+    }
+}
+
+impl AtRequest for ReadPhonebook {
+    type Response = Phonebook;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CPBR")
+            .with_int_parameter(self.first as i32);
+
+        match self.last {
+            Some(last) => builder.with_int_parameter(last as i32).finish(),
+            None => builder.finish(),
+        }
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_entries(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_query_imsi_request() {
+        let mut buffer = [0; 512];
+        let result = QueryImsi.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CIMI\r\n");
+    }
+
+    #[test]
+    fn test_query_imsi_response() {
+        let imsi = QueryImsi
+            .parse_response_struct(b"\r\n214011234567890\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(imsi.as_str(), "214011234567890");
+    }
+
+    #[test]
+    fn test_query_imsi_malformed_response() {
+        assert!(QueryImsi
+            .parse_response_struct(b"\r\n21401123456789\r\n\r\nOK\r")
+            .is_err());
+        assert!(QueryImsi
+            .parse_response_struct(b"\r\n21401123456789A\r\n\r\nOK\r")
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_phonebook_entry() {
+        let mut buffer = [0; 512];
+        let request = ReadPhonebook::range(1, 5).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CPBR=1,5\r\n");
+
+        let entries = request
+            .parse_response_struct(
+                b"\r\n+CPBR: 1,\"+34600111222\",145,\"Server\"\r\n+CPBR: 3,\"\",129,\"\"\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].index, 1);
+        assert_eq!(entries[0].number.as_str(), "+34600111222");
+        assert_eq!(entries[0].number_type, 145);
+        assert_eq!(entries[0].name.as_str(), "Server");
+    }
+
+    #[test]
+    fn test_read_phonebook_empty_slot() {
+        let mut buffer = [0; 512];
+        let request = ReadPhonebook::entry(2).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CPBR=2\r\n");
+
+        assert!(request
+            .parse_response_struct(b"\r\nOK\r\n")
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            ReadPhonebook::range(5, 1),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+}
@@ -22,6 +22,7 @@ impl From<i32> for SleepIndication {
     }
 }
 
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SleepIndicationStatus;
 
 impl SleepIndicationStatus {
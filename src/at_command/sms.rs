@@ -0,0 +1,833 @@
+use crate::at_command::{strip_ok, verify_ok, AtRequest, BufferType, FinishExact, PromptExchange};
+use crate::AtError;
+use at_commands::parser::CommandParser;
+use pdu::{DeliverPdu, SubmitPdu, PDU_MAX_SIZE};
+
+pub mod pdu;
+
+/// Memory used to store SMS messages
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsStorage {
+    /// SIM message storage
+    SM,
+    /// Mobile equipment message storage
+    ME,
+    /// Storages associated with the mobile equipment
+    MT,
+}
+
+impl SmsStorage {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SmsStorage::SM => "SM",
+            SmsStorage::ME => "ME",
+            SmsStorage::MT => "MT",
+        }
+    }
+
+    /// Returns `None` on storages not supported by this crate
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "SM" => Some(SmsStorage::SM),
+            "ME" => Some(SmsStorage::ME),
+            "MT" => Some(SmsStorage::MT),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StorageUsage {
+    pub used: u16,
+    pub total: u16,
+}
+
+impl StorageUsage {
+    fn new(used: i32, total: i32) -> Result<Self, AtError> {
+        Ok(StorageUsage {
+            used: u16::try_from(used).map_err(|_| AtError::AtParseError)?,
+            total: u16::try_from(total).map_err(|_| AtError::AtParseError)?,
+        })
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.used >= self.total
+    }
+}
+
+/// Selects the storages used to read and delete, to write and send, and to store received
+/// messages. An unsupported storage is rejected by the modem with an error reply.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetSmsStorage {
+    pub read: SmsStorage,
+    pub write: Option<SmsStorage>,
+    pub receive: Option<SmsStorage>,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmsStorageUsage {
+    pub read: StorageUsage,
+    pub write: Option<StorageUsage>,
+    pub receive: Option<StorageUsage>,
+}
+
+impl SetSmsStorage {
+    fn get_usage(data: &[u8]) -> Result<SmsStorageUsage, AtError> {
+        let (read_used, read_total, write_used, write_total, receive_used, receive_total) =
+            CommandParser::parse(strip_ok(data)?)
+                .expect_identifier(b"+CPMS: ")
+                .expect_int_parameter()
+                .expect_int_parameter()
+                .expect_optional_int_parameter()
+                .expect_optional_int_parameter()
+                .expect_optional_int_parameter()
+                .expect_optional_int_parameter()
+                .finish()?;
+
+        let optional_usage = |used: Option<i32>, total: Option<i32>| match (used, total) {
+            (Some(used), Some(total)) => StorageUsage::new(used, total).map(Some),
+            (None, None) => Ok(None),
+            _ => Err(AtError::AtParseError),
+        };
+
+        Ok(SmsStorageUsage {
+            read: StorageUsage::new(read_used, read_total)?,
+            write: optional_usage(write_used, write_total)?,
+            receive: optional_usage(receive_used, receive_total)?,
+        })
+    }
+}
+
+impl AtRequest for SetSmsStorage {
+    type Response = SmsStorageUsage;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let mut builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CPMS")
+            .with_string_parameter(self.read.as_str());
+        if let Some(write) = self.write {
+            builder = builder.with_string_parameter(write.as_str());
+        }
+        if let Some(receive) = self.receive {
+            builder = builder.with_string_parameter(receive.as_str());
+        }
+        builder.finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_usage(data)
+    }
+}
+
+/// Queries the selected SMS storages and how full they are
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QuerySmsStorage;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmsStorageStatus {
+    /// `None` if the modem reports a storage not supported by this crate
+    pub storage: Option<SmsStorage>,
+    pub usage: StorageUsage,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuerySmsStorageResponse {
+    pub read: SmsStorageStatus,
+    pub write: SmsStorageStatus,
+    pub receive: SmsStorageStatus,
+}
+
+impl QuerySmsStorage {
+    fn get_status(data: &[u8]) -> Result<QuerySmsStorageResponse, AtError> {
+        let (
+            read,
+            read_used,
+            read_total,
+            write,
+            write_used,
+            write_total,
+            receive,
+            receive_used,
+            receive_total,
+        ) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CPMS: ")
+            .expect_string_parameter()
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .expect_string_parameter()
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .expect_string_parameter()
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        let status = |storage: &str, used: i32, total: i32| {
+            Ok::<_, AtError>(SmsStorageStatus {
+                storage: SmsStorage::from_str(storage),
+                usage: StorageUsage::new(used, total)?,
+            })
+        };
+
+        Ok(QuerySmsStorageResponse {
+            read: status(read, read_used, read_total)?,
+            write: status(write, write_used, write_total)?,
+            receive: status(receive, receive_used, receive_total)?,
+        })
+    }
+}
+
+impl AtRequest for QuerySmsStorage {
+    type Response = QuerySmsStorageResponse;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CPMS")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_status(data)
+    }
+}
+
+/// Configures how the modem reports new messages, e.g. `mt` 1 reports the storage index of
+/// each message with [NewMessageStored] and 2 forwards it directly with [MessageReceived]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetNewMessageIndication {
+    pub(crate) mode: u8,
+    pub(crate) mt: u8,
+    pub(crate) bm: u8,
+    pub(crate) ds: u8,
+    pub(crate) bfr: u8,
+}
+
+impl SetNewMessageIndication {
+    pub fn new(mode: u8, mt: u8, bm: u8, ds: u8, bfr: u8) -> Result<Self, AtError> {
+        if mode > 3 || mt > 3 || bm > 3 || ds > 2 || bfr > 1 {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self {
+            mode,
+            mt,
+            bm,
+            ds,
+            bfr,
+        })
+    }
+}
+
+impl AtRequest for SetNewMessageIndication {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CNMI")
+            .with_int_parameter(self.mode)
+            .with_int_parameter(self.mt)
+            .with_int_parameter(self.bm)
+            .with_int_parameter(self.ds)
+            .with_int_parameter(self.bfr)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Unsolicited message sent by the modem when a new message is stored
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewMessageStored {
+    /// `None` if the storage is not supported by this crate
+    pub storage: Option<SmsStorage>,
+    pub index: u16,
+}
+
+impl NewMessageStored {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (storage, index) = CommandParser::parse(data)
+            .expect_identifier(b"+CMTI: ")
+            .expect_string_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        Ok(NewMessageStored {
+            storage: SmsStorage::from_str(storage),
+            index: u16::try_from(index).map_err(|_| AtError::AtParseError)?,
+        })
+    }
+}
+
+const SENDER_MAX_SIZE: usize = 20;
+const TIMESTAMP_MAX_SIZE: usize = 20;
+/// Enough for the hex encoded PDU of a single message
+pub const SMS_DATA_MAX_SIZE: usize = 2 * PDU_MAX_SIZE;
+
+/// Unsolicited message sent by the modem when a new message is forwarded without storing it
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageReceived {
+    /// Only reported in text mode
+    pub sender: Option<heapless::String<SENDER_MAX_SIZE>>,
+    /// Only reported in text mode
+    pub timestamp: Option<heapless::String<TIMESTAMP_MAX_SIZE>>,
+    /// Length of the PDU in octets, without the SMSC address. Only reported in PDU mode.
+    pub pdu_len: Option<u16>,
+    /// Message text in text mode, hex encoded PDU in PDU mode
+    pub data: heapless::Vec<u8, SMS_DATA_MAX_SIZE>,
+}
+
+impl MessageReceived {
+    /// The `+CMT` header and the message are sent on two lines, here they are expected to be
+    /// separated by a single `\n`
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let split = data
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or(AtError::AtParseError)?;
+        let (header, message) = (&data[..split], &data[split + 1..]);
+        let data = heapless::Vec::from_slice(message).map_err(|_| AtError::CapacityError)?;
+
+        let header = header
+            .strip_prefix(b"+CMT: ")
+            .and_then(|header| core::str::from_utf8(header).ok())
+            .ok_or(AtError::AtParseError)?;
+        let (sender, rest) = split_quoted_field(header)?;
+
+        // PDU mode: +CMT: [<alpha>],<length>
+        if !rest.contains('"') {
+            return Ok(MessageReceived {
+                sender: None,
+                timestamp: None,
+                pdu_len: Some(rest.parse().map_err(|_| AtError::AtParseError)?),
+                data,
+            });
+        }
+
+        // Text mode: +CMT: <oa>,[<alpha>],<scts>
+        let timestamp = rest
+            .strip_suffix('"')
+            .and_then(|rest| rest.rsplit_once(",\""))
+            .map(|(_alpha, timestamp)| timestamp)
+            .ok_or(AtError::AtParseError)?;
+
+        Ok(MessageReceived {
+            sender: Some(sender.ok_or(AtError::AtParseError)?.try_into()?),
+            timestamp: Some(timestamp.try_into()?),
+            pdu_len: None,
+            data,
+        })
+    }
+}
+
+/// Splits the first field of a parameter list, which may be empty or quoted
+fn split_quoted_field(data: &str) -> Result<(Option<&str>, &str), AtError> {
+    if let Some(quoted) = data.strip_prefix('"') {
+        let (field, rest) = quoted.split_once('"').ok_or(AtError::AtParseError)?;
+        let rest = rest.strip_prefix(',').ok_or(AtError::AtParseError)?;
+        Ok((Some(field), rest))
+    } else {
+        let (field, rest) = data.split_once(',').ok_or(AtError::AtParseError)?;
+        Ok((if field.is_empty() { None } else { Some(field) }, rest))
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmsFormat {
+    PDU = 0,
+    Text = 1,
+}
+
+/// Selects the format used to send and read messages
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetSmsFormat {
+    pub format: SmsFormat,
+}
+
+impl AtRequest for SetSmsFormat {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CMGF")
+            .with_int_parameter(self.format as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Sends a message in PDU mode, see [SetSmsFormat]. The PDU is only written once the modem
+/// answered the `>` prompt, so it has to be sent with
+/// [Modem::send_prompt_exchange](crate::Modem::send_prompt_exchange).
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SendSmsPdu<'a> {
+    pub pdu: &'a SubmitPdu,
+}
+
+/// Terminates the PDU of [SendSmsPdu]
+const CTRL_Z: u8 = 0x1A;
+
+impl SendSmsPdu<'_> {
+    fn write_header(&self, buffer: &mut [u8]) -> Result<usize, usize> {
+        Ok(
+            at_commands::builder::CommandBuilder::create_set(buffer, true)
+                .named("+CMGS")
+                .with_int_parameter(self.pdu.tpdu_len() as i32)
+                .finish_with(b"\r")?
+                .len(),
+        )
+    }
+
+    fn write_data(&self, buffer: &mut [u8]) -> Result<usize, usize> {
+        let pdu = self.pdu.as_bytes();
+        let size = 2 * pdu.len() + 1;
+        let hex_len =
+            pdu::write_hex(pdu, buffer.get_mut(..size - 1).ok_or(size)?).map_err(|_| size)?;
+        buffer[hex_len] = CTRL_Z;
+        Ok(size)
+    }
+
+    fn get_message_reference(data: &[u8]) -> Result<u8, AtError> {
+        let (message_reference,) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CMGS: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        u8::try_from(message_reference).map_err(|_| AtError::AtParseError)
+    }
+}
+
+impl PromptExchange for SendSmsPdu<'_> {
+    /// Message reference assigned by the modem
+    type Response = u8;
+
+    fn get_header<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let len = self.write_header(&mut buffer[..])?;
+        Ok(&buffer[..len])
+    }
+
+    fn get_data<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        let len = self.write_data(&mut buffer[..])?;
+        Ok(&buffer[..len])
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_message_reference(data)
+    }
+}
+
+/// Reads a stored message in PDU mode, see [SetSmsFormat]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReadSmsPdu {
+    /// Index in the storage, e.g. reported by [NewMessageStored]
+    pub index: u16,
+}
+
+impl ReadSmsPdu {
+    fn get_message(data: &[u8]) -> Result<DeliverPdu, AtError> {
+        let payload = strip_ok(data)?;
+        let split = payload
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or(AtError::AtParseError)?;
+        let (header, pdu) = (&payload[..split], &payload[split + 1..]);
+
+        // +CMGR: <stat>,[<alpha>],<length>
+        if !header.starts_with(b"+CMGR: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        let pdu = pdu::decode_hex::<PDU_MAX_SIZE>(super::trim_line_breaks(pdu))?;
+        DeliverPdu::decode(&pdu)
+    }
+}
+
+impl AtRequest for ReadSmsPdu {
+    type Response = DeliverPdu;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CMGR")
+            .with_int_parameter(self.index as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_message(data)
+    }
+}
+
+/// Longest service center number accepted by [SetServiceCenter], `+` included
+pub const SERVICE_CENTER_MAX_LEN: usize = 21;
+
+/// Type of address of a number in international format, starting with `+`
+pub const INTERNATIONAL_NUMBER_TYPE: u8 = 145;
+/// Type of address of a number in national or unknown format
+pub const NATIONAL_NUMBER_TYPE: u8 = 129;
+
+/// Number of the SMS service center used to send messages
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceCenter {
+    pub number: heapless::String<SERVICE_CENTER_MAX_LEN>,
+    /// [INTERNATIONAL_NUMBER_TYPE] or [NATIONAL_NUMBER_TYPE]
+    pub type_of_address: u8,
+}
+
+/// Sets the SMS service center with `AT+CSCA`, needed on networks where the SIM does not
+/// provide it
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetServiceCenter<'a> {
+    pub(crate) number: &'a str,
+}
+
+impl<'a> SetServiceCenter<'a> {
+    /// The number is made of digits, optionally starting with `+` for the international format.
+    /// The type of address is chosen from it.
+    pub fn new(number: &'a str) -> Result<Self, AtError> {
+        let digits = number.strip_prefix('+').unwrap_or(number);
+        if digits.is_empty()
+            || number.len() > SERVICE_CENTER_MAX_LEN
+            || !digits.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { number })
+    }
+
+    fn type_of_address(&self) -> u8 {
+        if self.number.starts_with('+') {
+            INTERNATIONAL_NUMBER_TYPE
+        } else {
+            NATIONAL_NUMBER_TYPE
+        }
+    }
+}
+
+impl AtRequest for SetServiceCenter<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSCA")
+            .with_string_parameter(self.number)
+            .with_int_parameter(self.type_of_address() as i32)
+            .finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads the SMS service center with `AT+CSCA?`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryServiceCenter;
+
+impl AtRequest for QueryServiceCenter {
+    type Response = ServiceCenter;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CSCA")
+            .finish()
+    }
+
+    /// Parses `+CSCA: "<number>",<tosca>`
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let (number, type_of_address) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CSCA: ")
+            .expect_string_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        Ok(ServiceCenter {
+            number: heapless::String::try_from(number).map_err(|_| AtError::CapacityError)?,
+            type_of_address: u8::try_from(type_of_address).map_err(|_| AtError::AtParseError)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockTimer, MockWriter};
+    use crate::Modem;
+
+    #[test]
+    fn test_set_sms_storage_request() {
+        let mut buffer = [0; 512];
+        let request = SetSmsStorage {
+            read: SmsStorage::SM,
+            write: Some(SmsStorage::SM),
+            receive: Some(SmsStorage::SM),
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CPMS=\"SM\",\"SM\",\"SM\"\r\n"
+        );
+
+        let request = SetSmsStorage {
+            read: SmsStorage::SM,
+            write: None,
+            receive: None,
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CPMS=\"SM\"\r\n");
+    }
+
+    #[test]
+    fn test_set_sms_storage_response() {
+        let request = SetSmsStorage {
+            read: SmsStorage::SM,
+            write: Some(SmsStorage::SM),
+            receive: Some(SmsStorage::SM),
+        };
+        let usage = request
+            .parse_response_struct(b"\r\n+CPMS: 3,50,3,50,50,50\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(usage.read, StorageUsage { used: 3, total: 50 });
+        assert!(!usage.write.unwrap().is_full());
+        assert!(usage.receive.unwrap().is_full());
+
+        let usage = request
+            .parse_response_struct(b"\r\n+CPMS: 3,50\r\n\r\nOK\r")
+            .unwrap();
+        assert!(usage.write.is_none());
+        assert!(usage.receive.is_none());
+    }
+
+    #[test]
+    fn test_query_sms_storage_response() {
+        let mut buffer = [0; 512];
+        let result = QuerySmsStorage.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CPMS?\r\n");
+
+        let status = QuerySmsStorage
+            .parse_response_struct(b"\r\n+CPMS: \"SM\",3,50,\"ME\",0,20,\"SR\",1,10\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(status.read.storage, Some(SmsStorage::SM));
+        assert_eq!(status.read.usage, StorageUsage { used: 3, total: 50 });
+        assert_eq!(status.write.storage, Some(SmsStorage::ME));
+        assert_eq!(status.receive.storage, None);
+        assert_eq!(status.receive.usage, StorageUsage { used: 1, total: 10 });
+    }
+
+    #[test]
+    fn test_set_new_message_indication_request() {
+        let mut buffer = [0; 512];
+        let request = SetNewMessageIndication::new(2, 1, 0, 0, 0).unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CNMI=2,1,0,0,0\r\n"
+        );
+    }
+
+    #[test]
+    fn test_set_new_message_indication_out_of_range() {
+        assert!(matches!(
+            SetNewMessageIndication::new(4, 1, 0, 0, 0),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            SetNewMessageIndication::new(2, 1, 0, 3, 0),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            SetNewMessageIndication::new(2, 1, 0, 0, 2),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_parse_new_message_stored() {
+        let report = NewMessageStored::parse(b"+CMTI: \"SM\",3").unwrap();
+        assert_eq!(report.storage, Some(SmsStorage::SM));
+        assert_eq!(report.index, 3);
+    }
+
+    #[test]
+    fn test_parse_message_received_text_mode() {
+        let message =
+            MessageReceived::parse(b"+CMT: \"+34600111222\",,\"24/01/01,12:00:00+04\"\nHello")
+                .unwrap();
+        assert_eq!(message.sender.unwrap().as_str(), "+34600111222");
+        assert_eq!(message.timestamp.unwrap().as_str(), "24/01/01,12:00:00+04");
+        assert_eq!(message.pdu_len, None);
+        assert_eq!(message.data.as_slice(), b"Hello");
+    }
+
+    #[test]
+    fn test_parse_message_received_pdu_mode() {
+        let message = MessageReceived::parse(b"+CMT: ,5\n0001000A").unwrap();
+        assert_eq!(message.sender, None);
+        assert_eq!(message.pdu_len, Some(5));
+        assert_eq!(message.data.as_slice(), b"0001000A");
+    }
+
+    #[test]
+    fn test_set_sms_format_request() {
+        let mut buffer = [0; 512];
+        let request = SetSmsFormat {
+            format: SmsFormat::PDU,
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CMGF=0\r\n");
+    }
+
+    #[test]
+    fn test_send_sms_pdu_request() {
+        let mut buffer = [0; 512];
+        let pdu = SubmitPdu::new("+34600111222", "hellohello").unwrap();
+        let request = SendSmsPdu { pdu: &pdu };
+        let header = request.get_header(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(header).unwrap(), "AT+CMGS=22\r");
+        let data = request.get_data(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(data).unwrap(),
+            "0001000B914306101122F200000AE8329BFD4697D9EC37\u{1a}"
+        );
+    }
+
+    #[test]
+    fn test_send_sms_pdu_response() {
+        let pdu = SubmitPdu::new("+34600111222", "hello").unwrap();
+        let request = SendSmsPdu { pdu: &pdu };
+        let message_reference = request
+            .parse_response_struct(b"\r\n+CMGS: 12\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(message_reference, 12);
+    }
+
+    #[test]
+    fn test_read_sms_pdu_response() {
+        let mut buffer = [0; 512];
+        let request = ReadSmsPdu { index: 3 };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CMGR=3\r\n");
+
+        let message = request
+            .parse_response_struct(
+                b"\r\n+CMGR: 0,,33\r\n07914306073011F0040B914306101122F2000042101021430080\
+                0AE8329BFD4697D9EC37\r\n\r\nOK\r",
+            )
+            .unwrap();
+        assert_eq!(message.sender.as_str(), "+34600111222");
+        assert_eq!(message.text.as_str(), "hellohello");
+    }
+
+    #[test]
+    fn test_send_sms_pdu_prompt_exchange() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n> \r\n+CMGS: 7\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let pdu = SubmitPdu::new("+34600111222", "hellohello").unwrap();
+
+        let message_reference = modem
+            .send_prompt_exchange(&SendSmsPdu { pdu: &pdu }, 1000, &MockTimer::new(10))
+            .unwrap();
+        assert_eq!(message_reference, 7);
+        assert!(writer
+            .written
+            .ends_with(b"AT+CMGS=22\r0001000B914306101122F200000AE8329BFD4697D9EC37\x1a"));
+    }
+
+    #[test]
+    fn test_send_sms_pdu_prompt_refused() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nERROR\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let pdu = SubmitPdu::new("+34600111222", "hello").unwrap();
+
+        assert!(matches!(
+            modem.send_prompt_exchange(&SendSmsPdu { pdu: &pdu }, 1000, &MockTimer::new(10)),
+            Err(AtError::ErrorReply(_))
+        ));
+        assert!(writer.written.ends_with(b"AT+CMGS=18\r"));
+    }
+
+    #[test]
+    fn test_send_sms_pdu_prompt_cme_error() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CME ERROR: 10\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let pdu = SubmitPdu::new("+34600111222", "hello").unwrap();
+
+        assert!(matches!(
+            modem.send_prompt_exchange(&SendSmsPdu { pdu: &pdu }, 1000, &MockTimer::new(10)),
+            Err(AtError::Cme(10))
+        ));
+        assert!(writer.written.ends_with(b"AT+CMGS=18\r"));
+    }
+
+    #[test]
+    fn test_send_sms_pdu_prompt_timeout() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CMTI: \"SM\",1\r\n\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let pdu = SubmitPdu::new("+34600111222", "hello").unwrap();
+
+        assert!(matches!(
+            modem.send_prompt_exchange(&SendSmsPdu { pdu: &pdu }, 50, &MockTimer::new(10)),
+            Err(AtError::Timeout)
+        ));
+        assert!(writer.written.ends_with(b"AT+CMGS=18\r"));
+    }
+
+    #[test]
+    fn test_set_service_center_request() {
+        let mut buffer = [0; 512];
+
+        let request = SetServiceCenter::new("+34609090909").unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSCA=\"+34609090909\",145\r\n"
+        );
+
+        let request = SetServiceCenter::new("609090909").unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSCA=\"609090909\",129\r\n"
+        );
+    }
+
+    #[test]
+    fn test_set_invalid_service_center() {
+        for number in ["", "+", "+34 609", "*100#", "+3460909090909090909090"] {
+            assert!(matches!(
+                SetServiceCenter::new(number),
+                Err(AtError::InvalidParameter)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_query_service_center_response() {
+        let service_center = QueryServiceCenter
+            .parse_response_struct(b"\r\n+CSCA: \"+34609090909\",145\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(service_center.number.as_str(), "+34609090909");
+        assert_eq!(service_center.type_of_address, INTERNATIONAL_NUMBER_TYPE);
+
+        assert!(QueryServiceCenter
+            .parse_response_struct(b"\r\n+CSCA: \"+34609090909\"\r\n\r\nOK\r\n")
+            .is_err());
+    }
+}
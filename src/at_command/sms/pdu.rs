@@ -0,0 +1,869 @@
+//! Minimal encoder of SMS-SUBMIT and decoder of SMS-DELIVER PDUs (3GPP TS 23.040). Only the
+//! GSM 7 bit default alphabet and UCS2 are supported.
+
+use crate::AtError;
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// Max size of a PDU in octets, SMSC address included
+pub const PDU_MAX_SIZE: usize = 176;
+/// Max size of the user data in octets
+const USER_DATA_MAX_SIZE: usize = 140;
+/// Max size of the user data in septets when using the GSM 7 bit alphabet
+pub const GSM7_MAX_SEPTETS: usize = 160;
+pub const SMS_TEXT_MAX_SIZE: usize = 320;
+const ADDRESS_MAX_SIZE: usize = 20;
+
+pub type Address = heapless::String<ADDRESS_MAX_SIZE>;
+pub type SmsText = heapless::String<SMS_TEXT_MAX_SIZE>;
+
+const ESCAPE: u8 = 0x1B;
+
+#[rustfmt::skip]
+const GSM7_BASIC: [char; 128] = [
+    '@', '£', '$', '¥', 'è', 'é', 'ù', 'ì', 'ò', 'Ç', '\n', 'Ø', 'ø', '\r', 'Å', 'å',
+    'Δ', '_', 'Φ', 'Γ', 'Λ', 'Ω', 'Π', 'Ψ', 'Σ', 'Θ', 'Ξ', '\u{1b}', 'Æ', 'æ', 'ß', 'É',
+    ' ', '!', '"', '#', '¤', '%', '&', '\'', '(', ')', '*', '+', ',', '-', '.', '/',
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', ':', ';', '<', '=', '>', '?',
+    '¡', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O',
+    'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'Ä', 'Ö', 'Ñ', 'Ü', '§',
+    '¿', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o',
+    'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'ä', 'ö', 'ñ', 'ü', 'à',
+];
+
+/// Characters reached through the escape septet
+const GSM7_EXTENSION: [(u8, char); 10] = [
+    (0x0A, '\u{c}'),
+    (0x14, '^'),
+    (0x28, '{'),
+    (0x29, '}'),
+    (0x2F, '\\'),
+    (0x3C, '['),
+    (0x3D, '~'),
+    (0x3E, ']'),
+    (0x40, '|'),
+    (0x65, '€'),
+];
+
+/// Septets needed to encode the character, `None` if it is not part of the GSM alphabet
+pub(crate) fn gsm7_encode_char(c: char) -> Option<([u8; 2], usize)> {
+    if c != '\u{1b}' {
+        if let Some(septet) = GSM7_BASIC.iter().position(|x| *x == c) {
+            return Some(([septet as u8, 0], 1));
+        }
+    }
+    GSM7_EXTENSION
+        .iter()
+        .find(|(_, x)| *x == c)
+        .map(|(septet, _)| ([ESCAPE, *septet], 2))
+}
+
+fn gsm7_decode(septets: impl Iterator<Item = u8>, text: &mut SmsText) -> Result<(), AtError> {
+    let mut escaped = false;
+    for septet in septets {
+        let c = if escaped {
+            escaped = false;
+            GSM7_EXTENSION
+                .iter()
+                .find(|(x, _)| *x == septet)
+                .map_or(' ', |(_, c)| *c)
+        } else if septet == ESCAPE {
+            escaped = true;
+            continue;
+        } else {
+            GSM7_BASIC[septet as usize & 0x7F]
+        };
+        text.push(c).map_err(|_| AtError::CapacityError)?;
+    }
+    Ok(())
+}
+
+/// Packs the header octets followed by the septets, the septets start on a septet boundary.
+/// Returns the user data and its length in septets.
+fn pack_user_data(
+    header: &[u8],
+    septets: &[u8],
+) -> Result<(heapless::Vec<u8, USER_DATA_MAX_SIZE>, u8), AtError> {
+    let mut user_data = [0_u8; USER_DATA_MAX_SIZE];
+    let mut bit = 0;
+    let mut write = |bit: &mut usize, value: u8, len: usize| -> Result<(), AtError> {
+        for i in 0..len {
+            let position = *bit + i;
+            let octet = user_data
+                .get_mut(position / 8)
+                .ok_or(AtError::CapacityError)?;
+            if value >> i & 1 == 1 {
+                *octet |= 1 << (position % 8);
+            }
+        }
+        *bit += len;
+        Ok(())
+    };
+
+    for octet in header {
+        write(&mut bit, *octet, 8)?;
+    }
+    bit = bit.div_ceil(7) * 7;
+    for septet in septets {
+        write(&mut bit, *septet, 7)?;
+    }
+
+    let user_data = heapless::Vec::from_slice(&user_data[..bit.div_ceil(8)])
+        .map_err(|_| AtError::CapacityError)?;
+    Ok((user_data, (bit / 7) as u8))
+}
+
+/// Reads `count` septets starting at the given septet
+fn unpack_septets(data: &[u8], start: usize, count: usize) -> impl Iterator<Item = u8> + '_ {
+    (start..start + count).map_while(move |septet| {
+        let mut value = 0;
+        for i in 0..7 {
+            let position = septet * 7 + i;
+            let octet = data.get(position / 8)?;
+            value |= (octet >> (position % 8) & 1) << i;
+        }
+        Some(value)
+    })
+}
+
+/// SMS-SUBMIT PDU using the SMSC stored in the SIM
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitPdu {
+    octets: heapless::Vec<u8, PDU_MAX_SIZE>,
+}
+
+impl SubmitPdu {
+    /// Encodes the text with the GSM 7 bit alphabet if possible, UCS2 otherwise
+    pub fn new(destination: &str, text: &str) -> Result<Self, AtError> {
+        Self::with_user_data_header(destination, &[], text)
+    }
+
+    /// The header information elements are given without the header length
+    pub(crate) fn with_user_data_header(
+        destination: &str,
+        header: &[u8],
+        text: &str,
+    ) -> Result<Self, AtError> {
+        let mut octets = heapless::Vec::<u8, PDU_MAX_SIZE>::new();
+        let mut push = |data: &[u8]| {
+            octets
+                .extend_from_slice(data)
+                .map_err(|_| AtError::CapacityError)
+        };
+
+        // SMSC from the SIM, SMS-SUBMIT, message reference set by the modem
+        let first_octet = if header.is_empty() { 0x01 } else { 0x41 };
+        push(&[0x00, first_octet, 0x00])?;
+        push_address(&mut push, destination)?;
+
+        let mut full_header = heapless::Vec::<u8, USER_DATA_MAX_SIZE>::new();
+        if !header.is_empty() {
+            full_header
+                .push(header.len() as u8)
+                .map_err(|_| AtError::CapacityError)?;
+            full_header
+                .extend_from_slice(header)
+                .map_err(|_| AtError::CapacityError)?;
+        }
+
+        match encode_gsm7(text)? {
+            Some(septets) => {
+                let (user_data, length) = pack_user_data(&full_header, &septets)?;
+                // Protocol identifier, data coding scheme and user data length
+                push(&[0x00, 0x00, length])?;
+                push(&user_data)?;
+            }
+            None => {
+                let mut user_data = full_header;
+                for unit in text.encode_utf16() {
+                    user_data
+                        .extend_from_slice(&unit.to_be_bytes())
+                        .map_err(|_| AtError::CapacityError)?;
+                }
+                push(&[0x00, 0x08, user_data.len() as u8])?;
+                push(&user_data)?;
+            }
+        }
+
+        Ok(SubmitPdu { octets })
+    }
+
+    /// Length expected by `AT+CMGS`, which does not count the SMSC address
+    pub fn tpdu_len(&self) -> usize {
+        self.octets.len() - 1
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.octets
+    }
+}
+
+/// Septets and UCS2 characters of a single part of a concatenated message, the rest of the
+/// user data is used by the header
+const CONCATENATED_GSM7_MAX_SEPTETS: usize = 153;
+const CONCATENATED_UCS2_MAX_UNITS: usize = 67;
+const UCS2_MAX_UNITS: usize = 70;
+
+/// Splits a text too long for a single message into the parts of a concatenated message. A
+/// text which fits in a single message is returned as a single part without header.
+pub struct ConcatenatedSubmit<'a> {
+    destination: &'a str,
+    remaining: &'a str,
+    gsm7: bool,
+    reference: u8,
+    total: u8,
+    sequence: u8,
+}
+
+impl<'a> ConcatenatedSubmit<'a> {
+    /// The reference has to be different for each concatenated message sent to a destination
+    pub fn new(destination: &'a str, text: &'a str, reference: u8) -> Result<Self, AtError> {
+        let gsm7 = text.chars().all(|c| gsm7_encode_char(c).is_some());
+        let mut split = Self {
+            destination,
+            remaining: text,
+            gsm7,
+            reference,
+            total: 1,
+            sequence: 0,
+        };
+
+        if !split.fits_single_message() {
+            let mut total = 0_u8;
+            let mut remaining = text;
+            while !remaining.is_empty() {
+                total = total.checked_add(1).ok_or(AtError::CapacityError)?;
+                remaining = &remaining[split.part_len(remaining)..];
+            }
+            split.total = total;
+        }
+        Ok(split)
+    }
+
+    pub fn parts(&self) -> u8 {
+        self.total
+    }
+
+    fn fits_single_message(&self) -> bool {
+        if self.gsm7 {
+            septet_count(self.remaining) <= GSM7_MAX_SEPTETS
+        } else {
+            self.remaining.encode_utf16().count() <= UCS2_MAX_UNITS
+        }
+    }
+
+    /// Length in bytes of the next part, characters are never split
+    fn part_len(&self, text: &str) -> usize {
+        let mut used = 0;
+        for (index, c) in text.char_indices() {
+            let (size, max) = if self.gsm7 {
+                (
+                    septet_count(c.encode_utf8(&mut [0; 4])),
+                    CONCATENATED_GSM7_MAX_SEPTETS,
+                )
+            } else {
+                (c.len_utf16(), CONCATENATED_UCS2_MAX_UNITS)
+            };
+            if used + size > max {
+                return index;
+            }
+            used += size;
+        }
+        text.len()
+    }
+}
+
+impl Iterator for ConcatenatedSubmit<'_> {
+    type Item = Result<SubmitPdu, AtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.sequence == self.total {
+            return None;
+        }
+        self.sequence += 1;
+
+        if self.total == 1 {
+            let text = core::mem::take(&mut self.remaining);
+            return Some(SubmitPdu::new(self.destination, text));
+        }
+
+        let (part, remaining) = self.remaining.split_at(self.part_len(self.remaining));
+        self.remaining = remaining;
+        let header = [
+            IEI_CONCATENATION_8_BIT,
+            3,
+            self.reference,
+            self.total,
+            self.sequence,
+        ];
+        Some(SubmitPdu::with_user_data_header(
+            self.destination,
+            &header,
+            part,
+        ))
+    }
+}
+
+fn septet_count(text: &str) -> usize {
+    text.chars()
+        .filter_map(gsm7_encode_char)
+        .map(|(_, len)| len)
+        .sum()
+}
+
+/// Returns `None` if the text is not part of the GSM alphabet
+fn encode_gsm7(text: &str) -> Result<Option<heapless::Vec<u8, GSM7_MAX_SEPTETS>>, AtError> {
+    let mut septets = heapless::Vec::new();
+    for c in text.chars() {
+        let Some((encoded, len)) = gsm7_encode_char(c) else {
+            return Ok(None);
+        };
+        septets
+            .extend_from_slice(&encoded[..len])
+            .map_err(|_| AtError::CapacityError)?;
+    }
+    Ok(Some(septets))
+}
+
+fn push_address(
+    push: &mut impl FnMut(&[u8]) -> Result<(), AtError>,
+    address: &str,
+) -> Result<(), AtError> {
+    let (type_of_address, digits) = match address.strip_prefix('+') {
+        Some(digits) => (0x91, digits),
+        None => (0x81, address),
+    };
+    if digits.is_empty()
+        || digits.len() > ADDRESS_MAX_SIZE
+        || !digits.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(AtError::InvalidAddress);
+    }
+
+    push(&[digits.len() as u8, type_of_address])?;
+    for pair in digits.as_bytes().chunks(2) {
+        let low = pair[0] - b'0';
+        let high = pair.get(1).map_or(0x0F, |x| x - b'0');
+        push(&[high << 4 | low])?;
+    }
+    Ok(())
+}
+
+/// Decoded SMS-DELIVER PDU
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliverPdu {
+    pub sender: Address,
+    /// Service center timestamp in the sender local time
+    pub timestamp: NaiveDateTime,
+    /// Offset of the timestamp from UTC in quarters of an hour
+    pub time_zone_quarter_hours: i8,
+    pub text: SmsText,
+    /// Set if the message is a part of a concatenated message
+    pub concatenation: Option<Concatenation>,
+}
+
+/// Position of a part in a concatenated message
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Concatenation {
+    /// Same for all the parts of a message
+    pub reference: u16,
+    pub total: u8,
+    /// Starts at 1
+    pub sequence: u8,
+}
+
+/// Information element identifiers of the concatenation with 8 and 16 bit references
+const IEI_CONCATENATION_8_BIT: u8 = 0x00;
+const IEI_CONCATENATION_16_BIT: u8 = 0x08;
+
+impl Concatenation {
+    /// Looks for the concatenation in the information elements of the user data header
+    fn from_header(mut header: &[u8]) -> Result<Option<Self>, AtError> {
+        while let [identifier, len, rest @ ..] = header {
+            let (element, rest) = rest
+                .split_at_checked(*len as usize)
+                .ok_or(AtError::AtParseError)?;
+            let concatenation = match (*identifier, element) {
+                (IEI_CONCATENATION_8_BIT, [reference, total, sequence]) => Some(Concatenation {
+                    reference: *reference as u16,
+                    total: *total,
+                    sequence: *sequence,
+                }),
+                (IEI_CONCATENATION_16_BIT, [high, low, total, sequence]) => Some(Concatenation {
+                    reference: u16::from_be_bytes([*high, *low]),
+                    total: *total,
+                    sequence: *sequence,
+                }),
+                _ => None,
+            };
+            if concatenation.is_some() {
+                return Ok(concatenation);
+            }
+            header = rest;
+        }
+        Ok(None)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AtError> {
+        let data = self
+            .data
+            .get(self.position..self.position + len)
+            .ok_or(AtError::AtParseError)?;
+        self.position += len;
+        Ok(data)
+    }
+
+    fn byte(&mut self) -> Result<u8, AtError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let data = &self.data[self.position..];
+        self.position = self.data.len();
+        data
+    }
+}
+
+fn semi_octet(octet: u8) -> Result<u32, AtError> {
+    let (low, high) = (octet & 0x0F, octet >> 4);
+    if low > 9 || high > 9 {
+        return Err(AtError::AtParseError);
+    }
+    Ok(low as u32 * 10 + high as u32)
+}
+
+impl DeliverPdu {
+    /// Decodes the PDU, SMSC address included, as returned by the modem in PDU mode
+    pub fn decode(pdu: &[u8]) -> Result<Self, AtError> {
+        let mut reader = Reader {
+            data: pdu,
+            position: 0,
+        };
+
+        let smsc_len = reader.byte()? as usize;
+        reader.take(smsc_len)?;
+
+        let first_octet = reader.byte()?;
+        if first_octet & 0x03 != 0x00 {
+            // Not an SMS-DELIVER
+            return Err(AtError::AtParseError);
+        }
+        let has_header = first_octet & 0x40 != 0;
+
+        let sender_digits = reader.byte()? as usize;
+        let type_of_address = reader.byte()?;
+        let sender = decode_address(
+            reader.take(sender_digits.div_ceil(2))?,
+            sender_digits,
+            type_of_address,
+        )?;
+
+        let _protocol_identifier = reader.byte()?;
+        let data_coding_scheme = reader.byte()?;
+        let (timestamp, time_zone_quarter_hours) = decode_timestamp(reader.take(7)?)?;
+        let user_data_len = reader.byte()? as usize;
+        let user_data = reader.rest();
+
+        let (header_len, concatenation) = if has_header {
+            let header_len = 1 + *user_data.first().ok_or(AtError::AtParseError)? as usize;
+            let header = user_data.get(1..header_len).ok_or(AtError::AtParseError)?;
+            (header_len, Concatenation::from_header(header)?)
+        } else {
+            (0, None)
+        };
+
+        let mut text = SmsText::new();
+        match data_coding_scheme {
+            // General data coding with the default alphabet
+            0x00..=0x03 | 0x10..=0x13 | 0xF0..=0xF3 => {
+                let start = (header_len * 8).div_ceil(7);
+                let count = user_data_len
+                    .checked_sub(start)
+                    .ok_or(AtError::AtParseError)?;
+                gsm7_decode(unpack_septets(user_data, start, count), &mut text)?;
+            }
+            0x08..=0x0B | 0x18..=0x1B => {
+                let data = user_data
+                    .get(header_len..user_data_len)
+                    .ok_or(AtError::AtParseError)?;
+                let units = data
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+                for c in char::decode_utf16(units) {
+                    text.push(c.map_err(|_| AtError::AtParseError)?)
+                        .map_err(|_| AtError::CapacityError)?;
+                }
+            }
+            _ => return Err(AtError::AtParseError),
+        }
+
+        Ok(DeliverPdu {
+            sender,
+            timestamp,
+            time_zone_quarter_hours,
+            text,
+            concatenation,
+        })
+    }
+}
+
+fn decode_address(data: &[u8], digits: usize, type_of_address: u8) -> Result<Address, AtError> {
+    let mut address = Address::new();
+    match type_of_address & 0x70 {
+        // Alphanumeric, the length counts the semi octets of the packed septets
+        0x50 => {
+            let mut text = SmsText::new();
+            gsm7_decode(unpack_septets(data, 0, digits * 4 / 7), &mut text)?;
+            address
+                .push_str(&text)
+                .map_err(|_| AtError::CapacityError)?;
+        }
+        type_of_number => {
+            if type_of_number == 0x10 {
+                address.push('+').map_err(|_| AtError::CapacityError)?;
+            }
+            for i in 0..digits {
+                let digit = data[i / 2] >> (4 * (i % 2)) & 0x0F;
+                let c = match digit {
+                    0..=9 => (b'0' + digit) as char,
+                    0x0A => '*',
+                    0x0B => '#',
+                    _ => return Err(AtError::AtParseError),
+                };
+                address.push(c).map_err(|_| AtError::CapacityError)?;
+            }
+        }
+    }
+    Ok(address)
+}
+
+fn decode_timestamp(data: &[u8]) -> Result<(NaiveDateTime, i8), AtError> {
+    let date = NaiveDate::from_ymd_opt(
+        2000 + semi_octet(data[0])? as i32,
+        semi_octet(data[1])?,
+        semi_octet(data[2])?,
+    )
+    .and_then(|date| {
+        date.and_hms_opt(
+            semi_octet(data[3]).ok()?,
+            semi_octet(data[4]).ok()?,
+            semi_octet(data[5]).ok()?,
+        )
+    })
+    .ok_or(AtError::AtParseError)?;
+
+    // The sign is the bit 3 of the tens digit
+    let time_zone = data[6];
+    let quarter_hours = semi_octet(time_zone & 0xF7)? as i8;
+    let quarter_hours = if time_zone & 0x08 != 0 {
+        -quarter_hours
+    } else {
+        quarter_hours
+    };
+
+    Ok((date, quarter_hours))
+}
+
+struct StoredPart {
+    sender: Address,
+    concatenation: Concatenation,
+    text: SmsText,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReassembledSms<const M: usize> {
+    pub sender: Address,
+    pub text: heapless::String<M>,
+}
+
+/// Reassembles concatenated messages, keeping up to `N` parts until the rest of their message
+/// is received
+pub struct SmsReassembler<const N: usize> {
+    parts: heapless::Vec<StoredPart, N>,
+}
+
+impl<const N: usize> Default for SmsReassembler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SmsReassembler<N> {
+    pub fn new() -> Self {
+        Self {
+            parts: heapless::Vec::new(),
+        }
+    }
+
+    /// Returns the full message once all its parts were received. Messages which are not
+    /// concatenated are returned right away and repeated parts are ignored.
+    pub fn push<const M: usize>(
+        &mut self,
+        message: DeliverPdu,
+    ) -> Result<Option<ReassembledSms<M>>, AtError> {
+        let Some(concatenation) = message.concatenation else {
+            let text = message.text.as_str().try_into()?;
+            return Ok(Some(ReassembledSms {
+                sender: message.sender,
+                text,
+            }));
+        };
+        if concatenation.sequence == 0 || concatenation.sequence > concatenation.total {
+            return Err(AtError::AtParseError);
+        }
+
+        let sender = message.sender;
+        let same_message = |part: &StoredPart| {
+            part.sender == sender && part.concatenation.reference == concatenation.reference
+        };
+        let repeated = self.parts.iter().any(|part| {
+            same_message(part) && part.concatenation.sequence == concatenation.sequence
+        });
+        if !repeated {
+            self.parts
+                .push(StoredPart {
+                    sender: sender.clone(),
+                    concatenation,
+                    text: message.text,
+                })
+                .map_err(|_| AtError::CapacityError)?;
+        }
+
+        let received = self.parts.iter().filter(|part| same_message(part)).count();
+        if received < concatenation.total as usize {
+            return Ok(None);
+        }
+
+        let mut text = heapless::String::new();
+        let joined = (1..=concatenation.total).try_for_each(|sequence| {
+            let part = self
+                .parts
+                .iter()
+                .find(|part| same_message(part) && part.concatenation.sequence == sequence)
+                .ok_or(AtError::AtParseError)?;
+            text.push_str(&part.text)
+                .map_err(|_| AtError::CapacityError)
+        });
+        self.parts.retain(|part| !same_message(part));
+        joined?;
+
+        Ok(Some(ReassembledSms {
+            sender: sender.clone(),
+            text,
+        }))
+    }
+
+    /// Amount of parts waiting for the rest of their message
+    pub fn pending(&self) -> usize {
+        self.parts.len()
+    }
+}
+
+/// Writes the data as upper case hex, returns the amount of bytes written
+pub(crate) fn write_hex(data: &[u8], out: &mut [u8]) -> Result<usize, AtError> {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+    let out = out
+        .get_mut(..data.len() * 2)
+        .ok_or(AtError::CapacityError)?;
+    for (octet, hex) in data.iter().zip(out.chunks_exact_mut(2)) {
+        hex[0] = DIGITS[(octet >> 4) as usize];
+        hex[1] = DIGITS[(octet & 0x0F) as usize];
+    }
+    Ok(data.len() * 2)
+}
+
+pub(crate) fn decode_hex<const N: usize>(hex: &[u8]) -> Result<heapless::Vec<u8, N>, AtError> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(AtError::AtParseError);
+    }
+    let digit = |c: u8| match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(AtError::AtParseError),
+    };
+
+    let mut data = heapless::Vec::new();
+    for pair in hex.chunks_exact(2) {
+        data.push(digit(pair[0])? << 4 | digit(pair[1])?)
+            .map_err(|_| AtError::CapacityError)?;
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn to_hex(data: &[u8]) -> std::string::String {
+        let mut hex = [0; 2 * PDU_MAX_SIZE];
+        let len = write_hex(data, &mut hex).unwrap();
+        std::string::String::from_utf8(hex[..len].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_encode_submit_gsm7() {
+        let pdu = SubmitPdu::new("+34600111222", "hellohello").unwrap();
+        assert_eq!(
+            to_hex(pdu.as_bytes()),
+            "0001000B914306101122F200000AE8329BFD4697D9EC37"
+        );
+        assert_eq!(pdu.tpdu_len(), 22);
+    }
+
+    #[test]
+    fn test_encode_submit_ucs2() {
+        let pdu = SubmitPdu::new("600111222", "Привет").unwrap();
+        assert_eq!(
+            to_hex(pdu.as_bytes()),
+            "000100098106101122F200080C041F04400438043204350442"
+        );
+    }
+
+    #[test]
+    fn test_encode_submit_invalid_destination() {
+        assert!(matches!(
+            SubmitPdu::new("+34-600", "hello"),
+            Err(AtError::InvalidAddress)
+        ));
+    }
+
+    #[test]
+    fn test_decode_deliver() {
+        let pdu = decode_hex::<PDU_MAX_SIZE>(
+            b"07914306073011F0040B914306101122F2000042101021430080\
+            0AE8329BFD4697D9EC37",
+        )
+        .unwrap();
+        let deliver = DeliverPdu::decode(&pdu).unwrap();
+
+        assert_eq!(deliver.sender.as_str(), "+34600111222");
+        assert_eq!(
+            deliver.timestamp,
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(12, 34, 0)
+                .unwrap()
+        );
+        assert_eq!(deliver.time_zone_quarter_hours, 8);
+        assert_eq!(deliver.text.as_str(), "hellohello");
+    }
+
+    #[test]
+    fn test_decode_deliver_alphanumeric_sender() {
+        let pdu =
+            decode_hex::<PDU_MAX_SIZE>(b"000407D0C7F7FB0C00004210102143008805E8329BFD06").unwrap();
+        let deliver = DeliverPdu::decode(&pdu).unwrap();
+
+        assert_eq!(deliver.sender.as_str(), "Goog");
+        assert_eq!(deliver.time_zone_quarter_hours, -8);
+        assert_eq!(deliver.text.as_str(), "hello");
+    }
+
+    #[test]
+    fn test_decode_deliver_concatenated() {
+        let pdu = decode_hex::<PDU_MAX_SIZE>(
+            b"00440B914306101122F2000042101021430080\
+            0C0500032A0201D06536FB0D",
+        )
+        .unwrap();
+        let deliver = DeliverPdu::decode(&pdu).unwrap();
+
+        assert_eq!(deliver.text.as_str(), "hello");
+        assert_eq!(
+            deliver.concatenation,
+            Some(Concatenation {
+                reference: 0x2A,
+                total: 2,
+                sequence: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_split_concatenated() {
+        let text: std::string::String = (0..300).map(|i| (b'a' + (i % 26) as u8) as char).collect();
+        let split = ConcatenatedSubmit::new("+34600111222", &text, 0x2A).unwrap();
+        assert_eq!(split.parts(), 2);
+
+        let parts: std::vec::Vec<SubmitPdu> = split.map(|part| part.unwrap()).collect();
+        assert_eq!(parts.len(), 2);
+        for (i, part) in parts.iter().enumerate() {
+            let octets = part.as_bytes();
+            // UDHI is set
+            assert_eq!(octets[1], 0x41);
+            // Header after the 11 digit address, protocol identifier, coding and length
+            assert_eq!(
+                &octets[14..20],
+                &[0x05, 0x00, 0x03, 0x2A, 0x02, i as u8 + 1]
+            );
+        }
+        // User data lengths in septets: header (7) plus the text
+        assert_eq!(parts[0].as_bytes()[13], 7 + 153);
+        assert_eq!(parts[1].as_bytes()[13], 7 + 147);
+    }
+
+    #[test]
+    fn test_split_short_message_is_not_concatenated() {
+        let mut split = ConcatenatedSubmit::new("+34600111222", "hellohello", 1).unwrap();
+        assert_eq!(split.parts(), 1);
+        assert_eq!(
+            split.next().unwrap().unwrap(),
+            SubmitPdu::new("+34600111222", "hellohello").unwrap()
+        );
+        assert!(split.next().is_none());
+    }
+
+    fn part(text: &str, sequence: u8) -> DeliverPdu {
+        DeliverPdu {
+            sender: "+34600111222".try_into().unwrap(),
+            timestamp: NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(12, 34, 0)
+                .unwrap(),
+            time_zone_quarter_hours: 0,
+            text: text.try_into().unwrap(),
+            concatenation: Some(Concatenation {
+                reference: 0x2A,
+                total: 2,
+                sequence,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_reassemble_concatenated() {
+        let mut reassembler = SmsReassembler::<4>::new();
+
+        // Parts may arrive out of order
+        assert!(reassembler.push::<64>(part(" world", 2)).unwrap().is_none());
+        assert!(reassembler.push::<64>(part(" world", 2)).unwrap().is_none());
+        assert_eq!(reassembler.pending(), 1);
+
+        let message = reassembler.push::<64>(part("hello", 1)).unwrap().unwrap();
+        assert_eq!(message.sender.as_str(), "+34600111222");
+        assert_eq!(message.text.as_str(), "hello world");
+        assert_eq!(reassembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_reassemble_full() {
+        let mut reassembler = SmsReassembler::<1>::new();
+        assert!(reassembler.push::<64>(part("hello", 1)).unwrap().is_none());
+
+        let mut other = part("other", 1);
+        other.concatenation.as_mut().unwrap().reference = 7;
+        assert!(matches!(
+            reassembler.push::<64>(other),
+            Err(AtError::CapacityError)
+        ));
+    }
+}
@@ -1,13 +1,20 @@
+use crate::at_command::pdp_context::{PDPContextResponse, PDPType, QueryPdpContexts};
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
+use crate::urc::Urc;
 use crate::{
-    at_command::{verify_ok, AtRequest},
-    AtError,
+    at_command::{
+        parse_csv_line, strip_ok, trim_line_breaks, validate_string_parameter, verify_ok,
+        AtRequest, FinishExact, LenientRequest, StreamedRequest,
+    },
+    AtError, Modem, Timer, BUFFER_SIZE,
 };
+use embedded_io::{Read, Write};
 
 /// Domain for the socket connection
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Domain {
     IPv4 = 1,
     IPv6 = 2,
@@ -16,6 +23,7 @@ pub enum Domain {
 /// Indicates the type of connection for the socket
 #[repr(u8)]
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Type {
     TCP = 1,
     UPD = 2,
@@ -25,6 +33,7 @@ pub enum Type {
 /// Indicates the underlaying protocol using for the socket
 #[repr(u8)]
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Protocol {
     IP = 1,
     ICMP = 2,
@@ -32,6 +41,7 @@ pub enum Protocol {
 }
 
 /// AT command to create a socket
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CreateSocket {
     /// Type of IP connection that will be used
     pub domain: Domain,
@@ -43,19 +53,80 @@ pub struct CreateSocket {
     pub cid: Option<i32>,
 }
 
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SocketCreated {
     pub socket_id: u8,
 }
 
 impl CreateSocket {
+    /// Creates the socket on a context reported by the modem with [PDPContext], so its cid is
+    /// always one the modem has defined
+    pub fn on_context(
+        domain: Domain,
+        connection_type: Type,
+        protocol: Protocol,
+        context: &PDPContextResponse,
+    ) -> Self {
+        Self {
+            domain,
+            connection_type,
+            protocol,
+            cid: Some(context.context),
+        }
+    }
+
+    /// Creates the socket on context `cid` once checked it is one of the contexts defined in
+    /// the modem, listed with [QueryPdpContexts], and its type carries the socket domain.
+    /// [AtError::InvalidParameter] is returned if the context is not defined, has another IP
+    /// version or is a Non-IP context.
+    pub fn on_defined_context<T: Write, U: Read>(
+        modem: &mut Modem<'_, T, U>,
+        domain: Domain,
+        connection_type: Type,
+        protocol: Protocol,
+        cid: i32,
+    ) -> Result<Self, AtError> {
+        let contexts = modem.send_and_wait_response(&QueryPdpContexts)?;
+        let pdp_type = contexts
+            .iter()
+            .find(|context| context.cid == cid)
+            .and_then(|context| context.pdp_type);
+        let supported = matches!(
+            (domain, pdp_type),
+            (Domain::IPv4, Some(PDPType::IP | PDPType::IPV4V6))
+                | (Domain::IPv6, Some(PDPType::IPV6 | PDPType::IPV4V6))
+        );
+        if !supported {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self {
+            domain,
+            connection_type,
+            protocol,
+            cid: Some(cid),
+        })
+    }
+
+    /// Besides the socket id the modem can reply `+CSOC:` without an id when no socket is
+    /// free, or a negative error code: `-1` when no socket is free and `-2` when a parameter
+    /// is not valid.
     fn get_socket_id(data: &[u8]) -> Result<u8, AtError> {
-        let (socket_id,) = at_commands::parser::CommandParser::parse(data)
-            .expect_identifier(b"\r\n+CSOC: ")
+        let payload = strip_ok(data)?;
+        if payload.trim_ascii_end() == b"+CSOC:" {
+            return Err(AtError::NoFreeSocket);
+        }
+
+        let (socket_id,) = at_commands::parser::CommandParser::parse(payload)
+            .expect_identifier(b"+CSOC: ")
             .expect_int_parameter()
-            .expect_identifier(b"\r\n\r\nOK\r")
             .finish()?;
 
-        Ok(socket_id as u8)
+        match socket_id {
+            -1 => Err(AtError::NoFreeSocket),
+            -2 => Err(AtError::InvalidParameter),
+            _ => u8::try_from(socket_id).map_err(|_| AtError::AtParseError),
+        }
     }
 }
 
@@ -90,7 +161,23 @@ impl AtRequest for CreateSocket {
     }
 }
 
+impl LenientRequest for CreateSocket {
+    const PREFIX: &'static str = "+CSOC";
+
+    fn parse_exact(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        // Only the first field is read, a layout with more fields is not the one expected
+        if strip_ok(data)
+            .is_ok_and(|payload| parse_csv_line(payload).is_ok_and(|fields| fields.count() > 1))
+        {
+            return Err(AtError::AtParseError);
+        }
+
+        self.parse_response_struct(data)
+    }
+}
+
 /// Command to connect the socket to a remote address
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ConnectSocketToRemote<'a> {
     /// Socket ID obtained by using [CreateSocket]
     pub socket_id: u8,
@@ -102,6 +189,52 @@ pub struct ConnectSocketToRemote<'a> {
     pub connection_type: Type,
 }
 
+impl<'a> ConnectSocketToRemote<'a> {
+    /// Creates the request validating the address against the socket domain. IPv6 literals can
+    /// be given in the `[2001:db8::1]` form, the brackets are removed as the modem expects the
+    /// bare address. Addresses with a zone id are rejected with [AtError::InvalidAddress], the
+    /// modem cannot select an interface. IPv4 addresses are sent as given, as long as they can
+    /// be sent as a string parameter.
+    pub fn new(
+        socket_id: u8,
+        domain: Domain,
+        remote_address: &'a str,
+        port: u16,
+        connection_type: Type,
+    ) -> Result<Self, AtError> {
+        let remote_address = match domain {
+            Domain::IPv4 => {
+                validate_string_parameter(remote_address).map_err(|_| AtError::InvalidAddress)?;
+                remote_address
+            }
+            Domain::IPv6 => normalize_ipv6_address(remote_address)?,
+        };
+
+        Ok(Self {
+            socket_id,
+            port,
+            remote_address,
+            connection_type,
+        })
+    }
+}
+
+fn normalize_ipv6_address(address: &str) -> Result<&str, AtError> {
+    let address = address
+        .strip_prefix('[')
+        .and_then(|address| address.strip_suffix(']'))
+        .unwrap_or(address);
+    // The modem has no way to select the interface of a zone id
+    if address.contains('%') {
+        return Err(AtError::InvalidAddress);
+    }
+
+    address
+        .parse::<core::net::Ipv6Addr>()
+        .map_err(|_| AtError::InvalidAddress)?;
+    Ok(address)
+}
+
 impl AtRequest for ConnectSocketToRemote<'_> {
     type Response = ();
 
@@ -114,7 +247,7 @@ impl AtRequest for ConnectSocketToRemote<'_> {
             .with_string_parameter(self.remote_address)
             .with_int_parameter(self.connection_type as u8);
 
-        builder.finish()
+        builder.finish_exact()
     }
 
     #[allow(deprecated)]
@@ -130,6 +263,7 @@ impl AtRequest for ConnectSocketToRemote<'_> {
 }
 
 /// Struct used to send data through the socket
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SendSocketMessage<'a> {
     /// Socket ID obtained by using [CreateSocket]
     pub socket_id: u8,
@@ -149,7 +283,7 @@ impl AtRequest for SendSocketMessage<'_> {
             .with_int_parameter(self.data_len)
             .with_raw_parameter(self.data);
 
-        builder.finish()
+        builder.finish_exact()
     }
 
     #[allow(deprecated)]
@@ -166,130 +300,2251 @@ impl AtRequest for SendSocketMessage<'_> {
     }
 }
 
-/// Closes the opened TCP socket
-pub struct CloseSocket {
+/// Sends the data without copying it into the command buffer, see
+/// [Modem::send_streamed](crate::Modem::send_streamed)
+impl StreamedRequest for SendSocketMessage<'_> {
+    type Response = ();
+
+    fn get_header<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOSEND")
+            .with_int_parameter(self.socket_id)
+            .with_int_parameter(self.data_len)
+            .finish_with(b",")
+    }
+
+    fn data(&self) -> &[u8] {
+        self.data
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Format of the data exchanged on the sockets
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DataFormat {
+    /// Every byte is sent as two hex digits
+    #[default]
+    Hex = 0,
+    /// The bytes are sent as they are, they have to be valid text
+    Text = 1,
+}
+
+/// Selects the format of the data received on every socket with `AT+CSORCVFLAG`. Use
+/// [Modem::set_socket_data_format] instead of sending it directly, so the data sent through
+/// [TcpConnection] follows the same format.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetSocketDataFormat {
+    pub format: DataFormat,
+}
+
+impl AtRequest for SetSocketDataFormat {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSORCVFLAG")
+            .with_int_parameter(self.format as u8)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Sends text through the socket regardless of the format set with
+/// [Modem::set_socket_data_format], the modem is told the data is text by a length of `0`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SendSocketText<'a> {
     /// Socket ID obtained by using [CreateSocket]
-    pub socket_id: u8,
+    socket_id: u8,
+    text: &'a str,
 }
 
-impl AtRequest for CloseSocket {
+impl<'a> SendSocketText<'a> {
+    /// The text is sent as a quoted parameter, so it cannot contain quotes nor control
+    /// characters
+    pub fn new(socket_id: u8, text: &'a str) -> Result<Self, AtError> {
+        validate_string_parameter(text)?;
+
+        Ok(Self { socket_id, text })
+    }
+}
+
+impl AtRequest for SendSocketText<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOSEND")
+            .with_int_parameter(self.socket_id)
+            .with_int_parameter(0)
+            .with_string_parameter(self.text)
+            .finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Most reconnection attempts accepted by [SetSocketAutoReconnect]
+pub const MAX_RECONNECT_RETRIES: u8 = 10;
+/// Longest time between reconnection attempts accepted by [SetSocketAutoReconnect]
+pub const MAX_RECONNECT_INTERVAL_SECS: u16 = 300;
+
+/// Makes the modem reconnect a TCP socket dropped by the network with `AT+CSORECONN`, the
+/// attempts are reported with [SocketReconnect]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetSocketAutoReconnect {
+    pub(crate) socket_id: u8,
+    /// `None` disables the reconnection, otherwise the amount of attempts and the time
+    /// between them
+    pub(crate) retries: Option<(u8, u16)>,
+}
+
+impl SetSocketAutoReconnect {
+    pub fn enable(socket_id: u8, retries: u8, interval_secs: u16) -> Result<Self, AtError> {
+        if !(1..=MAX_RECONNECT_RETRIES).contains(&retries)
+            || !(1..=MAX_RECONNECT_INTERVAL_SECS).contains(&interval_secs)
+        {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self {
+            socket_id,
+            retries: Some((retries, interval_secs)),
+        })
+    }
+
+    pub fn disable(socket_id: u8) -> Self {
+        Self {
+            socket_id,
+            retries: None,
+        }
+    }
+}
+
+impl AtRequest for SetSocketAutoReconnect {
     type Response = ();
 
     fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
         let builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
-            .named("+CSOCL")
+            .named("+CSORECONN")
             .with_int_parameter(self.socket_id);
 
-        builder.finish()
+        match self.retries {
+            Some((retries, interval_secs)) => builder
+                .with_int_parameter(1)
+                .with_int_parameter(retries)
+                .with_int_parameter(interval_secs as i32)
+                .finish(),
+            None => builder.with_int_parameter(0).finish(),
+        }
     }
 
-    fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
-        Ok(())
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
     }
 }
 
-#[cfg(test)]
-mod test {
-    #![allow(deprecated)]
-    use crate::at_command::{
-        socket::{CloseSocket, ConnectSocketToRemote, CreateSocket, Domain, Protocol, Type},
-        AtRequest, AtResponse,
-    };
+/// Unsolicited message sent after an automatic reconnection attempt, see
+/// [SetSocketAutoReconnect]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketReconnect {
+    pub socket_id: u8,
+    /// Whether the socket is connected again, if not the modem gave up
+    pub reconnected: bool,
+}
 
-    #[test]
-    fn test_create_socket_command() {
-        let mut buffer = [0; 512];
+impl SocketReconnect {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (socket_id, state) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CSORECONN: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .finish()?;
 
-        let create_socket = CreateSocket {
-            domain: Domain::IPv4,
-            connection_type: Type::TCP,
-            protocol: Protocol::IP,
-            cid: Some(3),
+        Ok(SocketReconnect {
+            socket_id: u8::try_from(socket_id).map_err(|_| AtError::AtParseError)?,
+            reconnected: match state {
+                0 => false,
+                1 => true,
+                _ => return Err(AtError::AtParseError),
+            },
+        })
+    }
+}
+
+/// Destination kind of a [SendSocketDatagram]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatagramTarget {
+    Unicast,
+    /// Limited broadcast, only available on IPv4
+    Broadcast,
+    Multicast,
+}
+
+/// Sends a datagram through an UDP socket to the given address with `AT+CSOSENDTO`, without
+/// connecting the socket first
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SendSocketDatagram<'a> {
+    /// Socket ID obtained by using [CreateSocket]
+    pub socket_id: u8,
+    pub remote_address: &'a str,
+    pub port: u16,
+    pub data: &'a [u8],
+}
+
+impl<'a> SendSocketDatagram<'a> {
+    /// Creates the request validating that the socket is an UDP one and that the address
+    /// matches the target, e.g. only multicast addresses can be used with
+    /// [DatagramTarget::Multicast]. Broadcast and multicast are rejected on TCP and raw
+    /// sockets, which only reach a single peer.
+    pub fn new(
+        socket_id: u8,
+        domain: Domain,
+        connection_type: Type,
+        remote_address: &'a str,
+        port: u16,
+        data: &'a [u8],
+        target: DatagramTarget,
+    ) -> Result<Self, AtError> {
+        if !matches!(connection_type, Type::UPD) || port == 0 {
+            return Err(AtError::InvalidParameter);
+        }
+
+        let remote_address = match domain {
+            Domain::IPv4 => {
+                let address = remote_address
+                    .parse::<core::net::Ipv4Addr>()
+                    .map_err(|_| AtError::InvalidAddress)?;
+                let matches_target = match target {
+                    DatagramTarget::Unicast => !address.is_broadcast() && !address.is_multicast(),
+                    DatagramTarget::Broadcast => address.is_broadcast(),
+                    DatagramTarget::Multicast => address.is_multicast(),
+                };
+                if !matches_target {
+                    return Err(AtError::InvalidAddress);
+                }
+                remote_address
+            }
+            Domain::IPv6 => {
+                let remote_address = normalize_ipv6_address(remote_address)?;
+                let is_multicast = remote_address
+                    .parse::<core::net::Ipv6Addr>()
+                    .map_err(|_| AtError::InvalidAddress)?
+                    .is_multicast();
+                let matches_target = match target {
+                    DatagramTarget::Unicast => !is_multicast,
+                    DatagramTarget::Broadcast => false,
+                    DatagramTarget::Multicast => is_multicast,
+                };
+                if !matches_target {
+                    return Err(AtError::InvalidAddress);
+                }
+                remote_address
+            }
         };
 
-        let result = create_socket.get_command(&mut buffer).unwrap();
+        Ok(Self {
+            socket_id,
+            remote_address,
+            port,
+            data,
+        })
+    }
+}
 
-        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOC=1,1,1,3\r\n");
+impl AtRequest for SendSocketDatagram<'_> {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOSENDTO")
+            .with_int_parameter(self.socket_id)
+            .with_string_parameter(self.remote_address)
+            .with_int_parameter(self.port as i32)
+            .with_int_parameter(self.data.len() as i32)
+            .with_raw_parameter(self.data)
+            .finish_exact()
     }
 
-    #[test]
-    fn test_create_socket_command_without_cid() {
-        let mut buffer = [0; 512];
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
 
-        let create_socket = CreateSocket {
-            domain: Domain::IPv6,
-            connection_type: Type::RAW,
-            protocol: Protocol::ICMP,
-            cid: None,
-        };
+/// Longest linger time accepted by the modem
+pub const MAX_LINGER_SECS: u16 = 3600;
 
-        let result = create_socket.get_command(&mut buffer).unwrap();
+/// Options of a TCP socket, the ones left as `None` are not changed
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Disables the Nagle algorithm, sending small segments right away
+    pub no_delay: Option<bool>,
+    /// Time a close waits for the pending data to be sent, 0 disables lingering
+    pub linger_secs: Option<u16>,
+}
 
-        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOC=2,3,2\r\n");
+/// Sets options of a TCP socket with `AT+CSOSETOPT`, given as name and value pairs
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetSocketOptions {
+    pub(crate) socket_id: u8,
+    pub(crate) options: SocketOptions,
+}
+
+impl SetSocketOptions {
+    pub fn new(socket_id: u8, options: SocketOptions) -> Result<Self, AtError> {
+        if options == SocketOptions::default() {
+            return Err(AtError::InvalidParameter);
+        }
+        if options
+            .linger_secs
+            .is_some_and(|linger_secs| linger_secs > MAX_LINGER_SECS)
+        {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { socket_id, options })
     }
+}
 
-    #[test]
-    fn test_parse_create_socket_response() {
-        let create_socket = CreateSocket {
-            domain: Domain::IPv4,
-            connection_type: Type::TCP,
-            protocol: Protocol::IP,
-            cid: None,
-        };
+impl AtRequest for SetSocketOptions {
+    type Response = ();
 
-        // Response example: +CSOC 5\r\n\r\nOK\r\n
-        let response = b"\r\n+CSOC: 5\r\n\r\nOK\r";
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        let mut builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOSETOPT")
+            .with_int_parameter(self.socket_id);
 
-        let parsed = create_socket.parse_response(response).unwrap();
+        if let Some(no_delay) = self.options.no_delay {
+            builder = builder
+                .with_string_parameter("NODELAY")
+                .with_int_parameter(no_delay as i32);
+        }
+        if let Some(linger_secs) = self.options.linger_secs {
+            builder = builder
+                .with_string_parameter("LINGER")
+                .with_int_parameter(linger_secs as i32);
+        }
 
-        match parsed {
-            AtResponse::SocketCreated(id) => assert_eq!(id, 5),
-            _ => panic!("Expected AtResponse::SocketCreated"),
+        builder.finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// How a TCP socket is closed by [CloseSocket]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseMode {
+    /// The connection is shut down with a FIN, waiting for the remote
+    Graceful = 0,
+    /// The connection is reset with a RST, which is faster but may lose data in flight
+    Abortive = 1,
+}
+
+impl TryFrom<u8> for CloseMode {
+    type Error = AtError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CloseMode::Graceful),
+            1 => Ok(CloseMode::Abortive),
+            _ => Err(AtError::InvalidParameter),
         }
     }
+}
 
-    #[test]
-    fn test_connect_remote_socket_command() {
-        let mut buffer = [0; 512];
+/// Closes the opened TCP socket
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CloseSocket {
+    /// Socket ID obtained by using [CreateSocket]
+    pub socket_id: u8,
+    /// `None` leaves the choice to the firmware. When set it is always sent as the second
+    /// `+CSOCL` parameter, so it must only be set on firmwares that accept it
+    pub close_mode: Option<CloseMode>,
+}
 
-        let at_connect_request = ConnectSocketToRemote {
-            connection_type: super::Type::TCP,
-            port: 1111,
-            socket_id: 1,
-            remote_address: "127.0.0.1",
-        };
+impl AtRequest for CloseSocket {
+    type Response = ();
 
-        let result = at_connect_request.get_command(&mut buffer).unwrap();
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        let builder = at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOCL")
+            .with_int_parameter(self.socket_id);
 
-        assert_eq!(
-            core::str::from_utf8(result).unwrap(),
-            "AT+CSOCON=1,1111,\"127.0.0.1\",1\r\n"
-        );
+        match self.close_mode {
+            Some(close_mode) => builder.with_int_parameter(close_mode as u8).finish(),
+            None => builder.finish(),
+        }
     }
 
-    #[test]
-    #[should_panic]
-    fn test_connect_remote_socket_command_with_invalid_port() {
-        let mut buffer = [0; 512];
+    fn parse_response_struct(&self, _data: &[u8]) -> Result<Self::Response, AtError> {
+        Ok(())
+    }
+}
 
-        let at_connect_request = ConnectSocketToRemote {
-            connection_type: super::Type::TCP,
-            port: 0,
-            socket_id: 1,
-            remote_address: "127.0.0.1",
-        };
+/// Queries how much buffer the modem has left for a socket, used to throttle sends before
+/// [SendSocketMessage] fails under load
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetSocketBufferMemory {
+    /// Socket ID obtained by using [CreateSocket]
+    pub socket_id: u8,
+}
 
-        at_connect_request.get_command(&mut buffer).unwrap();
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SocketBufferMemory {
+    pub socket_id: u8,
+    /// Bytes that can still be queued for sending
+    pub send_buffer_free: u16,
+    /// Bytes received and not yet read
+    pub receive_buffer_used: u16,
+}
+
+impl GetSocketBufferMemory {
+    fn parse_buffer_memory(data: &[u8]) -> Result<SocketBufferMemory, AtError> {
+        let (socket_id, send_buffer_free, receive_buffer_used) =
+            at_commands::parser::CommandParser::parse(strip_ok(data)?)
+                .expect_identifier(b"+CSOBUF: ")
+                .expect_int_parameter()
+                .expect_int_parameter()
+                .expect_int_parameter()
+                .finish()?;
+
+        Ok(SocketBufferMemory {
+            socket_id: socket_id as u8,
+            send_buffer_free: send_buffer_free as u16,
+            receive_buffer_used: receive_buffer_used as u16,
+        })
     }
+}
 
-    #[test]
-    fn test_close_socket() {
-        let mut buffer = [0; 512];
+impl AtRequest for GetSocketBufferMemory {
+    /// `None` if the firmware answers with a plain OK, i.e. it does not report buffer usage
+    type Response = Option<SocketBufferMemory>;
 
-        let at_connect_request = CloseSocket { socket_id: 0 };
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOBUF")
+            .with_int_parameter(self.socket_id)
+            .finish()
+    }
 
-        let result = at_connect_request.get_command(&mut buffer).unwrap();
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        if verify_ok(data).is_ok() {
+            return Ok(None);
+        }
 
-        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOCL=0\r\n");
+        Self::parse_buffer_memory(data).map(Some)
+    }
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketState {
+    Unused,
+    Created,
+    Connecting,
+    Connected,
+    Unknown,
+}
+
+impl From<i32> for SocketState {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => SocketState::Unused,
+            1 => SocketState::Created,
+            2 => SocketState::Connecting,
+            3 => SocketState::Connected,
+            _ => SocketState::Unknown,
+        }
+    }
+}
+
+/// Queries the state of a socket
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetSocketStatus {
+    /// Socket ID obtained by using [CreateSocket]
+    pub socket_id: u8,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketStatus {
+    pub socket_id: u8,
+    pub state: SocketState,
+}
+
+impl GetSocketStatus {
+    fn parse_status(data: &[u8]) -> Result<SocketStatus, AtError> {
+        Self::parse_status_line(strip_ok(data)?)
+    }
+
+    fn parse_status_line(line: &[u8]) -> Result<SocketStatus, AtError> {
+        let (socket_id, state) = at_commands::parser::CommandParser::parse(line)
+            .expect_identifier(b"+CSOSTATUS: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        Ok(SocketStatus {
+            socket_id: socket_id as u8,
+            state: state.into(),
+        })
+    }
+}
+
+impl AtRequest for GetSocketStatus {
+    type Response = SocketStatus;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOSTATUS")
+            .with_int_parameter(self.socket_id)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::parse_status(data)
+    }
+}
+
+/// Amount of sockets the SIM7020 supports at the same time
+pub const MAX_SOCKETS: usize = 5;
+
+pub type OpenSockets = heapless::Vec<SocketStatus, MAX_SOCKETS>;
+
+/// Lists the sockets in use with `AT+CSOSTATUS?`, e.g. to reconcile a [SocketPool] with the
+/// modem after the host restarted. Unused sockets are left out.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ListSockets;
+
+impl AtRequest for ListSockets {
+    type Response = OpenSockets;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_query(buffer, true)
+            .named("+CSOSTATUS")
+            .finish()
+    }
+
+    /// Parses a `+CSOSTATUS: <socket_id>,<state>` line per socket
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let mut sockets = OpenSockets::new();
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = trim_line_breaks(line);
+            if line.is_empty() {
+                continue;
+            }
+
+            let status = GetSocketStatus::parse_status_line(line)?;
+            if status.state != SocketState::Unused {
+                sockets.push(status).map_err(|_| AtError::CapacityError)?;
+            }
+        }
+
+        Ok(sockets)
+    }
+}
+
+/// Send request built by [TcpConnection] for the data format in use
+enum SocketSend<'a> {
+    Hex(SendSocketMessage<'a>),
+    Text(SendSocketText<'a>),
+}
+
+impl SocketSend<'_> {
+    /// Amount of bytes acknowledged by [SocketSendAcknowledged] once all of them were sent
+    fn data_len(&self) -> u16 {
+        match self {
+            SocketSend::Hex(send) => send.data_len,
+            // Checked against u16::MAX when built
+            SocketSend::Text(send) => send.text.len() as u16,
+        }
+    }
+}
+
+impl AtRequest for SocketSend<'_> {
+    /// Acknowledgement received before the OK, if any
+    type Response = Option<SocketSendAcknowledged>;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        match self {
+            SocketSend::Hex(send) => send.get_command(buffer),
+            SocketSend::Text(send) => send.get_command(buffer),
+        }
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        // The acknowledgement shares the name of the command, so it is left in the response
+        let mut acknowledged = None;
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = trim_line_breaks(line);
+            if !line.is_empty() {
+                acknowledged = Some(SocketSendAcknowledged::parse(line)?);
+            }
+        }
+
+        Ok(acknowledged)
+    }
+}
+
+/// For how long the state queried by [TcpConnection::is_connected] is reused
+const SOCKET_STATE_CACHE_MS: u64 = 1000;
+
+/// Handle to a connected TCP socket
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TcpConnection {
+    socket_id: u8,
+    // connected and the time it was queried
+    last_state: Option<(bool, u64)>,
+}
+
+impl TcpConnection {
+    /// Creates a TCP socket and connects it to the remote
+    pub fn connect<T: Write, U: Read>(
+        modem: &mut Modem<'_, T, U>,
+        domain: Domain,
+        remote_address: &str,
+        port: u16,
+        cid: Option<i32>,
+    ) -> Result<Self, AtError> {
+        let socket_id = modem
+            .send_and_wait_response(&CreateSocket {
+                domain,
+                connection_type: Type::TCP,
+                protocol: Protocol::IP,
+                cid,
+            })?
+            .socket_id;
+
+        let connected =
+            ConnectSocketToRemote::new(socket_id, domain, remote_address, port, Type::TCP)
+                .and_then(|request| modem.send_and_wait_response(&request));
+        if let Err(e) = connected {
+            // Do not leak the socket, the connection error is the relevant one
+            let _ = modem.send_and_wait_response(&CloseSocket {
+                socket_id,
+                close_mode: None,
+            });
+            return Err(e);
+        }
+
+        Ok(Self {
+            socket_id,
+            last_state: None,
+        })
+    }
+
+    pub fn socket_id(&self) -> u8 {
+        self.socket_id
+    }
+
+    /// Sends the data in the format set with [Modem::set_socket_data_format]: hex encoded as
+    /// expected by [SendSocketMessage], or text as sent by [SendSocketText], in which case it
+    /// has to be valid UTF-8
+    pub fn send<T: Write, U: Read>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        data: &[u8],
+    ) -> Result<(), AtError> {
+        let send = self.build_send(modem.socket_data_format, data)?;
+        modem.send_and_wait_response(&send).map(|_| ())
+    }
+
+    /// Sends the data as [TcpConnection::send] does and waits up to `timeout_ms` until the
+    /// firmware reports with [SocketSendAcknowledged] that all of it was transmitted, as the OK
+    /// of the send only means the data was queued. Only for firmwares which report it.
+    pub fn send_confirmed<T: Write, U: Read, C: Timer>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        data: &[u8],
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<(), AtError> {
+        let start = timer.now_ms();
+        let send = self.build_send(modem.socket_data_format, data)?;
+        let data_len = send.data_len();
+        let socket_id = self.socket_id;
+        let acknowledged = modem.send_and_wait_response(&send)?;
+        if acknowledged.is_some_and(|ack| ack.socket_id == socket_id && ack.is_complete(data_len)) {
+            return Ok(());
+        }
+
+        loop {
+            let remaining = timeout_ms.saturating_sub(timer.now_ms().saturating_sub(start));
+            if let Urc::SocketSendAcknowledged(ack) = modem.wait_for_urc_matching(
+                |urc| matches!(urc, Urc::SocketSendAcknowledged(ack) if ack.socket_id == socket_id),
+                remaining,
+                timer,
+            )? {
+                if ack.is_complete(data_len) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Builds the send request for the data format in use
+    fn build_send<'b>(
+        &self,
+        format: DataFormat,
+        data: &'b [u8],
+    ) -> Result<SocketSend<'b>, AtError> {
+        let data_len = u16::try_from(data.len()).map_err(|_| AtError::InvalidParameter)?;
+        Ok(match format {
+            DataFormat::Hex => SocketSend::Hex(SendSocketMessage {
+                socket_id: self.socket_id,
+                data_len,
+                data,
+            }),
+            DataFormat::Text => SocketSend::Text(SendSocketText::new(
+                self.socket_id,
+                core::str::from_utf8(data).map_err(|_| AtError::InvalidParameter)?,
+            )?),
+        })
+    }
+
+    /// Sends the payload as [TcpConnection::send] does and waits up to `timeout_ms` for the
+    /// reply, which is copied into `response_buf`, for simple request/response protocols. The
+    /// reply may arrive before or after the OK of the send. Only the first chunk of data
+    /// received is returned, data received on other sockets is kept for
+    /// [Modem::pop_pending_urc].
+    pub fn request<'b, T: Write, U: Read, C: Timer>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        payload: &[u8],
+        response_buf: &'b mut [u8],
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<&'b [u8], AtError> {
+        let start = timer.now_ms();
+        let send = self.build_send(modem.socket_data_format, payload)?;
+        let socket_id = self.socket_id;
+        let mut reply = None;
+        modem.send_and_intercept_urcs(&send, |urc| match urc {
+            Urc::SocketData(data) if data.socket_id == socket_id && reply.is_none() => {
+                reply = Some(data);
+                None
+            }
+            urc => Some(urc),
+        })?;
+
+        let reply = match reply {
+            Some(reply) => reply,
+            None => {
+                let remaining = timeout_ms.saturating_sub(timer.now_ms().saturating_sub(start));
+                self.wait_for_data(modem, remaining, timer)?
+            }
+        };
+
+        let response_buf = response_buf
+            .get_mut(..reply.data.len())
+            .ok_or(AtError::CapacityError)?;
+        response_buf.copy_from_slice(&reply.data);
+        Ok(response_buf)
+    }
+
+    /// Waits for data on this socket, keeping the URCs received meanwhile
+    fn wait_for_data<T: Write, U: Read, C: Timer>(
+        &self,
+        modem: &mut Modem<'_, T, U>,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<SocketDataReceived, AtError> {
+        let socket_id = self.socket_id;
+        match modem.wait_for_urc_matching(
+            |urc| matches!(urc, Urc::SocketData(data) if data.socket_id == socket_id),
+            timeout_ms,
+            timer,
+        )? {
+            Urc::SocketData(data) => Ok(data),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Whether the socket is still connected. The state is queried at most once every
+    /// second, in between the last known state is returned.
+    pub fn is_connected<T: Write, U: Read, C: Timer>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        timer: &C,
+    ) -> Result<bool, AtError> {
+        let now = timer.now_ms();
+        if let Some((connected, queried_at)) = self.last_state {
+            if now.saturating_sub(queried_at) < SOCKET_STATE_CACHE_MS {
+                return Ok(connected);
+            }
+        }
+
+        let status = modem.send_and_wait_response(&GetSocketStatus {
+            socket_id: self.socket_id,
+        })?;
+        let connected = status.state == SocketState::Connected;
+        self.last_state = Some((connected, now));
+
+        Ok(connected)
+    }
+
+    pub fn close<T: Write, U: Read>(self, modem: &mut Modem<'_, T, U>) -> Result<(), AtError> {
+        modem.send_and_wait_response(&CloseSocket {
+            socket_id: self.socket_id,
+            close_mode: None,
+        })
+    }
+}
+
+/// Keeps track of the sockets opened through it, limiting them to `N` at the same time. The
+/// SIM7020 supports up to 5 sockets.
+pub struct SocketPool<const N: usize> {
+    sockets: heapless::Vec<u8, N>,
+}
+
+impl<const N: usize> Default for SocketPool<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SocketPool<N> {
+    pub const fn new() -> Self {
+        Self {
+            sockets: heapless::Vec::new(),
+        }
+    }
+
+    /// Creates a socket and tracks the ID given by the modem. Fails without sending anything
+    /// if the pool is full, and with [AtError::DuplicateSocket] if the modem gives an ID the
+    /// pool already tracks, as the pool no longer matches the sockets of the modem.
+    pub fn create<T: Write, U: Read>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        request: &CreateSocket,
+    ) -> Result<u8, AtError> {
+        if self.sockets.is_full() {
+            return Err(AtError::SocketPoolFull);
+        }
+
+        let socket_id = modem.send_and_wait_response(request)?.socket_id;
+        if self.is_in_use(socket_id) {
+            return Err(AtError::DuplicateSocket(socket_id));
+        }
+        // There is room as the pool was not full
+        let _ = self.sockets.push(socket_id);
+
+        Ok(socket_id)
+    }
+
+    /// Closes a socket created through this pool and frees its slot
+    pub fn close<T: Write, U: Read>(
+        &mut self,
+        modem: &mut Modem<'_, T, U>,
+        socket_id: u8,
+    ) -> Result<(), AtError> {
+        if !self.is_in_use(socket_id) {
+            return Err(AtError::UnknownSocket);
+        }
+
+        modem.send_and_wait_response(&CloseSocket {
+            socket_id,
+            close_mode: None,
+        })?;
+        self.sockets.retain(|id| *id != socket_id);
+
+        Ok(())
+    }
+
+    pub fn is_in_use(&self, socket_id: u8) -> bool {
+        self.sockets.contains(&socket_id)
+    }
+
+    /// Amount of sockets currently open
+    pub fn len(&self) -> usize {
+        self.sockets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sockets.is_empty()
+    }
+}
+
+/// Longest data field of a `+CSONMI` URC, a URC is at most as long as a line read from the
+/// modem
+pub const SOCKET_DATA_MAX_SIZE: usize = BUFFER_SIZE;
+
+/// Unsolicited message sent by the modem when data arrives on a socket
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketDataReceived {
+    /// Socket ID which received the data
+    pub socket_id: u8,
+    /// Length of the received data as reported by the modem
+    pub data_len: u16,
+    /// Data as sent by the modem, hex encoded unless configured otherwise with
+    /// [Modem::set_socket_data_format]
+    pub data: heapless::Vec<u8, SOCKET_DATA_MAX_SIZE>,
+    /// Bytes still waiting in the modem after this data, only reported once enabled with
+    /// [SetReceiveLengthReporting]
+    pub pending_len: Option<u16>,
+}
+
+impl SocketDataReceived {
+    /// Parses `+CSONMI: <socket_id>[,<pending_len>],<data_len>,<data>`
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        if !data.starts_with(b"+CSONMI: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        let mut fields: heapless::Vec<&[u8], 4> = heapless::Vec::new();
+        for field in parse_csv_line(data)? {
+            fields.push(field).map_err(|_| AtError::AtParseError)?;
+        }
+        let parse_int = |field: &[u8]| -> Result<u16, AtError> {
+            core::str::from_utf8(field)
+                .ok()
+                .and_then(|field| field.parse().ok())
+                .ok_or(AtError::AtParseError)
+        };
+
+        let (socket_id, pending_len, data_len, payload) = match fields.as_slice() {
+            [socket_id, data_len, payload] => (socket_id, None, data_len, payload),
+            [socket_id, pending_len, data_len, payload] => {
+                (socket_id, Some(parse_int(pending_len)?), data_len, payload)
+            }
+            _ => return Err(AtError::AtParseError),
+        };
+
+        Ok(SocketDataReceived {
+            socket_id: u8::try_from(parse_int(socket_id)?).map_err(|_| AtError::AtParseError)?,
+            data_len: parse_int(data_len)?,
+            data: heapless::Vec::from_slice(payload)?,
+            pending_len,
+        })
+    }
+}
+
+/// Adds the bytes still waiting in the modem to [SocketDataReceived] with `AT+CSONMIFLAG`, so
+/// the host can size its receive buffer
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetReceiveLengthReporting {
+    pub enable: bool,
+}
+
+impl AtRequest for SetReceiveLengthReporting {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSONMIFLAG")
+            .with_int_parameter(self.enable as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+/// Reads a datagram waiting on an UDP socket with `AT+CSORCV`, for sockets polled instead of
+/// reporting the data with [SocketDataReceived]. `Ok(None)` means there is no datagram right
+/// now, a socket error is returned as [AtError::Socket].
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ReceiveSocketData {
+    /// Socket ID obtained by using [CreateSocket]
+    pub socket_id: u8,
+    /// Largest amount of bytes to read
+    pub max_len: u16,
+}
+
+impl ReceiveSocketData {
+    fn get_data(data: &[u8]) -> Result<Option<SocketDataReceived>, AtError> {
+        let payload = strip_ok(data)?;
+        if payload.is_empty() {
+            return Ok(None);
+        }
+        if payload.starts_with(b"+CSOERR: ") {
+            let report = SocketErrorReport::parse(payload)?;
+            return Err(AtError::Socket(report.error()));
+        }
+
+        // +CSORCV: <socket_id>,<data_len>[,<data>]
+        let payload = core::str::from_utf8(payload).map_err(|_| AtError::AtParseError)?;
+        let mut fields = payload
+            .strip_prefix("+CSORCV: ")
+            .ok_or(AtError::AtParseError)?
+            .splitn(3, ',');
+        let mut next_int = || -> Result<u16, AtError> {
+            fields
+                .next()
+                .and_then(|field| field.parse().ok())
+                .ok_or(AtError::AtParseError)
+        };
+        let socket_id = u8::try_from(next_int()?).map_err(|_| AtError::AtParseError)?;
+        let data_len = next_int()?;
+        if data_len == 0 {
+            return Ok(None);
+        }
+        let received = fields.next().ok_or(AtError::AtParseError)?;
+
+        Ok(Some(SocketDataReceived {
+            socket_id,
+            data_len,
+            data: heapless::Vec::from_slice(received.as_bytes())?,
+            pending_len: None,
+        }))
+    }
+}
+
+impl AtRequest for ReceiveSocketData {
+    type Response = Option<SocketDataReceived>;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSORCV")
+            .with_int_parameter(self.socket_id)
+            .with_int_parameter(self.max_len)
+            .finish()
+    }
+
+    /// The error of the socket is reported in place of the data
+    fn claims_line(&self, line: &[u8]) -> bool {
+        line.starts_with(b"+CSOERR: ")
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        Self::get_data(data)
+    }
+}
+
+/// Unsolicited message sent by the modem when a socket fails or is closed by the remote
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketErrorReport {
+    /// Socket ID which failed
+    pub socket_id: u8,
+    /// Error code reported by the modem
+    pub error_code: i32,
+}
+
+impl SocketErrorReport {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (socket_id, error_code) = at_commands::parser::CommandParser::parse(data)
+            .expect_identifier(b"+CSOERR: ")
+            .expect_int_parameter()
+            .expect_int_parameter()
+            .finish()?;
+
+        Ok(SocketErrorReport {
+            socket_id: socket_id as u8,
+            error_code,
+        })
+    }
+
+    pub fn error(&self) -> SocketError {
+        SocketError::from(self.error_code)
+    }
+}
+
+/// Error of a socket, the modem reports the errno of its IP stack
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketError {
+    NoError,
+    BrokenPipe,
+    NetworkUnreachable,
+    ConnectionAborted,
+    ConnectionReset,
+    NotConnected,
+    TimedOut,
+    ConnectionRefused,
+    HostUnreachable,
+    Unknown(i32),
+}
+
+impl From<i32> for SocketError {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => SocketError::NoError,
+            32 => SocketError::BrokenPipe,
+            101 => SocketError::NetworkUnreachable,
+            103 => SocketError::ConnectionAborted,
+            104 => SocketError::ConnectionReset,
+            107 => SocketError::NotConnected,
+            110 => SocketError::TimedOut,
+            111 => SocketError::ConnectionRefused,
+            113 => SocketError::HostUnreachable,
+            code => SocketError::Unknown(code),
+        }
+    }
+}
+
+/// Queries the last error of a socket with `AT+CSOERR=<socket_id>`, giving the cause of a
+/// failed operation which only replied `ERROR`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryLastSocketError {
+    /// Socket ID obtained by using [CreateSocket]
+    pub socket_id: u8,
+}
+
+impl AtRequest for QueryLastSocketError {
+    type Response = SocketError;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut super::BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CSOERR")
+            .with_int_parameter(self.socket_id)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let report = SocketErrorReport::parse(strip_ok(data)?)?;
+        if report.socket_id != self.socket_id {
+            return Err(AtError::AtParseError);
+        }
+
+        Ok(report.error())
+    }
+}
+
+/// Unsolicited message sent by some firmwares once data sent with [SendSocketMessage] was
+/// transmitted. The immediate OK of the send only means the data was queued.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SocketSendAcknowledged {
+    /// Socket ID which sent the data
+    pub socket_id: u8,
+    /// Bytes acknowledged by the remote
+    pub acked_len: u16,
+    /// Bytes that can still be queued in the send buffer of the socket, only reported by some
+    /// firmwares
+    pub remaining_buffer: Option<u16>,
+}
+
+impl SocketSendAcknowledged {
+    /// Parses `+CSOSEND: <socket_id>,<acked_len>[,<remaining_buffer>]`
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        let (socket_id, acked_len, remaining_buffer) =
+            at_commands::parser::CommandParser::parse(data)
+                .expect_identifier(b"+CSOSEND: ")
+                .expect_int_parameter()
+                .expect_int_parameter()
+                .expect_optional_int_parameter()
+                .finish()?;
+
+        Ok(SocketSendAcknowledged {
+            socket_id: socket_id as u8,
+            acked_len: acked_len as u16,
+            remaining_buffer: remaining_buffer
+                .map(|remaining| u16::try_from(remaining).map_err(|_| AtError::AtParseError))
+                .transpose()?,
+        })
+    }
+
+    /// Whether all the `data_len` bytes that were sent have been acknowledged
+    pub fn is_complete(&self, data_len: u16) -> bool {
+        self.acked_len >= data_len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(deprecated)]
+    use crate::at_command::{
+        pdp_context::PDPContext,
+        socket::{
+            CloseMode, CloseSocket, ConnectSocketToRemote, CreateSocket, DataFormat,
+            DatagramTarget, Domain, GetSocketBufferMemory, ListSockets, Protocol,
+            QueryLastSocketError, ReceiveSocketData, SendSocketDatagram, SendSocketMessage,
+            SendSocketText, SetReceiveLengthReporting, SetSocketAutoReconnect, SetSocketDataFormat,
+            SetSocketOptions, SocketCreated, SocketDataReceived, SocketError, SocketOptions,
+            SocketPool, SocketSendAcknowledged, SocketState, SocketStatus, TcpConnection, Type,
+            MAX_LINGER_SECS,
+        },
+        AtRequest, AtResponse, Lenient, LenientResponse, UnknownResponse,
+    };
+    use crate::mock::{MockReader, MockTimer, MockWriter};
+    use crate::urc::Urc;
+    use crate::{AtError, Modem, UrcQueue};
+
+    #[test]
+    fn test_create_socket_command() {
+        let mut buffer = [0; 512];
+
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::TCP,
+            protocol: Protocol::IP,
+            cid: Some(3),
+        };
+
+        let result = create_socket.get_command(&mut buffer).unwrap();
+
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOC=1,1,1,3\r\n");
+    }
+
+    #[test]
+    fn test_create_socket_on_context() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CGACT: 1,1\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let mut buffer = [0; 512];
+
+        let context = modem.send_and_wait_response(&PDPContext).unwrap();
+        let create_socket =
+            CreateSocket::on_context(Domain::IPv4, Type::TCP, Protocol::IP, &context);
+        let result = create_socket.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOC=1,1,1,1\r\n");
+    }
+
+    #[test]
+    fn test_create_socket_on_defined_context() {
+        let mut writer = MockWriter::default();
+        let contexts = b"\r\n+CGDCONT: 1,\"IP\",\"iot.example\",\"10.0.0.2\",0,0\r\n\
+            +CGDCONT: 2,\"IPV6\",\"\",\"\",0,0\r\n\
+            +CGDCONT: 3,\"IPV4V6\",\"\",\"\",0,0\r\n\r\nOK\r\n";
+        let mut replies = b"\r\nOK\r\n".to_vec();
+        for _ in 0..4 {
+            replies.extend_from_slice(contexts);
+        }
+        let mut reader = MockReader::new(&replies);
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        for (domain, cid) in [
+            (Domain::IPv4, 1),
+            (Domain::IPv6, 2),
+            (Domain::IPv4, 3),
+            (Domain::IPv6, 3),
+        ] {
+            let create_socket =
+                CreateSocket::on_defined_context(&mut modem, domain, Type::TCP, Protocol::IP, cid)
+                    .unwrap();
+            assert_eq!(create_socket.cid, Some(cid));
+        }
+        assert!(modem.writer.written.ends_with(b"AT+CGDCONT?\r\n"));
+    }
+
+    #[test]
+    fn test_create_socket_on_undefined_context() {
+        let mut writer = MockWriter::default();
+        let contexts = b"\r\n+CGDCONT: 1,\"IP\",\"iot.example\",\"10.0.0.2\",0,0\r\n\
+            +CGDCONT: 2,\"IPV6\",\"\",\"\",0,0\r\n\
+            +CGDCONT: 5,\"Non-IP\",\"nidd.example\",\"\",0,0\r\n\r\nOK\r\n";
+        let mut replies = b"\r\nOK\r\n".to_vec();
+        for _ in 0..4 {
+            replies.extend_from_slice(contexts);
+        }
+        let mut reader = MockReader::new(&replies);
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        assert!(matches!(
+            CreateSocket::on_defined_context(&mut modem, Domain::IPv4, Type::TCP, Protocol::IP, 3),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(modem.writer.written.ends_with(b"AT+CGDCONT?\r\n"));
+
+        // The IP version or the lack of IP of the context does not match the socket
+        for (domain, cid) in [(Domain::IPv4, 2), (Domain::IPv4, 5), (Domain::IPv6, 1)] {
+            assert!(matches!(
+                CreateSocket::on_defined_context(&mut modem, domain, Type::TCP, Protocol::IP, cid),
+                Err(AtError::InvalidParameter)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_create_socket_response() {
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::UPD,
+            protocol: Protocol::IP,
+            cid: None,
+        };
+
+        let created = create_socket
+            .parse_response_struct(b"\r\n+CSOC: 2\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(created.socket_id, 2);
+    }
+
+    #[test]
+    fn test_create_socket_unknown_layout() {
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::UPD,
+            protocol: Protocol::IP,
+            cid: None,
+        };
+        let lenient = Lenient(&create_socket);
+
+        match lenient.parse_response_struct(b"\r\n+CSOC: 2,1\r\n\r\nOK\r\n") {
+            Ok(LenientResponse::Unknown(UnknownResponse { prefix, raw })) => {
+                assert_eq!(prefix.as_str(), "+CSOC");
+                assert_eq!(raw.as_slice(), b"+CSOC: 2,1");
+            }
+            _ => panic!("Expected LenientResponse::Unknown"),
+        }
+        assert!(matches!(
+            lenient.parse_response_struct(b"\r\n+CSOC: 2\r\n\r\nOK\r\n"),
+            Ok(LenientResponse::Parsed(SocketCreated { socket_id: 2 }))
+        ));
+
+        // Errors reported by the modem are kept
+        assert!(matches!(
+            lenient.parse_response_struct(b"\r\n+CSOC: -1\r\n\r\nOK\r\n"),
+            Err(AtError::NoFreeSocket)
+        ));
+        assert!(matches!(
+            lenient.parse_response_struct(b"\r\n+CGATT: 1\r\n\r\nOK\r\n"),
+            Err(AtError::AtParseError)
+        ));
+    }
+
+    #[test]
+    fn test_create_socket_error_forms() {
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::UPD,
+            protocol: Protocol::IP,
+            cid: None,
+        };
+
+        assert!(matches!(
+            create_socket.parse_response_struct(b"\r\n+CSOC: \r\n\r\nOK\r\n"),
+            Err(AtError::NoFreeSocket)
+        ));
+        assert!(matches!(
+            create_socket.parse_response_struct(b"\r\n+CSOC:\r\n\r\nOK\r\n"),
+            Err(AtError::NoFreeSocket)
+        ));
+        assert!(matches!(
+            create_socket.parse_response_struct(b"\r\n+CSOC: -1\r\n\r\nOK\r\n"),
+            Err(AtError::NoFreeSocket)
+        ));
+        assert!(matches!(
+            create_socket.parse_response_struct(b"\r\n+CSOC: -2\r\n\r\nOK\r\n"),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            create_socket.parse_response_struct(b"\r\n+CSOC: 300\r\n\r\nOK\r\n"),
+            Err(AtError::AtParseError)
+        ));
+    }
+
+    #[test]
+    fn test_send_socket_datagram_multicast() {
+        let mut buffer = [0; 512];
+        let send = SendSocketDatagram::new(
+            1,
+            Domain::IPv4,
+            Type::UPD,
+            "239.1.2.3",
+            5000,
+            b"AB01",
+            DatagramTarget::Multicast,
+        )
+        .unwrap();
+        let result = send.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSOSENDTO=1,\"239.1.2.3\",5000,4,AB01\r\n"
+        );
+
+        assert!(SendSocketDatagram::new(
+            1,
+            Domain::IPv6,
+            Type::UPD,
+            "[ff02::1]",
+            5000,
+            b"AB01",
+            DatagramTarget::Multicast,
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_send_socket_datagram_rejects_incompatible_targets() {
+        assert!(matches!(
+            SendSocketDatagram::new(
+                1,
+                Domain::IPv4,
+                Type::TCP,
+                "255.255.255.255",
+                5000,
+                b"AB01",
+                DatagramTarget::Broadcast,
+            ),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            SendSocketDatagram::new(
+                1,
+                Domain::IPv4,
+                Type::UPD,
+                "10.0.0.1",
+                5000,
+                b"AB01",
+                DatagramTarget::Broadcast,
+            ),
+            Err(AtError::InvalidAddress)
+        ));
+        assert!(matches!(
+            SendSocketDatagram::new(
+                1,
+                Domain::IPv6,
+                Type::UPD,
+                "ff02::1",
+                5000,
+                b"AB01",
+                DatagramTarget::Broadcast,
+            ),
+            Err(AtError::InvalidAddress)
+        ));
+        assert!(matches!(
+            SendSocketDatagram::new(
+                1,
+                Domain::IPv4,
+                Type::UPD,
+                "239.1.2.3",
+                5000,
+                b"AB01",
+                DatagramTarget::Unicast,
+            ),
+            Err(AtError::InvalidAddress)
+        ));
+    }
+
+    #[test]
+    fn test_set_socket_options_only_set_ones() {
+        let mut buffer = [0; 512];
+
+        let options = SocketOptions {
+            no_delay: Some(true),
+            linger_secs: None,
+        };
+        let command = SetSocketOptions::new(0, options).unwrap();
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSOSETOPT=0,\"NODELAY\",1\r\n"
+        );
+
+        let options = SocketOptions {
+            no_delay: Some(false),
+            linger_secs: Some(30),
+        };
+        let command = SetSocketOptions::new(2, options).unwrap();
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSOSETOPT=2,\"NODELAY\",0,\"LINGER\",30\r\n"
+        );
+    }
+
+    #[test]
+    fn test_set_socket_options_invalid() {
+        assert!(matches!(
+            SetSocketOptions::new(0, SocketOptions::default()),
+            Err(AtError::InvalidParameter)
+        ));
+        let options = SocketOptions {
+            no_delay: None,
+            linger_secs: Some(MAX_LINGER_SECS + 1),
+        };
+        assert!(matches!(
+            SetSocketOptions::new(0, options),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_query_last_socket_error() {
+        let mut buffer = [0; 512];
+        let query = QueryLastSocketError { socket_id: 1 };
+        let result = query.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOERR=1\r\n");
+
+        let error = query
+            .parse_response_struct(b"\r\n+CSOERR: 1,111\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(error, SocketError::ConnectionRefused);
+
+        let error = query
+            .parse_response_struct(b"\r\n+CSOERR: 1,7\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(error, SocketError::Unknown(7));
+    }
+
+    #[test]
+    fn test_send_socket_message_streamed() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        // Larger than the command buffer, it could not be sent if it was copied into it
+        let data = [b'A'; 1024];
+
+        modem
+            .send_streamed(&SendSocketMessage {
+                socket_id: 1,
+                data_len: data.len() as u16,
+                data: &data,
+            })
+            .unwrap();
+
+        let mut expected = b"ATE0\r\nAT+CSOSEND=1,1024,".to_vec();
+        expected.extend_from_slice(&data);
+        expected.extend_from_slice(b"\r\n");
+        assert_eq!(writer.written, expected);
+    }
+
+    #[test]
+    fn test_receive_socket_data() {
+        let mut buffer = [0; 512];
+        let receive = ReceiveSocketData {
+            socket_id: 1,
+            max_len: 128,
+        };
+        let result = receive.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSORCV=1,128\r\n");
+
+        let received = receive
+            .parse_response_struct(b"\r\n+CSORCV: 1,2,AB01\r\n\r\nOK\r\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.socket_id, 1);
+        assert_eq!(received.data_len, 2);
+        assert_eq!(received.data.as_slice(), b"AB01");
+    }
+
+    #[test]
+    fn test_receive_socket_data_empty_poll() {
+        let receive = ReceiveSocketData {
+            socket_id: 1,
+            max_len: 128,
+        };
+        assert!(receive
+            .parse_response_struct(b"\r\n+CSORCV: 1,0\r\n\r\nOK\r\n")
+            .unwrap()
+            .is_none());
+        assert!(receive
+            .parse_response_struct(b"\r\nOK\r\n")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_receive_socket_data_error() {
+        let receive = ReceiveSocketData {
+            socket_id: 1,
+            max_len: 128,
+        };
+        assert!(matches!(
+            receive.parse_response_struct(b"\r\n+CSOERR: 1,104\r\n\r\nOK\r\n"),
+            Err(AtError::Socket(SocketError::ConnectionReset))
+        ));
+    }
+
+    #[test]
+    fn test_set_socket_auto_reconnect() {
+        let mut buffer = [0; 512];
+        let command = SetSocketAutoReconnect::enable(1, 3, 30).unwrap();
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSORECONN=1,1,3,30\r\n"
+        );
+
+        let command = SetSocketAutoReconnect::disable(1);
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSORECONN=1,0\r\n"
+        );
+
+        assert!(matches!(
+            SetSocketAutoReconnect::enable(1, 0, 30),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            SetSocketAutoReconnect::enable(1, 3, 301),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_set_receive_length_reporting() {
+        let mut buffer = [0; 512];
+        let command = SetReceiveLengthReporting { enable: true };
+        let result = command.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSONMIFLAG=1\r\n");
+    }
+
+    #[test]
+    fn test_socket_data_with_pending_length() {
+        let received = SocketDataReceived::parse(b"+CSONMI: 1,120,4,AB01").unwrap();
+        assert_eq!(received.socket_id, 1);
+        assert_eq!(received.pending_len, Some(120));
+        assert_eq!(received.data_len, 4);
+        assert_eq!(received.data.as_slice(), b"AB01");
+
+        let received = SocketDataReceived::parse(b"+CSONMI: 1,4,AB01").unwrap();
+        assert_eq!(received.pending_len, None);
+        assert_eq!(received.data.as_slice(), b"AB01");
+    }
+
+    #[test]
+    fn test_create_socket_command_without_cid() {
+        let mut buffer = [0; 512];
+
+        let create_socket = CreateSocket {
+            domain: Domain::IPv6,
+            connection_type: Type::RAW,
+            protocol: Protocol::ICMP,
+            cid: None,
+        };
+
+        let result = create_socket.get_command(&mut buffer).unwrap();
+
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOC=2,3,2\r\n");
+    }
+
+    #[test]
+    fn test_parse_create_socket_response() {
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::TCP,
+            protocol: Protocol::IP,
+            cid: None,
+        };
+
+        // Response example: +CSOC 5\r\n\r\nOK\r\n
+        let response = b"\r\n+CSOC: 5\r\n\r\nOK\r";
+
+        let parsed = create_socket.parse_response(response).unwrap();
+
+        match parsed {
+            AtResponse::SocketCreated(id) => assert_eq!(id, 5),
+            _ => panic!("Expected AtResponse::SocketCreated"),
+        }
+    }
+
+    #[test]
+    fn test_connect_remote_socket_command() {
+        let mut buffer = [0; 512];
+
+        let at_connect_request = ConnectSocketToRemote {
+            connection_type: super::Type::TCP,
+            port: 1111,
+            socket_id: 1,
+            remote_address: "127.0.0.1",
+        };
+
+        let result = at_connect_request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CSOCON=1,1111,\"127.0.0.1\",1\r\n"
+        );
+    }
+
+    #[test]
+    fn test_connect_remote_socket_command_ipv6() {
+        let mut buffer = [0; 512];
+
+        for address in ["2001:db8::1", "[2001:db8::1]"] {
+            let request =
+                ConnectSocketToRemote::new(1, Domain::IPv6, address, 1111, Type::TCP).unwrap();
+            let result = request.get_command(&mut buffer).unwrap();
+
+            assert_eq!(
+                core::str::from_utf8(result).unwrap(),
+                "AT+CSOCON=1,1111,\"2001:db8::1\",1\r\n"
+            );
+        }
+    }
+
+    #[test]
+    fn test_connect_remote_socket_invalid_ipv6() {
+        for address in [
+            "127.0.0.1",
+            "[2001:db8::1",
+            "2001:db8::1%",
+            "2001:db8::1%1",
+            "[fe80::1%eth0]",
+            "example.com",
+        ] {
+            assert!(matches!(
+                ConnectSocketToRemote::new(1, Domain::IPv6, address, 1111, Type::TCP),
+                Err(AtError::InvalidAddress)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_connect_remote_socket_rejects_quoted_address() {
+        for address in ["127.0.0.1\",1", "example.com\r\nAT+CFUN=0"] {
+            assert!(matches!(
+                ConnectSocketToRemote::new(1, Domain::IPv4, address, 1111, Type::TCP),
+                Err(AtError::InvalidAddress)
+            ));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_connect_remote_socket_command_with_invalid_port() {
+        let mut buffer = [0; 512];
+
+        let at_connect_request = ConnectSocketToRemote {
+            connection_type: super::Type::TCP,
+            port: 0,
+            socket_id: 1,
+            remote_address: "127.0.0.1",
+        };
+
+        at_connect_request.get_command(&mut buffer).unwrap();
+    }
+
+    #[test]
+    fn test_command_len() {
+        let mut buffer = [0; 512];
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::TCP,
+            protocol: Protocol::IP,
+            cid: Some(1),
+        };
+        let built_len = create_socket.get_command(&mut buffer).unwrap().len();
+        assert_eq!(create_socket.command_len(), built_len);
+
+        let send = SendSocketMessage {
+            socket_id: 1,
+            data_len: 4,
+            data: b"AB01",
+        };
+        let built_len = send.get_command(&mut buffer).unwrap().len();
+        assert_eq!(send.command_len(), built_len);
+    }
+
+    #[test]
+    fn test_to_vec() {
+        let mut buffer = [0; 512];
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::TCP,
+            protocol: Protocol::IP,
+            cid: Some(1),
+        };
+
+        let command = create_socket.to_vec::<32>().unwrap();
+        assert_eq!(
+            command.as_slice(),
+            create_socket.get_command(&mut buffer).unwrap()
+        );
+        assert!(matches!(
+            create_socket.to_vec::<8>(),
+            Err(AtError::CapacityError)
+        ));
+    }
+
+    #[test]
+    fn test_command_len_too_large() {
+        let data = [b'A'; 600];
+        let send = SendSocketMessage {
+            socket_id: 1,
+            data_len: 300,
+            data: &data,
+        };
+        assert_eq!(
+            send.command_len(),
+            "AT+CSOSEND=1,300,".len() + data.len() + 2
+        );
+
+        // The last comma right at the end of the buffer is dropped as well
+        let data = [b'A'; 512 - "AT+CSOSEND=1,300,".len()];
+        let send = SendSocketMessage {
+            socket_id: 1,
+            data_len: 300,
+            data: &data,
+        };
+        assert_eq!(send.command_len(), 514);
+    }
+
+    #[test]
+    fn test_close_socket() {
+        let mut buffer = [0; 512];
+
+        let at_connect_request = CloseSocket {
+            socket_id: 0,
+            close_mode: None,
+        };
+
+        let result = at_connect_request.get_command(&mut buffer).unwrap();
+
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOCL=0\r\n");
+    }
+
+    #[test]
+    fn test_close_socket_modes() {
+        let mut buffer = [0; 512];
+
+        let graceful = CloseSocket {
+            socket_id: 1,
+            close_mode: Some(CloseMode::Graceful),
+        };
+        let result = graceful.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOCL=1,0\r\n");
+
+        let abortive = CloseSocket {
+            socket_id: 1,
+            close_mode: Some(CloseMode::try_from(1).unwrap()),
+        };
+        let result = abortive.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOCL=1,1\r\n");
+
+        assert!(matches!(
+            CloseMode::try_from(2),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_get_socket_buffer_memory() {
+        let mut buffer = [0; 512];
+
+        let request = GetSocketBufferMemory { socket_id: 1 };
+
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOBUF=1\r\n");
+
+        let response = b"\r\n+CSOBUF: 1,1024,12\r\n\r\nOK\r";
+        let memory = request.parse_response_struct(response).unwrap().unwrap();
+        assert_eq!(memory.socket_id, 1);
+        assert_eq!(memory.send_buffer_free, 1024);
+        assert_eq!(memory.receive_buffer_used, 12);
+    }
+
+    #[test]
+    fn test_get_socket_buffer_memory_unsupported() {
+        let request = GetSocketBufferMemory { socket_id: 1 };
+
+        let memory = request.parse_response_struct(b"\r\nOK\r").unwrap();
+        assert!(memory.is_none());
+    }
+
+    #[test]
+    fn test_parse_send_acknowledged_full() {
+        let ack = SocketSendAcknowledged::parse(b"+CSOSEND: 0,10").unwrap();
+
+        assert_eq!(ack.socket_id, 0);
+        assert_eq!(ack.acked_len, 10);
+        assert!(ack.is_complete(10));
+    }
+
+    #[test]
+    fn test_parse_send_acknowledged_partial() {
+        let ack = SocketSendAcknowledged::parse(b"+CSOSEND: 2,4").unwrap();
+
+        assert_eq!(ack.socket_id, 2);
+        assert_eq!(ack.acked_len, 4);
+        assert!(!ack.is_complete(10));
+        assert_eq!(ack.remaining_buffer, None);
+    }
+
+    #[test]
+    fn test_parse_send_acknowledged_remaining_buffer() {
+        let ack = SocketSendAcknowledged::parse(b"+CSOSEND: 1,10,1014").unwrap();
+
+        assert_eq!(ack.socket_id, 1);
+        assert_eq!(ack.acked_len, 10);
+        assert_eq!(ack.remaining_buffer, Some(1014));
+    }
+
+    #[test]
+    fn test_socket_pool_exhausted_and_reclaimed() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\n+CSOC: 1\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let request = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::TCP,
+            protocol: Protocol::IP,
+            cid: None,
+        };
+        let mut pool = SocketPool::<2>::new();
+
+        assert_eq!(pool.create(&mut modem, &request).unwrap(), 0);
+        assert_eq!(pool.create(&mut modem, &request).unwrap(), 1);
+        assert!(matches!(
+            pool.create(&mut modem, &request),
+            Err(AtError::SocketPoolFull)
+        ));
+
+        pool.close(&mut modem, 0).unwrap();
+        assert!(!pool.is_in_use(0));
+        assert!(matches!(
+            pool.close(&mut modem, 0),
+            Err(AtError::UnknownSocket)
+        ));
+
+        assert_eq!(pool.create(&mut modem, &request).unwrap(), 0);
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn test_socket_pool_rejects_duplicate_socket() {
+        let mut writer = MockWriter::default();
+        let mut reader =
+            MockReader::new(b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let request = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::TCP,
+            protocol: Protocol::IP,
+            cid: None,
+        };
+        let mut pool = SocketPool::<2>::new();
+
+        assert_eq!(pool.create(&mut modem, &request).unwrap(), 0);
+        assert!(matches!(
+            pool.create(&mut modem, &request),
+            Err(AtError::DuplicateSocket(0))
+        ));
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn test_tcp_connection_is_connected() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSOSTATUS: 0,3\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        assert!(connection.is_connected(&mut modem, &timer).unwrap());
+
+        // Cached, nothing else is sent nor read
+        let sent = modem.writer.written.len();
+        assert!(connection.is_connected(&mut modem, &timer).unwrap());
+        assert_eq!(modem.writer.written.len(), sent);
+        assert!(modem.writer.written.ends_with(b"AT+CSOSTATUS=0\r\n"));
+    }
+
+    #[test]
+    fn test_tcp_connection_remote_closed() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 1\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSOSTATUS: 1,1\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        assert_eq!(connection.socket_id(), 1);
+        assert!(!connection.is_connected(&mut modem, &timer).unwrap());
+    }
+
+    #[test]
+    fn test_parse_create_socket_response_with_varying_line_breaks() {
+        let create_socket = CreateSocket {
+            domain: Domain::IPv4,
+            connection_type: Type::TCP,
+            protocol: Protocol::IP,
+            cid: None,
+        };
+
+        for response in [
+            &b"\r\n+CSOC: 5\r\n\r\nOK\r"[..],
+            b"+CSOC: 5\r\nOK\r\n",
+            b"\r\n\r\n+CSOC: 5\r\n\r\n\r\nOK\r\n\r\n",
+        ] {
+            let created = create_socket.parse_response_struct(response).unwrap();
+            assert_eq!(created.socket_id, 5);
+        }
+
+        for response in [&b"\r\nERROR\r"[..], b"ERROR\r\n", b"\r\n\r\nERROR\r\n\r\n"] {
+            assert!(matches!(
+                create_socket.parse_response_struct(response),
+                Err(AtError::ErrorReply(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_tcp_connection_request() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSONMI: 3,2,FF\r\n\r\n+CSONMI: 0,4,AB01\r\n",
+        );
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        let mut response = [0; 16];
+        let reply = connection
+            .request(&mut modem, b"0102", &mut response, 1000, &timer)
+            .unwrap();
+
+        assert_eq!(reply, b"AB01");
+        assert!(modem.writer.written.ends_with(b"AT+CSOSEND=0,4,0102\r\n"));
+        // The data of the other socket is not lost
+        match modem.pop_pending_urc() {
+            Some(Urc::SocketData(data)) => assert_eq!(data.socket_id, 3),
+            _ => panic!("Expected Urc::SocketData"),
+        }
+    }
+
+    #[test]
+    fn test_tcp_connection_request_reply_before_ok() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSONMI: 0,4,AB01\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        let mut response = [0; 16];
+        let reply = connection
+            .request(&mut modem, b"0102", &mut response, 1000, &timer)
+            .unwrap();
+
+        assert_eq!(reply, b"AB01");
+        assert!(modem.pop_pending_urc().is_none());
+    }
+
+    #[test]
+    fn test_tcp_connection_request_reply_with_full_queue() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSONMI: 3,2,FF\r\n\r\n+CSONMI: 0,4,AB01\r\n\r\nOK\r\n",
+        );
+        let mut urcs = heapless::Deque::<Urc, 1>::new();
+        urcs.push_back(Urc::Ready).unwrap();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        let mut response = [0; 16];
+        let reply = connection
+            .request(&mut modem, b"0102", &mut response, 1000, &timer)
+            .unwrap();
+
+        assert_eq!(reply, b"AB01");
+        assert_eq!(modem.dropped_urcs(), 1);
+    }
+
+    #[test]
+    fn test_tcp_connection_request_rejects_oversized_payload() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        let payload = [b'0'; u16::MAX as usize + 2];
+        let mut response = [0; 16];
+        assert!(matches!(
+            connection.request(&mut modem, &payload, &mut response, 1000, &timer),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_set_socket_data_format() {
+        let mut buffer = [0; 512];
+        let result = SetSocketDataFormat {
+            format: DataFormat::Text,
+        }
+        .get_command(&mut buffer)
+        .unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSORCVFLAG=1\r\n");
+    }
+
+    #[test]
+    fn test_tcp_connection_send_follows_data_format() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        assert_eq!(modem.socket_data_format(), DataFormat::Hex);
+        connection.send(&mut modem, b"0102").unwrap();
+        assert!(modem.writer.written.ends_with(b"AT+CSOSEND=0,4,0102\r\n"));
+
+        modem.set_socket_data_format(DataFormat::Text).unwrap();
+        assert!(modem.writer.written.ends_with(b"AT+CSORCVFLAG=1\r\n"));
+        connection.send(&mut modem, b"ping").unwrap();
+        assert!(modem
+            .writer
+            .written
+            .ends_with(b"AT+CSOSEND=0,0,\"ping\"\r\n"));
+    }
+
+    #[test]
+    fn test_tcp_connection_send_keeps_interleaved_urc() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSONMI: 0,2,AB\r\n\r\nOK\r\n",
+        );
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        connection.send(&mut modem, b"0102").unwrap();
+
+        match modem.pop_pending_urc() {
+            Some(Urc::SocketData(data)) => assert_eq!(data.data.as_slice(), b"AB"),
+            _ => panic!("Expected Urc::SocketData"),
+        }
+    }
+
+    #[test]
+    fn test_tcp_connection_send_rejects_oversized_data() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        let sent = modem.writer.written.len();
+        let data = [b'0'; u16::MAX as usize + 2];
+        assert!(matches!(
+            connection.send(&mut modem, &data),
+            Err(AtError::InvalidParameter)
+        ));
+        assert_eq!(modem.writer.written.len(), sent);
+    }
+
+    #[test]
+    fn test_tcp_connection_send_confirmed() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSOSEND: 1,4\r\n\r\n+CSOSEND: 0,2\r\n\r\n+CSOSEND: 0,4\r\n",
+        );
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        connection
+            .send_confirmed(&mut modem, b"0102", 1000, &timer)
+            .unwrap();
+
+        // The acknowledgement of the other socket is kept
+        match modem.pop_pending_urc() {
+            Some(Urc::SocketSendAcknowledged(ack)) => assert_eq!(ack.socket_id, 1),
+            _ => panic!("Expected Urc::SocketSendAcknowledged"),
+        }
+    }
+
+    #[test]
+    fn test_tcp_connection_send_acknowledged_before_ok() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSOSEND: 0,4\r\n\r\nOK\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        connection
+            .send_confirmed(&mut modem, b"0102", 1000, &timer)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_tcp_connection_send_not_acknowledged() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 0\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\n+CSOSEND: 0,2\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(10);
+
+        let mut connection =
+            TcpConnection::connect(&mut modem, Domain::IPv4, "127.0.0.1", 1111, None).unwrap();
+        assert!(matches!(
+            connection.send_confirmed(&mut modem, b"0102", 30, &timer),
+            Err(AtError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_send_socket_text_rejects_quotes() {
+        assert!(matches!(
+            SendSocketText::new(0, "say \"hi\""),
+            Err(AtError::InvalidParameter)
+        ));
+        assert!(matches!(
+            SendSocketText::new(0, "line\r\n"),
+            Err(AtError::InvalidParameter)
+        ));
+
+        let mut buffer = [0; 512];
+        let send = SendSocketText::new(0, "ping").unwrap();
+        let result = send.get_command(&mut buffer).unwrap();
+        assert_eq!(result, b"AT+CSOSEND=0,0,\"ping\"\r\n");
+    }
+
+    #[test]
+    fn test_list_sockets() {
+        let mut buffer = [0; 512];
+        let result = ListSockets.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CSOSTATUS?\r\n");
+
+        let sockets = ListSockets
+            .parse_response_struct(
+                b"\r\n+CSOSTATUS: 0,3\r\n+CSOSTATUS: 1,0\r\n+CSOSTATUS: 2,1\r\n\r\nOK\r\n",
+            )
+            .unwrap();
+        assert_eq!(
+            sockets.as_slice(),
+            [
+                SocketStatus {
+                    socket_id: 0,
+                    state: SocketState::Connected,
+                },
+                SocketStatus {
+                    socket_id: 2,
+                    state: SocketState::Created,
+                },
+            ]
+        );
     }
 }
@@ -0,0 +1,59 @@
+use crate::at_command::{strip_ok, AtRequest, BufferType};
+use crate::AtError;
+use at_commands::parser::CommandParser;
+
+/// Reads the internal temperature of the module in degrees Celsius with `AT+CPMUTEMP`, on
+/// firmwares which implement it
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueryTemperature;
+
+impl AtRequest for QueryTemperature {
+    type Response = i8;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_execute(buffer, true)
+            .named("+CPMUTEMP")
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let (celsius,) = CommandParser::parse(strip_ok(data)?)
+            .expect_identifier(b"+CPMUTEMP: ")
+            .expect_int_parameter()
+            .finish()?;
+
+        i8::try_from(celsius).map_err(|_| AtError::AtParseError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockReader, MockWriter};
+    use crate::Modem;
+
+    #[test]
+    fn test_query_temperature() {
+        let mut buffer = [0; 512];
+        let result = QueryTemperature.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CPMUTEMP\r\n");
+
+        let celsius = QueryTemperature
+            .parse_response_struct(b"\r\n+CPMUTEMP: 31\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(celsius, 31);
+        let celsius = QueryTemperature
+            .parse_response_struct(b"\r\n+CPMUTEMP: -12\r\n\r\nOK\r\n")
+            .unwrap();
+        assert_eq!(celsius, -12);
+    }
+
+    #[test]
+    fn test_query_temperature_unsupported() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CME ERROR: 4\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        assert_eq!(modem.send_optional(&QueryTemperature).unwrap(), None);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::at_command::{parse_csv_line, strip_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Parity of the serial port framing, as set with `AT+ICF`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    Odd,
+    Even,
+    Mark,
+    Space,
+    /// The framing has no parity bit
+    None,
+}
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UartConfiguration {
+    /// Baud rate, `0` means the modem detects it automatically
+    pub baud_rate: u32,
+    pub data_bits: u8,
+    pub parity: Parity,
+    pub stop_bits: u8,
+}
+
+/// Reads the serial port configuration with `AT+IPR?;+ICF?`, e.g. to check a new baud rate
+/// or framing took effect
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GetUartConfiguration;
+
+const COMMAND: &[u8] = b"AT+IPR?;+ICF?\r\n";
+
+impl GetUartConfiguration {
+    /// Parses the `<format>,<parity>` fields of `+ICF`
+    fn parse_framing(format: &[u8], parity: &[u8]) -> Result<(u8, Parity, u8), AtError> {
+        let (data_bits, has_parity, stop_bits) = match format {
+            b"1" => (8, false, 2),
+            b"2" => (8, true, 1),
+            b"3" => (8, false, 1),
+            b"4" => (7, false, 2),
+            b"5" => (7, true, 1),
+            b"6" => (7, false, 1),
+            _ => return Err(AtError::AtParseError),
+        };
+
+        let parity = match (has_parity, parity) {
+            (false, _) => Parity::None,
+            (true, b"0") => Parity::Odd,
+            (true, b"1") => Parity::Even,
+            (true, b"2") => Parity::Mark,
+            (true, b"3") => Parity::Space,
+            _ => return Err(AtError::AtParseError),
+        };
+
+        Ok((data_bits, parity, stop_bits))
+    }
+}
+
+impl AtRequest for GetUartConfiguration {
+    type Response = UartConfiguration;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        if COMMAND.len() > buffer.len() {
+            return Err(COMMAND.len());
+        }
+
+        buffer[..COMMAND.len()].copy_from_slice(COMMAND);
+        Ok(&buffer[..COMMAND.len()])
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let mut baud_rate = None;
+        let mut framing = None;
+
+        for line in strip_ok(data)?.split(|b| *b == b'\n') {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.starts_with(b"+IPR: ") {
+                let rate = parse_csv_line(line)?.next().ok_or(AtError::AtParseError)?;
+                baud_rate = core::str::from_utf8(rate)
+                    .ok()
+                    .and_then(|rate| rate.parse().ok());
+            } else if line.starts_with(b"+ICF: ") {
+                let mut fields = parse_csv_line(line)?;
+                let format = fields.next().ok_or(AtError::AtParseError)?;
+                let parity = fields.next().ok_or(AtError::AtParseError)?;
+                framing = Some(Self::parse_framing(format, parity)?);
+            }
+        }
+
+        let (data_bits, parity, stop_bits) = framing.ok_or(AtError::AtParseError)?;
+        Ok(UartConfiguration {
+            baud_rate: baud_rate.ok_or(AtError::AtParseError)?,
+            data_bits,
+            parity,
+            stop_bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_get_uart_configuration_command() {
+        let mut buffer = [0; 512];
+        let result = GetUartConfiguration.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+IPR?;+ICF?\r\n");
+    }
+
+    #[test]
+    fn test_parse_uart_configuration() {
+        let configuration = GetUartConfiguration
+            .parse_response_struct(b"\r\n+IPR: 115200\r\n\r\n+ICF: 3,3\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(
+            configuration,
+            UartConfiguration {
+                baud_rate: 115200,
+                data_bits: 8,
+                parity: Parity::None,
+                stop_bits: 1,
+            }
+        );
+
+        let configuration = GetUartConfiguration
+            .parse_response_struct(b"\r\n+IPR: 9600\r\n\r\n+ICF: 2,1\r\n\r\nOK\r")
+            .unwrap();
+        assert_eq!(configuration.parity, Parity::Even);
+        assert_eq!(configuration.stop_bits, 1);
+    }
+
+    #[test]
+    fn test_parse_uart_configuration_missing_framing() {
+        assert!(matches!(
+            GetUartConfiguration.parse_response_struct(b"\r\n+IPR: 115200\r\n\r\nOK\r"),
+            Err(AtError::AtParseError)
+        ));
+    }
+}
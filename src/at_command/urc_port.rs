@@ -0,0 +1,69 @@
+use crate::at_command::{verify_ok, AtRequest, BufferType};
+use crate::AtError;
+
+/// Serial port the URCs are sent to
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrcPort {
+    /// Every port, the default
+    All = 0,
+    MainUart = 1,
+    AuxiliaryUart = 2,
+}
+
+impl TryFrom<u8> for UrcPort {
+    type Error = AtError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(UrcPort::All),
+            1 => Ok(UrcPort::MainUart),
+            2 => Ok(UrcPort::AuxiliaryUart),
+            _ => Err(AtError::InvalidParameter),
+        }
+    }
+}
+
+/// Routes the URCs to a single port, keeping the command traffic apart from the notifications
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SetUrcPort {
+    pub port: UrcPort,
+}
+
+impl AtRequest for SetUrcPort {
+    type Response = ();
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CATR")
+            .with_int_parameter(self.port as i32)
+            .finish()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        verify_ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_urc_port_request() {
+        let mut buffer = [0; 512];
+        let request = SetUrcPort {
+            port: UrcPort::try_from(2).unwrap(),
+        };
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(core::str::from_utf8(result).unwrap(), "AT+CATR=2\r\n");
+    }
+
+    #[test]
+    fn test_urc_port_out_of_range() {
+        assert!(matches!(
+            UrcPort::try_from(3),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::at_command::{
+    parse_csv_line, strip_ok, validate_string_parameter, AtRequest, BufferType, FinishExact,
+};
+use crate::AtError;
+
+/// Longest USSD code accepted by [SendUssd]
+pub const MAX_USSD_CODE_LEN: usize = 32;
+/// A USSD message has at most 182 characters
+pub const USSD_TEXT_MAX_SIZE: usize = 182;
+
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UssdStatus {
+    /// The session is over, no reply is expected
+    Done,
+    /// The network expects a reply, sent with another [SendUssd]
+    ReplyExpected,
+    /// The session was terminated by the network
+    Terminated,
+    /// Another client of the modem answered
+    OtherClient,
+    NotSupported,
+    Timeout,
+}
+
+impl TryFrom<&[u8]> for UssdStatus {
+    type Error = AtError;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        match value {
+            b"0" => Ok(UssdStatus::Done),
+            b"1" => Ok(UssdStatus::ReplyExpected),
+            b"2" => Ok(UssdStatus::Terminated),
+            b"3" => Ok(UssdStatus::OtherClient),
+            b"4" => Ok(UssdStatus::NotSupported),
+            b"5" => Ok(UssdStatus::Timeout),
+            _ => Err(AtError::AtParseError),
+        }
+    }
+}
+
+/// Reply of the network to a USSD request, sent with `+CUSD: <m>[,<text>,<dcs>]`
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UssdReply {
+    pub status: UssdStatus,
+    /// `None` if there is no text, e.g. when the session was terminated
+    pub text: Option<heapless::String<USSD_TEXT_MAX_SIZE>>,
+}
+
+impl UssdReply {
+    pub(crate) fn parse(data: &[u8]) -> Result<Self, AtError> {
+        if !data.starts_with(b"+CUSD: ") {
+            return Err(AtError::AtParseError);
+        }
+
+        let mut fields = parse_csv_line(data)?;
+        let status = fields.next().ok_or(AtError::AtParseError)?.try_into()?;
+        let text = fields
+            .next()
+            .map(|text| core::str::from_utf8(text).map_err(|_| AtError::AtParseError))
+            .transpose()?
+            .map(heapless::String::try_from)
+            .transpose()?;
+
+        Ok(UssdReply { status, text })
+    }
+}
+
+/// Sends a USSD code, e.g. `*100#` to check the balance, with `AT+CUSD=1,<code>`. Most
+/// networks reply after the OK, with a [UssdReply] URC, in which case `Ok(None)` is returned.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SendUssd<'a> {
+    pub(crate) code: &'a str,
+}
+
+impl<'a> SendUssd<'a> {
+    pub fn new(code: &'a str) -> Result<Self, AtError> {
+        validate_string_parameter(code)?;
+        if code.is_empty() || code.len() > MAX_USSD_CODE_LEN {
+            return Err(AtError::InvalidParameter);
+        }
+
+        Ok(Self { code })
+    }
+}
+
+impl AtRequest for SendUssd<'_> {
+    type Response = Option<UssdReply>;
+
+    fn get_command<'a>(&'a self, buffer: &'a mut BufferType) -> Result<&'a [u8], usize> {
+        at_commands::builder::CommandBuilder::create_set(buffer, true)
+            .named("+CUSD")
+            .with_int_parameter(1)
+            .with_string_parameter(self.code)
+            .finish_exact()
+    }
+
+    fn parse_response_struct(&self, data: &[u8]) -> Result<Self::Response, AtError> {
+        let payload = strip_ok(data)?;
+        if payload.is_empty() {
+            return Ok(None);
+        }
+
+        UssdReply::parse(payload).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_send_ussd_command() {
+        let mut buffer = [0; 512];
+        let request = SendUssd::new("*100#").unwrap();
+        let result = request.get_command(&mut buffer).unwrap();
+        assert_eq!(
+            core::str::from_utf8(result).unwrap(),
+            "AT+CUSD=1,\"*100#\"\r\n"
+        );
+
+        assert!(request
+            .parse_response_struct(b"\r\nOK\r\n")
+            .unwrap()
+            .is_none());
+        assert!(matches!(SendUssd::new(""), Err(AtError::InvalidParameter)));
+        assert!(matches!(
+            SendUssd::new("*100#\r\nAT+CFUN=0"),
+            Err(AtError::InvalidParameter)
+        ));
+    }
+
+    #[test]
+    fn test_parse_ussd_reply() {
+        let reply = UssdReply::parse(b"+CUSD: 0,\"Balance: 5,20 EUR\",15").unwrap();
+        assert_eq!(reply.status, UssdStatus::Done);
+        assert_eq!(reply.text.unwrap().as_str(), "Balance: 5,20 EUR");
+
+        let reply = UssdReply::parse(b"+CUSD: 2").unwrap();
+        assert_eq!(reply.status, UssdStatus::Terminated);
+        assert!(reply.text.is_none());
+    }
+}
@@ -0,0 +1,73 @@
+//! Conversions of the signal codes reported by the modem into physical units
+
+/// Code reported when the value is unknown or not detectable
+pub const UNKNOWN_CSQ: u8 = 99;
+/// Code reported by the engineering reports when the value is unknown
+pub const UNKNOWN_MEASUREMENT: u8 = 255;
+
+/// Converts the `<rssi>` code of `+CSQ` into dBm (3GPP TS 27.007, 8.5): `0` is -113 dBm or
+/// less, `1` is -111 dBm, `2..=30` go from -109 to -53 dBm in 2 dB steps and `31` is -51 dBm
+/// or more. `None` for [UNKNOWN_CSQ] or an out of range code.
+pub fn csq_rssi_to_dbm(code: u8) -> Option<i16> {
+    match code {
+        0..=31 => Some(-113 + 2 * code as i16),
+        _ => None,
+    }
+}
+
+/// Converts an RSRP code into dBm (3GPP TS 36.133, 9.1.4): `0` is below -140 dBm, `1..=96`
+/// are 1 dB wide ranges starting at -140 dBm and `97` is -44 dBm or more. The lower bound of
+/// each range is returned, so `0` gives -141 dBm. `None` for [UNKNOWN_MEASUREMENT] or an out of
+/// range code.
+pub fn rsrp_to_dbm(code: u8) -> Option<i16> {
+    match code {
+        0..=97 => Some(code as i16 - 141),
+        _ => None,
+    }
+}
+
+/// Converts an RSRQ code into dB (3GPP TS 36.133, 9.1.7): `0` is below -19.5 dB, `1..=33` are
+/// 0.5 dB wide ranges starting at -19.5 dB and `34` is -3 dB or more. The lower bound of each
+/// range is returned, so `0` gives -20 dB. `None` for [UNKNOWN_MEASUREMENT] or an out of range
+/// code.
+pub fn rsrq_to_db(code: u8) -> Option<f32> {
+    match code {
+        0..=34 => Some(code as f32 / 2.0 - 20.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_csq_rssi_to_dbm() {
+        assert_eq!(csq_rssi_to_dbm(0), Some(-113));
+        assert_eq!(csq_rssi_to_dbm(1), Some(-111));
+        assert_eq!(csq_rssi_to_dbm(15), Some(-83));
+        assert_eq!(csq_rssi_to_dbm(31), Some(-51));
+        assert_eq!(csq_rssi_to_dbm(32), None);
+        assert_eq!(csq_rssi_to_dbm(UNKNOWN_CSQ), None);
+    }
+
+    #[test]
+    fn test_rsrp_to_dbm() {
+        assert_eq!(rsrp_to_dbm(0), Some(-141));
+        assert_eq!(rsrp_to_dbm(1), Some(-140));
+        assert_eq!(rsrp_to_dbm(50), Some(-91));
+        assert_eq!(rsrp_to_dbm(97), Some(-44));
+        assert_eq!(rsrp_to_dbm(98), None);
+        assert_eq!(rsrp_to_dbm(UNKNOWN_MEASUREMENT), None);
+    }
+
+    #[test]
+    fn test_rsrq_to_db() {
+        assert_eq!(rsrq_to_db(0), Some(-20.0));
+        assert_eq!(rsrq_to_db(1), Some(-19.5));
+        assert_eq!(rsrq_to_db(17), Some(-11.5));
+        assert_eq!(rsrq_to_db(34), Some(-3.0));
+        assert_eq!(rsrq_to_db(35), None);
+        assert_eq!(rsrq_to_db(UNKNOWN_MEASUREMENT), None);
+    }
+}
@@ -3,23 +3,34 @@
 #![cfg_attr(not(test), no_main)]
 
 pub mod at_command;
+pub mod conversions;
+#[cfg(test)]
+mod mock;
 #[cfg(feature = "nonblocking")]
 pub mod nonblocking;
+pub mod rate_limit;
+pub mod urc;
 
+use crate::at_command::firmware::FirmwareUpdateState;
 use crate::at_command::flow_control::ControlFlowStatus;
 use crate::at_command::http::HttpClient;
+use crate::at_command::socket::DataFormat;
 #[allow(deprecated)]
 use crate::at_command::AtResponse;
 use crate::at_command::{
-    cmee::ReportMobileEquipmentErrorSetting, flow_control::GetFlowControlResponse,
+    cmee::{CmeError, ReportMobileEquipmentErrorSetting},
+    flow_control::GetFlowControlResponse,
 };
-use at_command::AtRequest;
+use crate::rate_limit::{ActiveRateLimiter, RateLimiter};
+use crate::urc::{Urc, UrcKind};
+use at_command::{AtRequest, PromptExchange, StreamedRequest};
 use at_commands::parser::ParseError;
 #[cfg(feature = "defmt")]
-use defmt::*;
+use defmt::{debug, error, info, trace, warn};
 #[cfg(feature = "defmt")]
 use embedded_io::Error;
 pub use embedded_io::{Read, Write};
+pub use urc::{decode, DecodedFrame};
 
 const BUFFER_SIZE: usize = 512;
 const LF: u8 = 10; // n
@@ -28,9 +39,47 @@ const CR: u8 = 13; // r
 const OK_TERMINATOR: &[u8] = &[CR, LF, b'O', b'K', CR, LF];
 const ERROR_TERMINATOR: &[u8] = &[b'R', b'R', b'O', b'R', CR, LF];
 
+/// Suggested capacity of a [UrcQueue]
+pub const MAX_PENDING_URCS: usize = 4;
+
+/// Queue keeping the URCs received while waiting for something else, see
+/// [Modem::with_urc_queue]. Any other capacity can be used with a [heapless::Deque].
+pub type UrcQueue = heapless::Deque<Urc, MAX_PENDING_URCS>;
+
+/// Longest wait for the `RDY` URC between the `AT` sent by [Modem::wait_until_ready]
+pub const READY_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Bytes requested on each read of a response
+const READ_CHUNK_SIZE: usize = 100;
+
+/// Monotonic time source used to implement timeouts
+pub trait Timer {
+    /// Milliseconds elapsed since an arbitrary but fixed point in time
+    fn now_ms(&self) -> u64;
+}
+
+/// Blocking delay, e.g. backed by a hardware timer or the scheduler of an RTOS
+pub trait Delay {
+    /// Blocks for at least the given amount of milliseconds
+    fn delay_ms(&mut self, ms: u64);
+}
+
+/// Driver of the modem.
+///
+/// Responses are parsed into owned values before the read buffer is reused, nothing returned
+/// borrows from it. As reads are done in chunks, the bytes received after the final result code
+/// of a response, e.g. a URC sent right after the OK, are kept and consumed first by the next
+/// read instead of being overwritten.
 pub struct Modem<'a, T: Write, U: Read> {
     pub writer: &'a mut T,
     pub reader: &'a mut U,
+    /// Provided by the caller, a [Urc] is several hundred bytes long
+    pending_urcs: Option<&'a mut heapless::deque::DequeView<Urc>>,
+    /// URCs lost as there was no queue or it was full
+    dropped_urcs: u32,
+    unread: heapless::Deque<u8, READ_CHUNK_SIZE>,
+    socket_data_format: DataFormat,
+    rate_limiter: Option<ActiveRateLimiter<'a>>,
 }
 
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -46,6 +95,28 @@ pub enum AtError {
     ConnectSocketError,
     CapacityError,
     ParseClockError,
+    Timeout,
+    SocketPoolFull,
+    UnknownSocket,
+    /// The modem created a socket with an ID a [SocketPool](at_command::socket::SocketPool)
+    /// already tracks, e.g. as it was closed without going through the pool
+    DuplicateSocket(u8),
+    InvalidAddress,
+    FirmwareUpdateFailed,
+    InvalidParameter,
+    /// The command line is longer than
+    /// [MAX_COMMAND_LINE_LENGTH](at_command::MAX_COMMAND_LINE_LENGTH), unlike the `Err(usize)`
+    /// of [AtRequest::get_command] which means the command buffer is too small
+    BufferOverflow,
+    /// The modem has no free socket left
+    NoFreeSocket,
+    /// `+CME ERROR: <n>` reply, see [CmeError](at_command::cmee::CmeError) for the meaning of
+    /// the code
+    Cme(u16),
+    /// Error reported by the modem for a socket
+    Socket(at_command::socket::SocketError),
+    /// The call started with [Dial](at_command::call::Dial) could not be established
+    CallFailed(at_command::call::CallFailure),
 }
 
 impl From<ParseError> for AtError {
@@ -68,12 +139,118 @@ impl From<chrono::format::ParseError> for AtError {
 
 impl<'a, T: Write, U: Read> Modem<'a, T, U> {
     pub fn new(writer: &'a mut T, reader: &'a mut U) -> Result<Self, AtError> {
-        let mut modem = Self { writer, reader };
+        let mut modem = Self::unconfigured(writer, reader);
         modem.disable_echo()?;
         // modem.get_flow_control().expect("failed to get flow control");
         Ok(modem)
     }
 
+    /// Same as [Modem::new], to be used right after powering the modem on: it waits until the
+    /// modem is ready with [Modem::wait_until_ready] instead of relying on a fixed delay. The
+    /// boot URCs other than `RDY` are dropped as no queue is set yet, follow them with
+    /// [BootSequence](urc::BootSequence) if needed.
+    pub fn after_power_on<C: Timer>(
+        writer: &'a mut T,
+        reader: &'a mut U,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<Self, AtError> {
+        let mut modem = Self::unconfigured(writer, reader);
+        modem.wait_until_ready(timeout_ms, timer)?;
+        modem.disable_echo()?;
+        Ok(modem)
+    }
+
+    fn unconfigured(writer: &'a mut T, reader: &'a mut U) -> Self {
+        Self {
+            writer,
+            reader,
+            pending_urcs: None,
+            dropped_urcs: 0,
+            unread: heapless::Deque::new(),
+            socket_data_format: DataFormat::Hex,
+            rate_limiter: None,
+        }
+    }
+
+    /// Keeps the URCs received while waiting for a response or for a different URC in the
+    /// given queue, to be retrieved with [Modem::pop_pending_urc]. Without a queue they are
+    /// dropped, see [Modem::dropped_urcs].
+    pub fn with_urc_queue<const N: usize>(
+        mut self,
+        queue: &'a mut heapless::Deque<Urc, N>,
+    ) -> Self {
+        self.pending_urcs = Some(queue.as_mut_view());
+        self
+    }
+
+    /// Spaces out the commands of the kinds set in the limiter, see
+    /// [AtRequest::command_kind]. The time is read from `timer`, `delay` blocks until the
+    /// interval elapsed.
+    pub fn with_rate_limiter(
+        mut self,
+        limiter: &'a mut RateLimiter,
+        timer: &'a dyn Timer,
+        delay: &'a mut dyn Delay,
+    ) -> Self {
+        self.rate_limiter = Some(ActiveRateLimiter {
+            limiter,
+            timer,
+            delay,
+        });
+        self
+    }
+
+    /// Amount of URCs lost because no queue was set with [Modem::with_urc_queue] or it was full
+    pub fn dropped_urcs(&self) -> u32 {
+        self.dropped_urcs
+    }
+
+    /// Keeps a URC which is not the one being waited for, counting it as dropped if it does not
+    /// fit
+    fn keep_urc(&mut self, urc: Urc) {
+        let kept = match self.pending_urcs.as_mut() {
+            Some(queue) => queue.push_back(urc).is_ok(),
+            None => false,
+        };
+        if !kept {
+            #[cfg(feature = "defmt")]
+            warn!("dropping URC, the queue is full or not set");
+            self.dropped_urcs = self.dropped_urcs.saturating_add(1);
+        }
+    }
+
+    /// Waits for the `RDY` URC sent once the modem booted and checks it answers a bare `AT`.
+    /// `RDY` is awaited for up to [READY_POLL_INTERVAL_MS] at a time, in between `AT` is sent,
+    /// so a modem which was already on is detected as well, until it answers or `timeout_ms`
+    /// elapsed. A reader returning an error as nothing is received, e.g. a UART timing out,
+    /// is expected while the modem boots; a reader blocking forever blocks this call as well.
+    pub fn wait_until_ready<C: Timer>(
+        &mut self,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<(), AtError> {
+        let start = timer.now_ms();
+        loop {
+            let remaining = timeout_ms.saturating_sub(timer.now_ms().saturating_sub(start));
+            match self.wait_for_urc(UrcKind::Ready, remaining.min(READY_POLL_INTERVAL_MS), timer) {
+                Ok(_) | Err(AtError::Timeout) | Err(AtError::NotReady) => {}
+                Err(e) => return Err(e),
+            }
+
+            // The rest of the boot URCs, sent before the OK, are kept as in any other response
+            match self.send_and_wait_response(&at_command::at::At {}) {
+                Ok(()) => return Ok(()),
+                Err(AtError::NotReady) | Err(AtError::AtParseError) => {}
+                Err(e) => return Err(e),
+            }
+
+            if timer.now_ms().saturating_sub(start) >= timeout_ms {
+                return Err(AtError::Timeout);
+            }
+        }
+    }
+
     /// disable echo if echo is enabled
     pub fn disable_echo(&mut self) -> Result<(), AtError> {
         #[cfg(feature = "defmt")]
@@ -104,6 +281,21 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
         Ok(())
     }
 
+    /// Sets the format of the data of every socket. The data received is delivered in it by
+    /// the modem, the data sent through [TcpConnection](at_command::socket::TcpConnection) is
+    /// encoded in it. Requests sending data directly, e.g.
+    /// [SendSocketMessage](at_command::socket::SendSocketMessage), are not affected.
+    pub fn set_socket_data_format(&mut self, format: DataFormat) -> Result<(), AtError> {
+        self.send_and_wait_response(&at_command::socket::SetSocketDataFormat { format })?;
+        self.socket_data_format = format;
+        Ok(())
+    }
+
+    /// Format set with [Modem::set_socket_data_format]
+    pub fn socket_data_format(&self) -> DataFormat {
+        self.socket_data_format
+    }
+
     /// probe the modem's readiness by sending 'AT'. Errors if not ready.
     pub fn ready(&mut self) -> Result<(), AtError> {
         #[cfg(feature = "defmt")]
@@ -112,15 +304,43 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
         Ok(())
     }
 
+    /// Times an `AT` to `OK` round trip and returns it in milliseconds, e.g. to detect a modem
+    /// slowed down by a heavy load. The time includes the transport, at low baud rates writing
+    /// and reading the few bytes involved is part of it.
+    pub fn measure_latency<C: Timer>(&mut self, timer: &C) -> Result<u64, AtError> {
+        let start = timer.now_ms();
+        self.send_and_wait_response(&at_command::at::At {})?;
+        Ok(timer.now_ms().saturating_sub(start))
+    }
+
+    /// Sends the request and parses its response. URCs received before the final OK are kept
+    /// pending, see [Modem::pop_pending_urc].
     pub fn send_and_wait_response<'b, V: AtRequest + 'b>(
         &'b mut self,
         payload: &V,
+    ) -> Result<V::Response, AtError> {
+        self.send_and_intercept_urcs(payload, Some)
+    }
+
+    /// Same as [Modem::send_and_wait_response], the URCs received before the final OK are
+    /// given to `intercept` first, only those it returns are kept pending
+    pub(crate) fn send_and_intercept_urcs<V: AtRequest>(
+        &mut self,
+        payload: &V,
+        mut intercept: impl FnMut(Urc) -> Option<Urc>,
     ) -> Result<V::Response, AtError> {
         #[cfg(feature = "defmt")]
         info!("Sending command to the modem");
 
+        payload.validate()?;
         let mut buffer = [0; BUFFER_SIZE];
-        let data = payload.get_command_no_error(&mut buffer);
+        let data = payload
+            .get_command(&mut buffer)
+            .map_err(at_command::command_too_long)?;
+
+        if let (Some(kind), Some(limiter)) = (payload.command_kind(), self.rate_limiter.as_mut()) {
+            limiter.acquire(kind);
+        }
 
         #[cfg(feature = "defmt")]
         debug!("sending command: {=[u8]:a}", data);
@@ -128,20 +348,153 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
         self.writer.write(data).map_err(|_e| AtError::IOError)?;
 
         let mut read_buffer = [0; BUFFER_SIZE];
-        let response_size = self.read_response(&mut read_buffer)?;
+        let response_size =
+            self.read_response_until(&mut read_buffer, |line| payload.is_final_result(line))?;
+        let response_size = urc::extract_interleaved_urcs(
+            data,
+            &mut read_buffer[..response_size],
+            |line| payload.claims_line(line),
+            |urc| {
+                if let Some(urc) = intercept(urc) {
+                    self.keep_urc(urc)
+                }
+            },
+        );
         let response = payload.parse_response_struct(&read_buffer[..response_size])?;
 
         Ok(response)
     }
 
+    /// Sends a request not every firmware implements. `Ok(None)` is returned when the firmware
+    /// reports it does not support it with `+CME ERROR: 4`, which requires numeric error
+    /// reports, see [SetReportMobileEquipmentError](at_command::cmee::SetReportMobileEquipmentError).
+    /// Any other error, a plain `ERROR` included, is returned as it may be a real failure.
+    pub fn send_optional<V: AtRequest>(
+        &mut self,
+        payload: &V,
+    ) -> Result<Option<V::Response>, AtError> {
+        match self.send_and_wait_response(payload) {
+            Ok(response) => Ok(Some(response)),
+            Err(AtError::Cme(code)) if CmeError::from(code) == CmeError::OperationNotSupported => {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sends the header of the request, waits up to `timeout_ms` for the `>` prompt and sends
+    /// the data. An `ERROR` reply to the header is returned as [AtError::ErrorReply], a
+    /// `+CME ERROR: <n>` one as [AtError::Cme].
+    pub fn send_prompt_exchange<V: PromptExchange, C: Timer>(
+        &mut self,
+        payload: &V,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<V::Response, AtError> {
+        let mut buffer = [0; BUFFER_SIZE];
+        let header = payload
+            .get_header(&mut buffer)
+            .map_err(at_command::command_too_long)?;
+
+        #[cfg(feature = "defmt")]
+        debug!("sending header: {=[u8]:a}", header);
+        self.writer.write(header).map_err(|_e| AtError::IOError)?;
+        self.read_prompt(timeout_ms, timer)?;
+
+        let data = payload
+            .get_data(&mut buffer)
+            .map_err(at_command::command_too_long)?;
+        self.writer.write(data).map_err(|_e| AtError::IOError)?;
+
+        let mut read_buffer = [0; BUFFER_SIZE];
+        let response_size = self.read_response(&mut read_buffer)?;
+        // The modem sends a space after the prompt
+        let start = read_buffer[..response_size]
+            .iter()
+            .take_while(|byte| **byte == b' ')
+            .count();
+        payload.parse_response_struct(&read_buffer[start..response_size])
+    }
+
+    /// Sends the header of the request followed by its data, which is written from the caller's
+    /// buffer without being copied
+    pub fn send_streamed<V: StreamedRequest>(
+        &mut self,
+        payload: &V,
+    ) -> Result<V::Response, AtError> {
+        let mut buffer = [0; BUFFER_SIZE];
+        let header = payload
+            .get_header(&mut buffer)
+            .map_err(at_command::command_too_long)?;
+        at_command::check_line_length(
+            header.len() + payload.data().len() + payload.trailer().len(),
+        )?;
+
+        #[cfg(feature = "defmt")]
+        debug!("sending header: {=[u8]:a}", header);
+        self.writer
+            .write_all(header)
+            .map_err(|_e| AtError::IOError)?;
+        self.writer
+            .write_all(payload.data())
+            .map_err(|_e| AtError::IOError)?;
+        self.writer
+            .write_all(payload.trailer())
+            .map_err(|_e| AtError::IOError)?;
+
+        let mut read_buffer = [0; BUFFER_SIZE];
+        let response_size = self.read_response(&mut read_buffer)?;
+        payload.parse_response_struct(&read_buffer[..response_size])
+    }
+
+    /// Reads until the `>` prompt, failing if the modem replies an error instead or the prompt
+    /// does not arrive within `timeout_ms`
+    fn read_prompt<C: Timer>(&mut self, timeout_ms: u64, timer: &C) -> Result<(), AtError> {
+        let start = timer.now_ms();
+        // Only the start of long lines is kept, enough to tell whether they are an error
+        let mut line = [0; 32];
+        let mut line_size = 0;
+        let mut byte = [0; 1];
+        loop {
+            if timer.now_ms().saturating_sub(start) >= timeout_ms {
+                return Err(AtError::Timeout);
+            }
+            if self.read_bytes(&mut byte)? == 0 {
+                continue;
+            }
+
+            match byte[0] {
+                b'>' => return Ok(()),
+                LF => {
+                    let received = &line[..line_size];
+                    if at_command::strip_error(received).is_some_and(<[u8]>::is_empty) {
+                        return Err(AtError::ErrorReply(0));
+                    }
+                    if let Some(code) = at_command::cmee::parse_cme_error(received) {
+                        return Err(code.map_or(AtError::ErrorReply(0), AtError::Cme));
+                    }
+                    line_size = 0;
+                }
+                byte if line_size < line.len() => {
+                    line[line_size] = byte;
+                    line_size += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
     #[deprecated(since = "3.0.0", note = "Use the send_and_wait_response")]
     #[allow(deprecated)]
     pub fn send_and_wait_reply<'b, V: AtRequest + 'b>(
         &'b mut self,
         payload: &V,
     ) -> Result<AtResponse, AtError> {
+        payload.validate()?;
         let mut buffer = [0; BUFFER_SIZE];
-        let data = payload.get_command_no_error(&mut buffer);
+        let data = payload
+            .get_command(&mut buffer)
+            .map_err(at_command::command_too_long)?;
 
         #[cfg(feature = "defmt")]
         debug!("sending command: {=[u8]:a}", data);
@@ -165,14 +518,190 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
         }
     }
 
+    /// Reads lines until a URC of the given kind arrives or `timeout_ms` elapsed. URCs of other
+    /// kinds are kept and can be retrieved with [Modem::pop_pending_urc], any other line, as
+    /// well as a URC which cannot be parsed, is discarded. The timeout is checked between lines,
+    /// a reader blocking forever will block this call as well.
+    pub fn wait_for_urc<C: Timer>(
+        &mut self,
+        kind: UrcKind,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<Urc, AtError> {
+        self.wait_for_urc_matching(|urc| urc.kind() == kind, timeout_ms, timer)
+    }
+
+    /// Same as [Modem::wait_for_urc], for the first URC accepted by `matches`, e.g. the data
+    /// of a given socket
+    pub fn wait_for_urc_matching<C: Timer>(
+        &mut self,
+        mut matches: impl FnMut(&Urc) -> bool,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<Urc, AtError> {
+        if let Some(urc) = self.take_pending_urc(&mut matches) {
+            return Ok(urc);
+        }
+
+        let start = timer.now_ms();
+        let deadline = start.saturating_add(timeout_ms);
+        let mut line = [0; BUFFER_SIZE];
+        loop {
+            if timer.now_ms().saturating_sub(start) >= timeout_ms {
+                return Err(AtError::Timeout);
+            }
+
+            let mut line_size = self.read_line(&mut line, deadline, timer)?;
+            if line[..line_size].starts_with(urc::CMT_PREFIX) && line_size < BUFFER_SIZE {
+                line[line_size] = LF;
+                line_size += 1;
+                line_size += self.read_line(&mut line[line_size..], deadline, timer)?;
+            }
+            match Urc::parse(&line[..line_size]) {
+                Ok(Some(urc)) if matches(&urc) => return Ok(urc),
+                // Dropping it would lose a notification the caller may still need
+                Ok(Some(urc)) => self.keep_urc(urc),
+                Ok(None) => {
+                    #[cfg(feature = "defmt")]
+                    debug!("discarding line: {=[u8]:a}", line[..line_size]);
+                }
+                Err(_) => {
+                    #[cfg(feature = "defmt")]
+                    warn!("discarding malformed URC: {=[u8]:a}", line[..line_size]);
+                }
+            }
+        }
+    }
+
+    /// Follows the progress of an update started with
+    /// [StartFirmwareUpdate](at_command::firmware::StartFirmwareUpdate) until the image is being
+    /// applied. The modem reboots afterwards, so it has to be initialized again, e.g. with
+    /// [Modem::new], once it is back.
+    pub fn wait_for_firmware_update<C: Timer>(
+        &mut self,
+        timeout_ms: u64,
+        timer: &C,
+    ) -> Result<(), AtError> {
+        let start = timer.now_ms();
+        loop {
+            let remaining = timeout_ms.saturating_sub(timer.now_ms().saturating_sub(start));
+            if let Urc::FirmwareUpdate(progress) =
+                self.wait_for_urc(UrcKind::FirmwareUpdate, remaining, timer)?
+            {
+                match progress.state {
+                    FirmwareUpdateState::Applying => return Ok(()),
+                    FirmwareUpdateState::Failed => return Err(AtError::FirmwareUpdateFailed),
+                    _ => {
+                        #[cfg(feature = "defmt")]
+                        info!("firmware update: {}", progress);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the oldest URC received while waiting for a different one
+    pub fn pop_pending_urc(&mut self) -> Option<Urc> {
+        self.pending_urcs.as_mut()?.pop_front()
+    }
+
+    /// Removes the oldest pending URC accepted by `matches`, keeping the order of the rest
+    pub(crate) fn take_pending_urc(
+        &mut self,
+        mut matches: impl FnMut(&Urc) -> bool,
+    ) -> Option<Urc> {
+        let queue = self.pending_urcs.as_mut()?;
+        let mut found = None;
+        for _ in 0..queue.storage_len() {
+            let urc = queue.pop_front()?;
+            if found.is_none() && matches(&urc) {
+                found = Some(urc);
+            } else {
+                // There is room as we just removed an element
+                let _ = queue.push_back(urc);
+            }
+        }
+        found
+    }
+
+    /// Reads a single non empty line, the line terminator is not included. A reader returning
+    /// nothing, e.g. at its end, fails with [AtError::Timeout] once `deadline_ms` passed.
+    fn read_line<C: Timer>(
+        &mut self,
+        line_out: &mut [u8],
+        deadline_ms: u64,
+        timer: &C,
+    ) -> Result<usize, AtError> {
+        let mut line_size = 0_usize;
+        let mut byte = [0_u8; 1];
+        loop {
+            if self.read_bytes(&mut byte)? == 0 {
+                if timer.now_ms() >= deadline_ms {
+                    return Err(AtError::Timeout);
+                }
+                continue;
+            }
+            match byte[0] {
+                CR => continue,
+                LF if line_size == 0 => continue,
+                LF => return Ok(line_size),
+                b => {
+                    if line_size == line_out.len() {
+                        return Err(AtError::CapacityError);
+                    }
+                    line_out[line_size] = b;
+                    line_size += 1;
+                }
+            }
+        }
+    }
+
+    /// Reads from the bytes kept by a previous read first, then from the reader
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> Result<usize, AtError> {
+        if !self.unread.is_empty() {
+            let unread = core::iter::from_fn(|| self.unread.pop_front());
+            let mut len = 0;
+            for (slot, byte) in buffer.iter_mut().zip(unread) {
+                *slot = byte;
+                len += 1;
+            }
+            return Ok(len);
+        }
+
+        self.reader.read(buffer).map_err(|_e| {
+            #[cfg(feature = "defmt")]
+            error!("uart error {}", _e.kind());
+            AtError::NotReady
+        })
+    }
+
+    /// Keeps the bytes read past a response so they are the next ones read
+    fn keep_unread(&mut self, data: &[u8]) {
+        for byte in data.iter().rev() {
+            // Reads are at most as large as the capacity and the kept bytes were drained
+            // before reading them
+            let _ = self.unread.push_front(*byte);
+        }
+    }
+
     pub fn read_response(
         &mut self,
         response_out: &mut [u8; BUFFER_SIZE],
+    ) -> Result<usize, AtError> {
+        self.read_response_until(response_out, |_| false)
+    }
+
+    /// Same as [Modem::read_response], a line accepted by `is_final` ends the response as
+    /// well and is included in it
+    fn read_response_until(
+        &mut self,
+        response_out: &mut [u8; BUFFER_SIZE],
+        is_final: impl Fn(&[u8]) -> bool,
     ) -> Result<usize, AtError> {
         let mut offset = 0_usize;
-        let mut read_buffer: [u8; 100] = [0; 100];
+        let mut read_buffer = [0; READ_CHUNK_SIZE];
         loop {
-            match self.reader.read(&mut read_buffer) {
+            match self.read_bytes(&mut read_buffer) {
                 Ok(num_bytes) => {
                     for i in 0..num_bytes {
                         response_out[offset + i] = read_buffer[i];
@@ -188,6 +717,7 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
 
                         match &response_out[start..stop] {
                             OK_TERMINATOR => {
+                                self.keep_unread(&read_buffer[i + 1..num_bytes]);
                                 return {
                                     #[cfg(feature = "defmt")]
                                     trace!(
@@ -195,9 +725,10 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
                                         response_out[..offset + i + 5]
                                     );
                                     Ok(offset + i)
-                                }
+                                };
                             }
                             ERROR_TERMINATOR => {
+                                self.keep_unread(&read_buffer[i + 1..num_bytes]);
                                 #[cfg(feature = "defmt")]
                                 error!(
                                     "received ERROR response: {=[u8]:a}",
@@ -205,6 +736,32 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
                                 );
                                 return Err(AtError::ErrorReply(offset + i));
                             }
+                            _ if read_buffer[i] == LF => {
+                                let line_start = response_out[..offset + i]
+                                    .iter()
+                                    .rposition(|byte| *byte == LF)
+                                    .map_or(0, |position| position + 1);
+                                if let Some(code) = at_command::cmee::parse_cme_error(
+                                    &response_out[line_start..stop],
+                                ) {
+                                    self.keep_unread(&read_buffer[i + 1..num_bytes]);
+                                    #[cfg(feature = "defmt")]
+                                    error!(
+                                        "received CME ERROR response: {=[u8]:a}",
+                                        response_out[..stop]
+                                    );
+                                    return Err(code
+                                        .map_or(AtError::ErrorReply(offset + i), |code| {
+                                            AtError::Cme(code)
+                                        }));
+                                }
+                                if is_final(at_command::trim_line_breaks(
+                                    &response_out[line_start..stop],
+                                )) {
+                                    self.keep_unread(&read_buffer[i + 1..num_bytes]);
+                                    return Ok(stop);
+                                }
+                            }
                             _ =>
                             {
                                 #[cfg(feature = "defmt")]
@@ -216,12 +773,460 @@ impl<'a, T: Write, U: Read> Modem<'a, T, U> {
                     offset += num_bytes;
                 }
 
-                Err(_e) => {
-                    #[cfg(feature = "defmt")]
-                    error!("uart error {}", _e.kind());
-                    return Err(AtError::NotReady);
-                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockDelay, MockReader, MockTimer, MockWriter};
+
+    #[test]
+    fn test_wait_for_urc_skips_noise() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSQ: 20,99\r\n\r\n+CSONMI: 1,2,AB\r\n\r\n+CSNTP: 24/10/14,08:01:02\r\n",
+        );
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(1);
+
+        let urc = modem
+            .wait_for_urc(UrcKind::NtpSynchronized, 1000, &timer)
+            .unwrap();
+        assert_eq!(urc.kind(), UrcKind::NtpSynchronized);
+
+        // The socket data arrived first but must not be lost
+        match modem.pop_pending_urc() {
+            Some(Urc::SocketData(data)) => assert_eq!(data.socket_id, 1),
+            _ => panic!("Expected pending Urc::SocketData"),
+        }
+        assert!(modem.pop_pending_urc().is_none());
+    }
+
+    #[test]
+    fn test_wait_for_urc_keeps_long_socket_data() {
+        let mut response = std::vec::Vec::from(&b"\r\nOK\r\n\r\n+CSONMI: 1,400,"[..]);
+        response.extend_from_slice(&[b'A'; 400]);
+        response.extend_from_slice(b"\r\n");
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(&response);
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+
+        // 200 bytes, hex encoded
+        match modem.wait_for_urc(UrcKind::SocketData, 1000, &timer) {
+            Ok(Urc::SocketData(data)) => {
+                assert_eq!(data.data_len, 400);
+                assert_eq!(data.data.as_slice(), &[b'A'; 400]);
             }
+            _ => panic!("Expected Urc::SocketData"),
         }
     }
+
+    #[test]
+    fn test_wait_for_urc_skips_malformed_urc() {
+        let mut writer = MockWriter::default();
+        let mut reader =
+            MockReader::new(b"\r\nOK\r\n\r\n+CSONMI: 1\r\n\r\n+CSNTP: 24/10/14,08:01:02\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+
+        let urc = modem
+            .wait_for_urc(UrcKind::NtpSynchronized, 1000, &timer)
+            .unwrap();
+        assert_eq!(urc.kind(), UrcKind::NtpSynchronized);
+        assert_eq!(modem.dropped_urcs(), 0);
+    }
+
+    #[test]
+    fn test_wait_for_urc_counts_dropped_urcs() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSONMI: 1,2,AB\r\n\r\n+CSONMI: 2,2,CD\r\n\r\n+CSNTP: 24/10/14,08:01:02\r\n",
+        );
+        let mut urcs = heapless::Deque::<Urc, 1>::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(1);
+
+        modem
+            .wait_for_urc(UrcKind::NtpSynchronized, 1000, &timer)
+            .unwrap();
+        assert_eq!(modem.dropped_urcs(), 1);
+        match modem.pop_pending_urc() {
+            Some(Urc::SocketData(data)) => assert_eq!(data.socket_id, 1),
+            _ => panic!("Expected pending Urc::SocketData"),
+        }
+    }
+
+    #[test]
+    fn test_modem_without_urc_queue_is_small() {
+        assert!(
+            core::mem::size_of::<Modem<'_, MockWriter, MockReader>>() < core::mem::size_of::<Urc>()
+        );
+    }
+
+    #[test]
+    fn test_wait_for_urc_reads_message_line() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CMT: \"+34600111222\",,\"24/01/01,12:00:00+04\"\r\nHello\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+
+        match modem.wait_for_urc(UrcKind::MessageReceived, 1000, &timer) {
+            Ok(Urc::MessageReceived(message)) => assert_eq!(message.data.as_slice(), b"Hello"),
+            _ => panic!("Expected Urc::MessageReceived"),
+        }
+    }
+
+    #[test]
+    fn test_cme_error_reply() {
+        use crate::at_command::cmee::CmeError;
+
+        let mut writer = MockWriter::default();
+        // The error replaces the final result code, no OK follows it
+        let mut reader =
+            MockReader::new(b"\r\nOK\r\n\r\n+CME ERROR: 10\r\n\r\n214011234567890\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        match modem.send_and_wait_response(&at_command::sim::QueryImsi) {
+            Err(AtError::Cme(code)) => assert_eq!(CmeError::from(code), CmeError::SimNotInserted),
+            _ => panic!("Expected AtError::Cme"),
+        }
+        // The next response is not affected
+        let imsi = modem
+            .send_and_wait_response(&at_command::sim::QueryImsi)
+            .unwrap();
+        assert_eq!(imsi.as_str(), "214011234567890");
+    }
+
+    #[test]
+    fn test_measure_latency() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nOK\r\n\r\nERROR\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(35);
+
+        assert_eq!(modem.measure_latency(&timer).unwrap(), 35);
+        assert!(modem.writer.written.ends_with(b"AT\r\n"));
+        assert!(matches!(
+            modem.measure_latency(&timer),
+            Err(AtError::ErrorReply(_))
+        ));
+    }
+
+    #[test]
+    fn test_urc_between_response_and_ok() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CSOC: 1\r\n\r\n+CEREG: 1,\"1A2B\",\"01A2B3C4\",9\r\n\r\nOK\r\n",
+        );
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+
+        let created = modem
+            .send_and_wait_response(&at_command::socket::CreateSocket {
+                domain: at_command::socket::Domain::IPv4,
+                connection_type: at_command::socket::Type::TCP,
+                protocol: at_command::socket::Protocol::IP,
+                cid: None,
+            })
+            .unwrap();
+        assert_eq!(created.socket_id, 1);
+        assert!(matches!(
+            modem.pop_pending_urc(),
+            Some(Urc::EpsRegistration(_))
+        ));
+        assert_eq!(modem.pop_pending_urc(), None);
+    }
+
+    #[test]
+    fn test_socket_error_in_place_of_data_is_not_a_urc() {
+        let mut writer = MockWriter::default();
+        let mut reader =
+            MockReader::new(b"\r\nOK\r\n\r\n+CSONMI: 2,2,AB\r\n\r\n+CSOERR: 1,104\r\n\r\nOK\r\n");
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+
+        assert!(matches!(
+            modem.send_and_wait_response(&at_command::socket::ReceiveSocketData {
+                socket_id: 1,
+                max_len: 64,
+            }),
+            Err(AtError::Socket(
+                at_command::socket::SocketError::ConnectionReset
+            ))
+        ));
+        assert!(matches!(
+            modem.pop_pending_urc(),
+            Some(Urc::SocketData(data)) if data.socket_id == 2
+        ));
+    }
+
+    #[test]
+    fn test_response_with_urc_prefix_is_kept() {
+        let mut response = *b"\r\n+CEREG: 0,1\r\n\r\n+CSONMI: 1,4,AB01\r\n\r\nOK\r\n";
+        let mut urcs = 0;
+        let size = urc::extract_interleaved_urcs(
+            b"AT+CEREG?\r\n",
+            &mut response,
+            |_| false,
+            |_| urcs += 1,
+        );
+        assert_eq!(&response[..size], b"\r\n+CEREG: 0,1\r\n\r\n\r\nOK\r\n");
+        assert_eq!(urcs, 1);
+    }
+
+    #[test]
+    fn test_response_and_urc_in_the_same_read() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::with_chunk_size(
+            b"\r\nOK\r\n\r\n+CSOC: 1\r\n\r\nOK\r\n\r\n+CSONMI: 1,4,AB01\r\n",
+            100,
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+
+        let created = modem
+            .send_and_wait_response(&at_command::socket::CreateSocket {
+                domain: at_command::socket::Domain::IPv4,
+                connection_type: at_command::socket::Type::TCP,
+                protocol: at_command::socket::Protocol::IP,
+                cid: None,
+            })
+            .unwrap();
+        assert_eq!(created.socket_id, 1);
+
+        match modem.wait_for_urc(UrcKind::SocketData, 1000, &timer) {
+            Ok(Urc::SocketData(data)) => {
+                assert_eq!(data.socket_id, 1);
+                assert_eq!(data.data.as_slice(), b"AB01");
+            }
+            _ => panic!("Expected Urc::SocketData"),
+        }
+    }
+
+    #[test]
+    fn test_wait_for_urc_uses_pending() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CSONMI: 1,2,AB\r\n\r\n+CSOERR: 1,4\r\n");
+        let mut urcs = UrcQueue::new();
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_urc_queue(&mut urcs);
+        let timer = MockTimer::new(1);
+
+        let urc = modem
+            .wait_for_urc(UrcKind::SocketError, 1000, &timer)
+            .unwrap();
+        assert_eq!(urc.kind(), UrcKind::SocketError);
+
+        // Already received, no further read is needed
+        let urc = modem
+            .wait_for_urc(UrcKind::SocketData, 1000, &timer)
+            .unwrap();
+        assert_eq!(urc.kind(), UrcKind::SocketData);
+    }
+
+    #[test]
+    fn test_after_power_on_waits_for_ready() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nRDY\r\n\r\n+CFUN: 1\r\n\r\nOK\r\n\r\nOK\r\n");
+        let timer = MockTimer::new(10);
+
+        let mut modem = Modem::after_power_on(&mut writer, &mut reader, 5000, &timer).unwrap();
+        assert_eq!(modem.writer.written, b"AT\r\nATE0\r\n");
+        // No queue is set yet
+        assert_eq!(modem.pop_pending_urc(), None);
+        assert_eq!(modem.dropped_urcs(), 1);
+    }
+
+    #[test]
+    fn test_wait_until_ready_polls_at() {
+        let mut writer = MockWriter::default();
+        // The reader times out while the modem is off and it never sends RDY
+        let mut reader = MockReader::after_silence(b"\r\nOK\r\n\r\nOK\r\n", 3);
+        let timer = MockTimer::new(10);
+
+        Modem::after_power_on(&mut writer, &mut reader, 5000, &timer).unwrap();
+        assert_eq!(writer.written, b"AT\r\nAT\r\nATE0\r\n");
+    }
+
+    #[test]
+    fn test_wait_until_ready_timeout() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"");
+        let timer = MockTimer::new(100);
+
+        assert!(matches!(
+            Modem::after_power_on(&mut writer, &mut reader, 1000, &timer),
+            Err(AtError::Timeout)
+        ));
+        assert!(writer.written.starts_with(b"AT\r\nAT\r\n"));
+    }
+
+    #[test]
+    fn test_command_longer_than_buffer() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        // Accepted by the modem but larger than the command buffer
+        let command = [b'A'; at_command::MAX_COMMAND_LINE_LENGTH - 2];
+        assert!(matches!(
+            modem.send_and_wait_response(&at_command::raw::RawCommand::new(&command)),
+            Err(AtError::CapacityError)
+        ));
+
+        let command = [b'A'; at_command::MAX_COMMAND_LINE_LENGTH - 1];
+        assert!(matches!(
+            modem.send_and_wait_response(&at_command::raw::RawCommand::new(&command)),
+            Err(AtError::BufferOverflow)
+        ));
+        assert_eq!(modem.writer.written, b"ATE0\r\n");
+    }
+
+    #[test]
+    fn test_streamed_command_line_length() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+
+        // `AT+CSOSEND=1,1060,` is 18 bytes long, plus the terminator
+        let data = [b'A'; 1061];
+        let longest = at_command::socket::SendSocketMessage {
+            socket_id: 1,
+            data_len: 1060,
+            data: &data[..1060],
+        };
+        modem.send_streamed(&longest).unwrap();
+        assert_eq!(
+            modem.writer.written.len() - b"ATE0\r\n".len(),
+            at_command::MAX_COMMAND_LINE_LENGTH
+        );
+
+        let sent = modem.writer.written.len();
+        let too_long = at_command::socket::SendSocketMessage {
+            socket_id: 1,
+            data_len: 1061,
+            data: &data,
+        };
+        assert!(matches!(
+            modem.send_streamed(&too_long),
+            Err(AtError::BufferOverflow)
+        ));
+        assert_eq!(modem.writer.written.len(), sent);
+    }
+
+    #[test]
+    fn test_wait_for_urc_timeout() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(100);
+
+        let result = modem.wait_for_urc(UrcKind::SocketData, 250, &timer);
+        assert!(matches!(result, Err(AtError::Timeout)));
+    }
+
+    #[test]
+    fn test_wait_for_urc_timeout_on_empty_reads() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::then_empty(b"\r\nOK\r\n\r\n+CSQ: 20");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(100);
+
+        let result = modem.wait_for_urc(UrcKind::SocketData, 250, &timer);
+        assert!(matches!(result, Err(AtError::Timeout)));
+    }
+
+    #[test]
+    fn test_wait_for_firmware_update() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(
+            b"\r\nOK\r\n\r\n+CFOTA: 1,50\r\n\r\n+CFOTA: 1,100\r\n\r\n+CFOTA: 2\r\n\r\n+CFOTA: 3\r\n",
+        );
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+
+        modem.wait_for_firmware_update(1000, &timer).unwrap();
+    }
+
+    #[test]
+    fn test_wait_for_failed_firmware_update() {
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\n+CFOTA: 1,50\r\n\r\n+CFOTA: 4\r\n");
+        let mut modem = Modem::new(&mut writer, &mut reader).unwrap();
+        let timer = MockTimer::new(1);
+
+        assert!(matches!(
+            modem.wait_for_firmware_update(1000, &timer),
+            Err(AtError::FirmwareUpdateFailed)
+        ));
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    #[allow(deprecated)]
+    fn test_types_implement_defmt_format() {
+        fn assert_format<T: defmt::Format>() {}
+
+        assert_format::<AtError>();
+        assert_format::<AtResponse>();
+        assert_format::<Urc>();
+        assert_format::<at_command::socket::CreateSocket>();
+        assert_format::<at_command::socket::ConnectSocketToRemote<'static>>();
+        assert_format::<at_command::socket::SendSocketMessage<'static>>();
+        assert_format::<at_command::socket::CloseSocket>();
+        assert_format::<at_command::socket::SocketStatus>();
+        assert_format::<at_command::network_information::NetworkInformationState>();
+        assert_format::<at_command::ntp::NtpSynchronized>();
+    }
+
+    #[test]
+    fn test_rate_limited_functionality_is_delayed() {
+        use crate::at_command::functionality::{Functionality, SetFunctionality};
+        use crate::rate_limit::CommandKind;
+
+        let mut writer = MockWriter::default();
+        let mut reader = MockReader::new(b"\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n\r\nOK\r\n");
+        let timer = MockTimer::new(10);
+        let mut delay = MockDelay::new(&timer);
+        let mut limiter = RateLimiter::new().with_interval(CommandKind::Functionality, 1000);
+        let mut modem = Modem::new(&mut writer, &mut reader)
+            .unwrap()
+            .with_rate_limiter(&mut limiter, &timer, &mut delay);
+        let reset = SetFunctionality {
+            functionality: Functionality::Full,
+            reset: true,
+        };
+
+        modem.send_and_wait_response(&reset).unwrap();
+        // Other commands are not delayed
+        modem.ready().unwrap();
+        modem.send_and_wait_response(&reset).unwrap();
+        drop(modem);
+
+        assert_eq!(delay.delays, [990]);
+        assert_eq!(
+            limiter.last_issued_ms(CommandKind::Functionality),
+            Some(1010)
+        );
+        assert!(writer
+            .written
+            .ends_with(b"AT+CFUN=1,1\r\nAT\r\nAT+CFUN=1,1\r\n"));
+    }
 }
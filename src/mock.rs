@@ -0,0 +1,163 @@
+//! In-memory serial port and timer used to drive the [Modem](crate::Modem) in tests.
+
+use crate::{Delay, Timer};
+use core::cell::Cell;
+use embedded_io::{ErrorKind, ErrorType, Read, Write};
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+/// Records every byte the modem writes
+#[derive(Default)]
+pub(crate) struct MockWriter {
+    pub(crate) written: Vec<u8>,
+}
+
+impl ErrorType for MockWriter {
+    type Error = ErrorKind;
+}
+
+impl Write for MockWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Replays the given bytes to the modem one at a time, like a UART without FIFO, and errors
+/// once they are exhausted unless created with [MockReader::then_empty]
+pub(crate) struct MockReader {
+    pending: VecDeque<u8>,
+    chunk_size: usize,
+    silent_reads: usize,
+    empty_when_exhausted: bool,
+}
+
+impl MockReader {
+    pub(crate) fn new(data: &[u8]) -> Self {
+        Self::with_chunk_size(data, 1)
+    }
+
+    /// Returns up to `chunk_size` bytes on each read, like a UART with FIFO
+    pub(crate) fn with_chunk_size(data: &[u8], chunk_size: usize) -> Self {
+        Self {
+            pending: data.iter().copied().collect(),
+            chunk_size,
+            silent_reads: 0,
+            empty_when_exhausted: false,
+        }
+    }
+
+    /// Errors on the first `reads` reads, like a UART read timing out while the modem is
+    /// still off
+    pub(crate) fn after_silence(data: &[u8], reads: usize) -> Self {
+        Self {
+            silent_reads: reads,
+            ..Self::new(data)
+        }
+    }
+
+    /// Returns empty reads once the bytes are exhausted, like a reader at its end
+    pub(crate) fn then_empty(data: &[u8]) -> Self {
+        Self {
+            empty_when_exhausted: true,
+            ..Self::new(data)
+        }
+    }
+}
+
+impl ErrorType for MockReader {
+    type Error = ErrorKind;
+}
+
+impl Read for MockReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.silent_reads > 0 {
+            self.silent_reads -= 1;
+            return Err(ErrorKind::TimedOut);
+        }
+        if self.pending.is_empty() {
+            if self.empty_when_exhausted {
+                return Ok(0);
+            }
+            return Err(ErrorKind::Other);
+        }
+
+        let len = buf.len().min(self.chunk_size).min(self.pending.len());
+        for slot in &mut buf[..len] {
+            // Checked above
+            *slot = self.pending.pop_front().unwrap();
+        }
+        Ok(len)
+    }
+}
+
+/// Timer that advances by a fixed step every time it is read
+pub(crate) struct MockTimer {
+    now: Cell<u64>,
+    step_ms: u64,
+}
+
+impl MockTimer {
+    pub(crate) fn new(step_ms: u64) -> Self {
+        Self {
+            now: Cell::new(0),
+            step_ms,
+        }
+    }
+}
+
+impl Timer for MockTimer {
+    fn now_ms(&self) -> u64 {
+        let now = self.now.get();
+        self.now.set(now + self.step_ms);
+        now
+    }
+}
+
+/// Records the delays and advances the timer by them instead of blocking
+pub(crate) struct MockDelay<'a> {
+    timer: &'a MockTimer,
+    pub(crate) delays: Vec<u64>,
+}
+
+impl<'a> MockDelay<'a> {
+    pub(crate) fn new(timer: &'a MockTimer) -> Self {
+        Self {
+            timer,
+            delays: Vec::new(),
+        }
+    }
+}
+
+impl Delay for MockDelay<'_> {
+    fn delay_ms(&mut self, ms: u64) {
+        self.delays.push(ms);
+        self.timer.now.set(self.timer.now.get() + ms);
+    }
+}
+
+/// Discards the logs, linking defmt requires a global logger
+#[cfg(feature = "defmt")]
+#[defmt::global_logger]
+struct DiscardLogger;
+
+#[cfg(feature = "defmt")]
+unsafe impl defmt::Logger for DiscardLogger {
+    fn acquire() {}
+
+    unsafe fn flush() {}
+
+    unsafe fn release() {}
+
+    unsafe fn write(_bytes: &[u8]) {}
+}
+
+#[cfg(feature = "defmt")]
+defmt::timestamp!("");
@@ -46,8 +46,11 @@ impl<'a, T: Write, U: Read> AsyncModem<T, U> {
         &'a mut self,
         payload: V,
     ) -> Result<V::Response, crate::AtError> {
+        payload.validate()?;
         let mut buffer = [0; BUFFER_SIZE];
-        let data = payload.get_command_no_error(&mut buffer);
+        let data = payload
+            .get_command(&mut buffer)
+            .map_err(at_command::command_too_long)?;
         #[cfg(feature = "defmt")]
         debug!("payload: {=[u8]:a}", &data);
         self.writer
@@ -58,7 +61,7 @@ impl<'a, T: Write, U: Read> AsyncModem<T, U> {
 
         #[cfg(feature = "defmt")]
         debug!("received response: {=[u8]:a}", buffer[..response_size]);
-        let response = payload.parse_response_struct(&buffer);
+        let response = payload.parse_response_struct(&buffer[..response_size]);
         #[cfg(feature = "defmt")]
         debug!("parsed response: {}", response);
         response
@@ -70,8 +73,11 @@ impl<'a, T: Write, U: Read> AsyncModem<T, U> {
         &'a mut self,
         payload: V,
     ) -> Result<AtResponse, crate::AtError> {
+        payload.validate()?;
         let mut buffer = [0; BUFFER_SIZE];
-        let data = payload.get_command_no_error(&mut buffer);
+        let data = payload
+            .get_command(&mut buffer)
+            .map_err(at_command::command_too_long)?;
         #[cfg(feature = "defmt")]
         debug!("payload: {=[u8]:a}", &data);
         self.writer.write(data).await.unwrap();
@@ -0,0 +1,130 @@
+//! Minimum interval between commands the modem rejects with busy errors when issued too often
+
+use crate::{Delay, Timer};
+
+/// Kind of command that has to be spaced out, see
+/// [AtRequest::command_kind](crate::at_command::AtRequest::command_kind)
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandKind {
+    /// `AT+CFUN`, e.g. a reset
+    Functionality = 0,
+    /// `AT+CGATT`
+    Attach = 1,
+}
+
+const KINDS: usize = 2;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Guard {
+    min_interval_ms: u64,
+    last_issued_ms: Option<u64>,
+}
+
+/// Keeps the time each kind of command was last issued, set on the modem with
+/// [Modem::with_rate_limiter](crate::Modem::with_rate_limiter). Kinds without an interval are
+/// never delayed.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    guards: [Guard; KINDS],
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum time between two commands of the given kind
+    pub fn with_interval(mut self, kind: CommandKind, min_interval_ms: u64) -> Self {
+        self.guards[kind as usize].min_interval_ms = min_interval_ms;
+        self
+    }
+
+    /// Time the given kind of command was last issued, as reported by the timer
+    pub fn last_issued_ms(&self, kind: CommandKind) -> Option<u64> {
+        self.guards[kind as usize].last_issued_ms
+    }
+
+    /// Time left at `now_ms` before the given kind of command can be issued
+    fn remaining_ms(&self, kind: CommandKind, now_ms: u64) -> u64 {
+        let guard = &self.guards[kind as usize];
+        match guard.last_issued_ms {
+            Some(last) => guard
+                .min_interval_ms
+                .saturating_sub(now_ms.saturating_sub(last)),
+            None => 0,
+        }
+    }
+
+    fn record(&mut self, kind: CommandKind, now_ms: u64) {
+        self.guards[kind as usize].last_issued_ms = Some(now_ms);
+    }
+}
+
+/// [RateLimiter] with the clock used to space out the commands sent by the modem
+pub(crate) struct ActiveRateLimiter<'a> {
+    pub(crate) limiter: &'a mut RateLimiter,
+    pub(crate) timer: &'a dyn Timer,
+    pub(crate) delay: &'a mut dyn Delay,
+}
+
+impl ActiveRateLimiter<'_> {
+    /// Delays until the command can be issued and records it as issued
+    pub(crate) fn acquire(&mut self, kind: CommandKind) {
+        loop {
+            let now = self.timer.now_ms();
+            match self.limiter.remaining_ms(kind, now) {
+                0 => {
+                    self.limiter.record(kind, now);
+                    return;
+                }
+                remaining => self.delay.delay_ms(remaining),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock::{MockDelay, MockTimer};
+
+    #[test]
+    fn test_rate_limiter_does_not_delay_other_kinds() {
+        let timer = MockTimer::new(10);
+        let mut delay = MockDelay::new(&timer);
+        let mut limiter = RateLimiter::new().with_interval(CommandKind::Functionality, 1000);
+        let mut active = ActiveRateLimiter {
+            limiter: &mut limiter,
+            timer: &timer,
+            delay: &mut delay,
+        };
+
+        active.acquire(CommandKind::Attach);
+        active.acquire(CommandKind::Functionality);
+        active.acquire(CommandKind::Attach);
+        assert!(delay.delays.is_empty());
+        assert_eq!(limiter.last_issued_ms(CommandKind::Attach), Some(20));
+        assert_eq!(limiter.last_issued_ms(CommandKind::Functionality), Some(10));
+    }
+
+    #[test]
+    fn test_rate_limiter_delays_for_the_remaining_time() {
+        let timer = MockTimer::new(10);
+        let mut delay = MockDelay::new(&timer);
+        let mut limiter = RateLimiter::new().with_interval(CommandKind::Functionality, 1000);
+        let mut active = ActiveRateLimiter {
+            limiter: &mut limiter,
+            timer: &timer,
+            delay: &mut delay,
+        };
+
+        active.acquire(CommandKind::Functionality);
+        active.acquire(CommandKind::Functionality);
+        assert_eq!(delay.delays, [990]);
+        assert_eq!(
+            limiter.last_issued_ms(CommandKind::Functionality),
+            Some(1010)
+        );
+    }
+}
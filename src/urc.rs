@@ -0,0 +1,404 @@
+//! Unsolicited result codes (URC) sent by the modem without a previous request
+
+use crate::at_command::at_cpin::PinStatus;
+use crate::at_command::call::CallProgress;
+use crate::at_command::clock::TimeZoneChanged;
+use crate::at_command::firmware::FirmwareUpdateProgress;
+use crate::at_command::functionality::Functionality;
+use crate::at_command::mqtt::{MqttDisconnected, MqttMessageReceived};
+use crate::at_command::network_registration_status::EpsRegistrationReport;
+use crate::at_command::nidd::NonIpDataReceived;
+use crate::at_command::ntp::NtpSynchronized;
+use crate::at_command::radio::EngineeringReport;
+use crate::at_command::sms::{MessageReceived, NewMessageStored};
+use crate::at_command::socket::{
+    SocketDataReceived, SocketErrorReport, SocketReconnect, SocketSendAcknowledged,
+};
+use crate::at_command::trim_line_breaks;
+use crate::at_command::ussd::UssdReply;
+use crate::AtError;
+
+/// Header of the only URC whose payload is sent on the next line
+pub(crate) const CMT_PREFIX: &[u8] = b"+CMT: ";
+
+/// Kind of a [Urc], used to select which URC has to be awaited
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrcKind {
+    /// `+CSONMI`
+    SocketData,
+    /// `+CSOERR`
+    SocketError,
+    /// `+CSOSEND`
+    SocketSendAcknowledged,
+    /// `+CSORECONN`
+    SocketReconnect,
+    /// `+CMQPUB`
+    MqttMessage,
+    /// `+CMQDISCON`
+    MqttDisconnected,
+    /// `+CSNTP`
+    NtpSynchronized,
+    /// `+CEREG`
+    EpsRegistration,
+    /// `+CFOTA`
+    FirmwareUpdate,
+    /// `+CTZV`
+    TimeZone,
+    /// `+CMTI`
+    NewMessageStored,
+    /// `+CMT`
+    MessageReceived,
+    /// `RDY`
+    Ready,
+    /// `+CFUN`
+    Functionality,
+    /// `+CPIN`
+    SimStatus,
+    /// `RING`, `NO CARRIER`, `BUSY` and `NO ANSWER`
+    CallProgress,
+    /// `+CUSD`
+    Ussd,
+    /// `+CRTDCP`
+    NonIpData,
+    /// `+CENG`
+    EngineeringReport,
+}
+
+/// A parsed unsolicited result code
+// There is no allocator to box the socket data
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Urc {
+    SocketData(SocketDataReceived),
+    SocketError(SocketErrorReport),
+    SocketSendAcknowledged(SocketSendAcknowledged),
+    SocketReconnect(SocketReconnect),
+    MqttMessage(MqttMessageReceived),
+    MqttDisconnected(MqttDisconnected),
+    NtpSynchronized(NtpSynchronized),
+    EpsRegistration(EpsRegistrationReport),
+    FirmwareUpdate(FirmwareUpdateProgress),
+    TimeZone(TimeZoneChanged),
+    NewMessageStored(NewMessageStored),
+    MessageReceived(MessageReceived),
+    /// The module booted and accepts AT commands
+    Ready,
+    Functionality(Functionality),
+    /// The SIM was initialized, sent after booting
+    SimStatus(PinStatus),
+    CallProgress(CallProgress),
+    Ussd(UssdReply),
+    NonIpData(NonIpDataReceived),
+    EngineeringReport(EngineeringReport),
+}
+
+impl Urc {
+    /// Parses a single line without the trailing `\r\n`. Returns `Ok(None)` if the line is not
+    /// a known URC. `+CMT` spans two lines, the header and the message have to be joined with
+    /// a single `\n`.
+    pub fn parse(line: &[u8]) -> Result<Option<Urc>, AtError> {
+        let urc = if line.starts_with(b"+CSONMI: ") {
+            Urc::SocketData(SocketDataReceived::parse(line)?)
+        } else if line.starts_with(b"+CSOERR: ") {
+            Urc::SocketError(SocketErrorReport::parse(line)?)
+        } else if line.starts_with(b"+CSOSEND: ") {
+            Urc::SocketSendAcknowledged(SocketSendAcknowledged::parse(line)?)
+        } else if line.starts_with(b"+CSORECONN: ") {
+            Urc::SocketReconnect(SocketReconnect::parse(line)?)
+        } else if line.starts_with(b"+CMQPUB: ") {
+            Urc::MqttMessage(MqttMessageReceived::parse(line)?)
+        } else if line.starts_with(b"+CMQDISCON: ") {
+            Urc::MqttDisconnected(MqttDisconnected::parse(line)?)
+        } else if line.starts_with(b"+CSNTP: ") {
+            Urc::NtpSynchronized(NtpSynchronized::parse(line)?)
+        } else if line.starts_with(b"+CEREG: ") {
+            Urc::EpsRegistration(EpsRegistrationReport::parse(line)?)
+        } else if line.starts_with(b"+CFOTA: ") {
+            Urc::FirmwareUpdate(FirmwareUpdateProgress::parse(line)?)
+        } else if line.starts_with(b"+CTZV: ") {
+            Urc::TimeZone(TimeZoneChanged::parse(line)?)
+        } else if line.starts_with(b"+CMTI: ") {
+            Urc::NewMessageStored(NewMessageStored::parse(line)?)
+        } else if line.starts_with(CMT_PREFIX) {
+            Urc::MessageReceived(MessageReceived::parse(line)?)
+        } else if line == b"RDY" {
+            Urc::Ready
+        } else if line.starts_with(b"+CFUN: ") {
+            Urc::Functionality(Functionality::parse(line)?)
+        } else if line.starts_with(b"+CPIN: ") {
+            Urc::SimStatus(PinStatus::parse(line)?)
+        } else if line.starts_with(b"+CUSD: ") {
+            Urc::Ussd(UssdReply::parse(line)?)
+        } else if line.starts_with(b"+CRTDCP: ") {
+            Urc::NonIpData(NonIpDataReceived::parse(line)?)
+        } else if line.starts_with(b"+CENG: ") {
+            Urc::EngineeringReport(EngineeringReport::parse(line)?)
+        } else if let Some(progress) = CallProgress::parse(line) {
+            Urc::CallProgress(progress)
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(urc))
+    }
+
+    pub fn kind(&self) -> UrcKind {
+        match self {
+            Urc::SocketData(_) => UrcKind::SocketData,
+            Urc::SocketError(_) => UrcKind::SocketError,
+            Urc::SocketSendAcknowledged(_) => UrcKind::SocketSendAcknowledged,
+            Urc::SocketReconnect(_) => UrcKind::SocketReconnect,
+            Urc::MqttMessage(_) => UrcKind::MqttMessage,
+            Urc::MqttDisconnected(_) => UrcKind::MqttDisconnected,
+            Urc::NtpSynchronized(_) => UrcKind::NtpSynchronized,
+            Urc::EpsRegistration(_) => UrcKind::EpsRegistration,
+            Urc::FirmwareUpdate(_) => UrcKind::FirmwareUpdate,
+            Urc::TimeZone(_) => UrcKind::TimeZone,
+            Urc::NewMessageStored(_) => UrcKind::NewMessageStored,
+            Urc::MessageReceived(_) => UrcKind::MessageReceived,
+            Urc::Ready => UrcKind::Ready,
+            Urc::Functionality(_) => UrcKind::Functionality,
+            Urc::SimStatus(_) => UrcKind::SimStatus,
+            Urc::CallProgress(_) => UrcKind::CallProgress,
+            Urc::Ussd(_) => UrcKind::Ussd,
+            Urc::NonIpData(_) => UrcKind::NonIpData,
+            Urc::EngineeringReport(_) => UrcKind::EngineeringReport,
+        }
+    }
+}
+
+/// Removes the URCs the modem interleaved in the response to `command`, e.g. a `+CEREG` sent
+/// between the `+CSOC` line of a new socket and its final OK, and hands them to `keep`.
+/// Returns the size of the remaining response, moved to the start of `response`.
+///
+/// Lines prefixed with the name of the command are part of the response even if the same
+/// prefix is used by a URC, as with `AT+CEREG?`, as are the lines accepted by `claims`. URCs
+/// without a `+` prefix, such as `RING`, cannot be told apart from data and are left in place,
+/// as is the two line `+CMT`.
+pub(crate) fn extract_interleaved_urcs(
+    command: &[u8],
+    response: &mut [u8],
+    claims: impl Fn(&[u8]) -> bool,
+    mut keep: impl FnMut(Urc),
+) -> usize {
+    let name = command.strip_prefix(b"AT").unwrap_or(command);
+    let name_end = name
+        .iter()
+        .position(|b| matches!(b, b'=' | b'?' | b'\r' | b'\n'))
+        .unwrap_or(name.len());
+    let name = &name[..name_end];
+
+    let mut read = 0;
+    let mut written = 0;
+    while read < response.len() {
+        let end = response[read..]
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(response.len(), |i| read + i + 1);
+
+        let line = trim_line_breaks(&response[read..end]);
+        let own_line =
+            !name.is_empty() && line.starts_with(name) && line.get(name.len()) == Some(&b':');
+        if line.starts_with(b"+") && !own_line && !claims(line) && !line.starts_with(CMT_PREFIX) {
+            if let Ok(Some(urc)) = Urc::parse(line) {
+                keep(urc);
+                read = end;
+                continue;
+            }
+        }
+
+        response.copy_within(read..end, written);
+        written += end - read;
+        read = end;
+    }
+
+    written
+}
+
+/// Follows the URCs sent while the modem boots: `RDY`, `+CFUN: 1` and `+CPIN: READY`, to
+/// detect when it is ready instead of waiting a fixed delay
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BootSequence {
+    pub ready: bool,
+    pub functionality: Option<Functionality>,
+    pub sim: Option<PinStatus>,
+}
+
+impl BootSequence {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a boot URC, returns whether it was one
+    pub fn observe(&mut self, urc: &Urc) -> bool {
+        match urc {
+            Urc::Ready => self.ready = true,
+            Urc::Functionality(functionality) => self.functionality = Some(*functionality),
+            Urc::SimStatus(status) => self.sim = Some(*status),
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Whether the SIM was reported, the last message of the sequence. The SIM may still
+    /// require a PIN, check [BootSequence::sim].
+    pub fn is_complete(&self) -> bool {
+        self.sim.is_some()
+    }
+}
+
+/// Any frame received from the modem, classified without knowing which request it belongs to
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DecodedFrame<'a> {
+    Urc(Urc),
+    Ok,
+    Error,
+    /// Not a known URC, or a known URC which could not be parsed. Line terminators are removed.
+    Raw(&'a [u8]),
+}
+
+/// Decodes a single frame as read from the modem, used by event driven drivers which read
+/// arbitrary lines
+pub fn decode(data: &[u8]) -> DecodedFrame<'_> {
+    let line = trim_line_breaks(data);
+    match line {
+        b"OK" => return DecodedFrame::Ok,
+        b"ERROR" => return DecodedFrame::Error,
+        _ => {}
+    }
+
+    match Urc::parse(line) {
+        Ok(Some(urc)) => DecodedFrame::Urc(urc),
+        _ => DecodedFrame::Raw(line),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::at_command::network_registration_status::NetworkRegistrationStatus;
+
+    #[test]
+    fn test_parse_socket_data() {
+        let urc = Urc::parse(b"+CSONMI: 0,4,AB01").unwrap().unwrap();
+
+        match urc {
+            Urc::SocketData(data) => {
+                assert_eq!(data.socket_id, 0);
+                assert_eq!(data.data_len, 4);
+                assert_eq!(data.data.as_slice(), b"AB01");
+            }
+            _ => panic!("Expected Urc::SocketData"),
+        }
+    }
+
+    #[test]
+    fn test_parse_socket_reconnect() {
+        match Urc::parse(b"+CSORECONN: 2,1").unwrap().unwrap() {
+            Urc::SocketReconnect(reconnect) => {
+                assert_eq!(reconnect.socket_id, 2);
+                assert!(reconnect.reconnected);
+            }
+            _ => panic!("Expected Urc::SocketReconnect"),
+        }
+    }
+
+    #[test]
+    fn test_parse_call_progress() {
+        assert_eq!(
+            Urc::parse(b"NO CARRIER").unwrap(),
+            Some(Urc::CallProgress(CallProgress::NoCarrier))
+        );
+        assert_eq!(
+            decode(b"\r\nRING\r\n"),
+            DecodedFrame::Urc(Urc::CallProgress(CallProgress::Ring))
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_line() {
+        assert!(Urc::parse(b"+CSQ: 20,99").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_parse_boot_urcs() {
+        assert_eq!(Urc::parse(b"RDY").unwrap(), Some(Urc::Ready));
+        assert_eq!(
+            Urc::parse(b"+CFUN: 1").unwrap(),
+            Some(Urc::Functionality(Functionality::Full))
+        );
+        assert_eq!(
+            Urc::parse(b"+CPIN: READY").unwrap(),
+            Some(Urc::SimStatus(PinStatus::Ready))
+        );
+        assert_eq!(
+            Urc::parse(b"+CPIN: SIM PIN").unwrap(),
+            Some(Urc::SimStatus(PinStatus::SimPin))
+        );
+    }
+
+    #[test]
+    fn test_boot_sequence() {
+        let mut boot = BootSequence::new();
+        for frame in [
+            &b"\r\nRDY\r\n"[..],
+            b"\r\n+CFUN: 1\r\n",
+            b"\r\n+CSONMI: 1,4,AB01\r\n",
+        ] {
+            if let DecodedFrame::Urc(urc) = decode(frame) {
+                boot.observe(&urc);
+            }
+            assert!(!boot.is_complete());
+        }
+
+        match decode(b"\r\n+CPIN: READY\r\n") {
+            DecodedFrame::Urc(urc) => assert!(boot.observe(&urc)),
+            _ => panic!("Expected Urc::SimStatus"),
+        }
+        assert!(boot.is_complete());
+        assert!(boot.ready);
+        assert_eq!(boot.functionality, Some(Functionality::Full));
+        assert_eq!(boot.sim, Some(PinStatus::Ready));
+    }
+
+    #[test]
+    fn test_decode_socket_data() {
+        match decode(b"\r\n+CSONMI: 1,4,AB01\r\n") {
+            DecodedFrame::Urc(Urc::SocketData(data)) => {
+                assert_eq!(data.socket_id, 1);
+                assert_eq!(data.data.as_slice(), b"AB01");
+            }
+            _ => panic!("Expected Urc::SocketData"),
+        }
+    }
+
+    #[test]
+    fn test_decode_eps_registration() {
+        match decode(b"\r\n+CEREG: 1,\"1A2B\",\"01A2B3C4\",9\r\n") {
+            DecodedFrame::Urc(Urc::EpsRegistration(report)) => {
+                assert_eq!(
+                    report.status,
+                    NetworkRegistrationStatus::RegisteredHomeNetwork
+                );
+                assert_eq!(report.tracking_area_code.unwrap().as_str(), "1A2B");
+                assert_eq!(report.cell_id.unwrap().as_str(), "01A2B3C4");
+            }
+            _ => panic!("Expected Urc::EpsRegistration"),
+        }
+    }
+
+    #[test]
+    fn test_decode_unknown_line() {
+        assert_eq!(decode(b"\r\nOK\r\n"), DecodedFrame::Ok);
+        assert_eq!(
+            decode(b"\r\n+FOO: 1,2\r\n"),
+            DecodedFrame::Raw(b"+FOO: 1,2")
+        );
+    }
+}